@@ -0,0 +1,570 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use skia_safe::{
+    AlphaType, ColorType, Data, FilterMode, Image as SkiaImage, ImageInfo, MipmapMode, Paint, Rect,
+    SamplingOptions, images, surfaces,
+};
+
+use crate::coords::{
+    LogicalPoint, LogicalSize, PhysicalPoint, PhysicalSize, PixelRect, ScaleFactor,
+};
+
+// 设置该环境变量时从 PNG 文件读取截图，便于在无显示器的环境中运行
+pub const CAPTURE_FILE_ENV: &str = "SCREENSHOT_CAPTURE_FILE";
+
+#[derive(Debug)]
+pub enum CaptureError {
+    // 没有可截取的显示器
+    NoDisplays,
+    // 截图后端失败
+    Backend(String),
+    // 截图像素无法转换或拼接
+    Image,
+    // 图片文件无法读取或解码
+    File(PathBuf, String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::NoDisplays => write!(f, "没有找到显示器"),
+            CaptureError::Backend(message) => write!(f, "截图失败: {message}"),
+            CaptureError::Image => write!(f, "无法处理截图像素"),
+            CaptureError::File(path, message) => {
+                write!(f, "无法读取图片 {}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+// 单个显示器在虚拟桌面中的位置（逻辑像素）和缩放比例
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayGeometry {
+    pub id: u32,
+    pub origin: LogicalPoint,
+    pub size: LogicalSize,
+    pub scale: ScaleFactor,
+}
+
+impl From<&display_info::DisplayInfo> for DisplayGeometry {
+    fn from(info: &display_info::DisplayInfo) -> Self {
+        Self {
+            id: info.id,
+            origin: LogicalPoint::new(info.x as f32, info.y as f32),
+            size: LogicalSize::new(info.width as f32, info.height as f32),
+            scale: ScaleFactor::new(info.scale_factor),
+        }
+    }
+}
+
+// 由所有显示器拼成的虚拟桌面
+// 画布使用所有显示器中最大的缩放比例，低 DPI 显示器的像素在拼接时放大，
+// 这样高 DPI 显示器上的内容不会损失分辨率
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualDesktop {
+    pub origin: LogicalPoint,
+    pub size: LogicalSize,
+    pub scale: ScaleFactor,
+    pub displays: Vec<DisplayGeometry>,
+}
+
+impl VirtualDesktop {
+    pub fn new(displays: Vec<DisplayGeometry>) -> Option<Self> {
+        let first = displays.first()?;
+        let (mut left, mut top) = (first.origin.x, first.origin.y);
+        let (mut right, mut bottom) = (left, top);
+        let mut scale = 0.0f32;
+
+        for display in &displays {
+            left = left.min(display.origin.x);
+            top = top.min(display.origin.y);
+            right = right.max(display.origin.x + display.size.width);
+            bottom = bottom.max(display.origin.y + display.size.height);
+            scale = scale.max(display.scale.get());
+        }
+
+        Some(Self {
+            origin: LogicalPoint::new(left, top),
+            size: LogicalSize::new(right - left, bottom - top),
+            scale: ScaleFactor::new(scale),
+            displays,
+        })
+    }
+
+    pub fn physical_size(&self) -> PhysicalSize {
+        self.size.to_physical(self.scale)
+    }
+
+    // 显示器在虚拟桌面画布上的区域（物理像素）
+    pub fn display_rect(&self, display: &DisplayGeometry) -> Rect {
+        self.canvas_rect(display.origin, display.size)
+    }
+
+    // 桌面坐标（与显示器位置相同的坐标系）中的矩形在画布上的区域（物理像素）
+    pub fn canvas_rect(&self, origin: LogicalPoint, size: LogicalSize) -> Rect {
+        let scale = self.scale.get();
+        Rect::from_xywh(
+            (origin.x - self.origin.x) * scale,
+            (origin.y - self.origin.y) * scale,
+            size.width * scale,
+            size.height * scale,
+        )
+    }
+
+    // 桌面物理坐标中的矩形在画布上的区域，只需平移到画布原点
+    pub fn physical_canvas_rect(&self, origin: PhysicalPoint, size: PhysicalSize) -> Rect {
+        let desktop_origin = self.origin.to_physical(self.scale);
+        Rect::from_xywh(
+            origin.x - desktop_origin.x,
+            origin.y - desktop_origin.y,
+            size.width_f32(),
+            size.height_f32(),
+        )
+    }
+
+    // 将各显示器的截图拼接为一张虚拟桌面图片
+    pub fn stitch(
+        &self,
+        captures: &[(DisplayGeometry, SkiaImage)],
+    ) -> Result<SkiaImage, CaptureError> {
+        let size = self.physical_size();
+        let mut surface = surfaces::raster_n32_premul((size.width as i32, size.height as i32))
+            .ok_or(CaptureError::Image)?;
+        let canvas = surface.canvas();
+
+        for (display, image) in captures {
+            canvas.draw_image_rect_with_sampling_options(
+                image,
+                None,
+                self.display_rect(display),
+                SamplingOptions::new(FilterMode::Linear, MipmapMode::None),
+                &Paint::default(),
+            );
+        }
+
+        Ok(surface.image_snapshot())
+    }
+}
+
+// 非预乘的 RGBA8888 像素转为 skia 图片
+pub fn image_from_rgba(width: u32, height: u32, rgba: &[u8]) -> Option<SkiaImage> {
+    let image_info = ImageInfo::new(
+        (width as i32, height as i32),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    );
+    images::raster_from_data(&image_info, Data::new_copy(rgba), (width * 4) as usize)
+}
+
+// 裁剪出图片上的一块像素区域
+pub fn crop_image(image: &SkiaImage, region: PixelRect) -> Option<SkiaImage> {
+    let region = PixelRect::enclosing(
+        (
+            region.left as f32,
+            region.top as f32,
+            region.right as f32,
+            region.bottom as f32,
+        ),
+        PhysicalSize::of_image(image),
+    )?;
+    let mut surface = surfaces::raster_n32_premul((region.width(), region.height()))?;
+    surface
+        .canvas()
+        .draw_image(image, (-region.left as f32, -region.top as f32), None);
+    Some(surface.image_snapshot())
+}
+
+/// 截图来源
+pub trait CaptureSource {
+    // 所有可截取的显示器，包含位置和缩放比例
+    fn outputs(&self) -> Result<Vec<DisplayGeometry>, CaptureError>;
+
+    // 截取整个显示器，返回该显示器的物理像素
+    fn capture_output(&self, output: &DisplayGeometry) -> Result<SkiaImage, CaptureError>;
+
+    // 截取显示器上的一块区域（显示器内的物理像素坐标）
+    fn capture_region(
+        &self,
+        output: &DisplayGeometry,
+        region: PixelRect,
+    ) -> Result<SkiaImage, CaptureError> {
+        let image = self.capture_output(output)?;
+        crop_image(&image, region).ok_or(CaptureError::Image)
+    }
+}
+
+/// 基于 screenshots crate 的屏幕截图
+#[derive(Debug, Default)]
+pub struct ScreenshotsCapture;
+
+impl CaptureSource for ScreenshotsCapture {
+    fn outputs(&self) -> Result<Vec<DisplayGeometry>, CaptureError> {
+        let infos = display_info::DisplayInfo::all()
+            .map_err(|err| CaptureError::Backend(err.to_string()))?;
+        Ok(infos.iter().map(DisplayGeometry::from).collect())
+    }
+
+    fn capture_output(&self, output: &DisplayGeometry) -> Result<SkiaImage, CaptureError> {
+        let screens =
+            screenshots::Screen::all().map_err(|err| CaptureError::Backend(err.to_string()))?;
+        let screen = screens
+            .iter()
+            .find(|screen| screen.display_info.id == output.id)
+            .ok_or(CaptureError::NoDisplays)?;
+        let image = screen
+            .capture()
+            .map_err(|err| CaptureError::Backend(err.to_string()))?;
+        image_from_rgba(image.width(), image.height(), image.as_raw()).ok_or(CaptureError::Image)
+    }
+}
+
+// 读取磁盘上的图片（PNG、JPEG 等 skia 支持的格式）
+pub fn load_image(path: &Path) -> Result<SkiaImage, CaptureError> {
+    let bytes =
+        std::fs::read(path).map_err(|err| CaptureError::File(path.into(), err.to_string()))?;
+    SkiaImage::from_encoded(Data::new_copy(&bytes))
+        .ok_or_else(|| CaptureError::File(path.into(), "不是支持的图片格式".into()))
+}
+
+/// 从 PNG 文件读取的“截图”，作为一个位于原点的显示器
+pub struct FileCapture {
+    path: PathBuf,
+    image: SkiaImage,
+    scale: ScaleFactor,
+}
+
+impl FileCapture {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        let path = path.as_ref().to_path_buf();
+        let image = load_image(&path)?;
+        Ok(Self {
+            path,
+            image,
+            scale: ScaleFactor::IDENTITY,
+        })
+    }
+
+    // 模拟高 DPI 显示器：图片像素按该比例对应到逻辑像素
+    pub fn with_scale(mut self, scale: ScaleFactor) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CaptureSource for FileCapture {
+    fn outputs(&self) -> Result<Vec<DisplayGeometry>, CaptureError> {
+        Ok(vec![DisplayGeometry {
+            id: 0,
+            origin: LogicalPoint::default(),
+            size: PhysicalSize::of_image(&self.image).to_logical(self.scale),
+            scale: self.scale,
+        }])
+    }
+
+    fn capture_output(&self, output: &DisplayGeometry) -> Result<SkiaImage, CaptureError> {
+        if output.id != 0 {
+            return Err(CaptureError::NoDisplays);
+        }
+        Ok(self.image.clone())
+    }
+}
+
+// 默认截图来源：设置了 SCREENSHOT_CAPTURE_FILE 时读取文件，否则截取屏幕
+pub fn default_source() -> Result<Box<dyn CaptureSource>, CaptureError> {
+    match std::env::var_os(CAPTURE_FILE_ENV) {
+        Some(path) => Ok(Box::new(FileCapture::open(path)?)),
+        None => Ok(Box::new(ScreenshotsCapture)),
+    }
+}
+
+// 一次完整的截图：虚拟桌面布局和拼接后的图片
+#[derive(Debug, Clone)]
+pub struct DesktopCapture {
+    pub desktop: VirtualDesktop,
+    pub image: SkiaImage,
+}
+
+impl DesktopCapture {
+    // 只有布局没有像素的空白截图，托盘模式启动时还没有截过图
+    pub fn blank(desktop: VirtualDesktop) -> Option<Self> {
+        let size = desktop.physical_size();
+        let mut surface = surfaces::raster_n32_premul((size.width as i32, size.height as i32))?;
+        let image = surface.image_snapshot();
+        Some(Self { desktop, image })
+    }
+}
+
+// 虚拟桌面布局，不截图
+pub fn desktop_layout(source: &dyn CaptureSource) -> Result<VirtualDesktop, CaptureError> {
+    VirtualDesktop::new(source.outputs()?).ok_or(CaptureError::NoDisplays)
+}
+
+// 截取所有显示器并拼接为虚拟桌面
+pub fn capture_desktop(source: &dyn CaptureSource) -> Result<DesktopCapture, CaptureError> {
+    let desktop = desktop_layout(source)?;
+
+    let captures = desktop
+        .displays
+        .iter()
+        .map(|display| Ok((*display, source.capture_output(display)?)))
+        .collect::<Result<Vec<_>, CaptureError>>()?;
+
+    let image = desktop.stitch(&captures)?;
+    Ok(DesktopCapture { desktop, image })
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::{Color, ISize};
+
+    use super::*;
+    use crate::{
+        export::encode_png,
+        test_support::{pixel, solid_image},
+    };
+
+    // 左半边为 left、右半边为 right 的图片
+    fn halves(width: i32, height: i32, left: Color, right: Color) -> SkiaImage {
+        let mut surface = surfaces::raster_n32_premul((width, height)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(right);
+        let mut paint = Paint::default();
+        paint.set_color(left);
+        canvas.draw_rect(Rect::from_wh(width as f32 / 2.0, height as f32), &paint);
+        surface.image_snapshot()
+    }
+
+    // 写到临时目录中的图片文件
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("capture-{}-{name}", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    // 40x30，左半边红色、右半边蓝色的 PNG
+    fn png_fixture(name: &str) -> PathBuf {
+        let image = halves(40, 30, Color::RED, Color::BLUE);
+        temp_file(name, &encode_png(&image).unwrap())
+    }
+
+    #[test]
+    fn file_capture_loads_png_at_identity_scale() {
+        let path = png_fixture("identity.png");
+        let source = FileCapture::open(&path).unwrap();
+        assert_eq!(source.path(), path);
+        assert_eq!(
+            source.outputs().unwrap(),
+            [DisplayGeometry {
+                id: 0,
+                origin: LogicalPoint::default(),
+                size: LogicalSize::new(40.0, 30.0),
+                scale: ScaleFactor::IDENTITY,
+            }]
+        );
+
+        let output = source.outputs().unwrap()[0];
+        let image = source.capture_output(&output).unwrap();
+        assert_eq!(image.dimensions(), ISize::new(40, 30));
+        assert_eq!(pixel(&image, 5, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 35, 25), [0, 0, 255, 255]);
+
+        let other = DisplayGeometry { id: 1, ..output };
+        assert!(matches!(
+            source.capture_output(&other),
+            Err(CaptureError::NoDisplays)
+        ));
+
+        // 模拟高 DPI 时图片像素不变，逻辑大小按比例缩小
+        let source = source.with_scale(ScaleFactor::new(2.0));
+        let output = source.outputs().unwrap()[0];
+        assert_eq!(output.size, LogicalSize::new(20.0, 15.0));
+        assert_eq!(
+            source.capture_output(&output).unwrap().dimensions(),
+            ISize::new(40, 30)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn capture_region_is_clamped_to_image() {
+        let path = png_fixture("region.png");
+        let source = FileCapture::open(&path).unwrap();
+        let output = source.outputs().unwrap()[0];
+        let region = |left, top, right, bottom| PixelRect {
+            left,
+            top,
+            right,
+            bottom,
+        };
+
+        let image = source
+            .capture_region(&output, region(5, 10, 25, 20))
+            .unwrap();
+        assert_eq!(image.dimensions(), ISize::new(20, 10));
+        assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 19, 9), [0, 0, 255, 255]);
+
+        // 超出图片的部分被裁掉
+        let image = source
+            .capture_region(&output, region(15, 20, 60, 50))
+            .unwrap();
+        assert_eq!(image.dimensions(), ISize::new(25, 10));
+        assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 24, 9), [0, 0, 255, 255]);
+        let image = source
+            .capture_region(&output, region(-10, -10, 10, 10))
+            .unwrap();
+        assert_eq!(image.dimensions(), ISize::new(10, 10));
+
+        // 完全在图片之外
+        assert!(matches!(
+            source.capture_region(&output, region(50, 50, 60, 60)),
+            Err(CaptureError::Image)
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn capture_desktop_reads_capture_file() {
+        let path = png_fixture("env.png");
+        // SAFETY: 测试中只有这里设置该环境变量
+        unsafe { std::env::set_var(CAPTURE_FILE_ENV, &path) };
+        let capture = default_source().and_then(|source| capture_desktop(source.as_ref()));
+        unsafe { std::env::remove_var(CAPTURE_FILE_ENV) };
+
+        let capture = capture.unwrap();
+        assert_eq!(capture.desktop.physical_size(), PhysicalSize::new(40, 30));
+        assert_eq!(capture.desktop.displays.len(), 1);
+        assert_eq!(capture.image.dimensions(), ISize::new(40, 30));
+        assert_eq!(pixel(&capture.image, 5, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(&capture.image, 35, 25), [0, 0, 255, 255]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unreadable_files_report_path() {
+        let missing = std::env::temp_dir().join("capture-missing-file.png");
+        let corrupt = temp_file("corrupt.png", b"not a png");
+        for path in [&missing, &corrupt] {
+            let err = FileCapture::open(path).err().unwrap();
+            assert!(
+                matches!(&err, CaptureError::File(error_path, _) if error_path == path),
+                "{err}"
+            );
+            assert!(err.to_string().contains(&*path.to_string_lossy()), "{err}");
+        }
+        assert!(
+            load_image(&corrupt)
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("不是支持的图片格式")
+        );
+        std::fs::remove_file(corrupt).unwrap();
+    }
+
+    // 固定显示器和截图的截图来源
+    struct Displays(Vec<(DisplayGeometry, SkiaImage)>);
+
+    impl CaptureSource for Displays {
+        fn outputs(&self) -> Result<Vec<DisplayGeometry>, CaptureError> {
+            Ok(self.0.iter().map(|(display, _)| *display).collect())
+        }
+
+        fn capture_output(&self, output: &DisplayGeometry) -> Result<SkiaImage, CaptureError> {
+            self.0
+                .iter()
+                .find(|(display, _)| display.id == output.id)
+                .map(|(_, image)| image.clone())
+                .ok_or(CaptureError::NoDisplays)
+        }
+    }
+
+    fn display(id: u32, x: f32, y: f32, width: f32, height: f32, scale: f32) -> DisplayGeometry {
+        DisplayGeometry {
+            id,
+            origin: LogicalPoint::new(x, y),
+            size: LogicalSize::new(width, height),
+            scale: ScaleFactor::new(scale),
+        }
+    }
+
+    #[test]
+    fn mixed_dpi_displays_are_stitched_at_max_scale() {
+        // 左边 1 倍的 100x50 显示器，右边 2 倍的 50x40 显示器，往下错开 10
+        let low = display(1, 0.0, 0.0, 100.0, 50.0, 1.0);
+        let high = display(2, 100.0, 10.0, 50.0, 40.0, 2.0);
+        let source = Displays(vec![
+            (low, halves(100, 50, Color::RED, Color::WHITE)),
+            (high, halves(100, 80, Color::GREEN, Color::BLUE)),
+        ]);
+
+        let capture = capture_desktop(&source).unwrap();
+        let desktop = &capture.desktop;
+        assert_eq!(desktop.origin, LogicalPoint::new(0.0, 0.0));
+        assert_eq!(desktop.size, LogicalSize::new(150.0, 50.0));
+        assert_eq!(desktop.scale, ScaleFactor::new(2.0));
+        assert_eq!(desktop.physical_size(), PhysicalSize::new(300, 100));
+        assert_eq!(capture.image.dimensions(), ISize::new(300, 100));
+
+        assert_eq!(
+            desktop.display_rect(&low),
+            Rect::from_xywh(0.0, 0.0, 200.0, 100.0)
+        );
+        assert_eq!(
+            desktop.display_rect(&high),
+            Rect::from_xywh(200.0, 20.0, 100.0, 80.0)
+        );
+
+        // 1 倍显示器放大两倍：原图 x = 50 的分界线在画布上的 x = 100
+        assert_eq!(pixel(&capture.image, 90, 50), [255, 0, 0, 255]);
+        assert_eq!(pixel(&capture.image, 110, 50), [255, 255, 255, 255]);
+        // 2 倍显示器按原始像素放在 (200, 20)
+        assert_eq!(pixel(&capture.image, 240, 60), [0, 255, 0, 255]);
+        assert_eq!(pixel(&capture.image, 260, 60), [0, 0, 255, 255]);
+        // 两个显示器都没有覆盖的区域保持透明
+        assert_eq!(pixel(&capture.image, 250, 10)[3], 0);
+    }
+
+    #[test]
+    fn desktop_origin_follows_leftmost_display() {
+        // 副屏在主屏左上方
+        let primary = display(1, 0.0, 0.0, 200.0, 100.0, 1.0);
+        let secondary = display(2, -100.0, -50.0, 100.0, 50.0, 1.0);
+        let source = Displays(vec![
+            (primary, solid_image(200, 100, Color::RED)),
+            (secondary, solid_image(100, 50, Color::BLUE)),
+        ]);
+
+        let capture = capture_desktop(&source).unwrap();
+        let desktop = &capture.desktop;
+        assert_eq!(desktop.origin, LogicalPoint::new(-100.0, -50.0));
+        assert_eq!(desktop.physical_size(), PhysicalSize::new(300, 150));
+        assert_eq!(
+            desktop.display_rect(&primary),
+            Rect::from_xywh(100.0, 50.0, 200.0, 100.0)
+        );
+        assert_eq!(pixel(&capture.image, 50, 25), [0, 0, 255, 255]);
+        assert_eq!(pixel(&capture.image, 150, 100), [255, 0, 0, 255]);
+        assert_eq!(pixel(&capture.image, 50, 100)[3], 0);
+    }
+
+    #[test]
+    fn desktop_needs_a_display() {
+        assert_eq!(VirtualDesktop::new(Vec::new()), None);
+        assert!(matches!(
+            capture_desktop(&Displays(Vec::new())),
+            Err(CaptureError::NoDisplays)
+        ));
+    }
+}
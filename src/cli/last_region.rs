@@ -0,0 +1,38 @@
+use std::{io, path::PathBuf};
+
+use crate::{
+    cli::{format_rect, parse_rect},
+    types::ui::Selection,
+};
+
+const LAST_REGION_FILE: &str = "last-region";
+
+// 状态目录：优先 XDG_STATE_HOME，其次 ~/.local/state，Windows 上使用 LOCALAPPDATA
+fn state_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+    Some(base.join(env!("CARGO_PKG_NAME")))
+}
+
+fn last_region_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(LAST_REGION_FILE))
+}
+
+// 读取上一次截图使用的区域（桌面截图上的物理像素）
+pub fn load_last_region() -> Option<Selection> {
+    let text = std::fs::read_to_string(last_region_path()?).ok()?;
+    parse_rect(text.trim())
+}
+
+pub fn save_last_region(selection: &Selection) -> io::Result<()> {
+    let path = last_region_path().ok_or_else(|| io::Error::other("无法确定状态目录"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, format_rect(selection))
+}
@@ -0,0 +1,523 @@
+pub mod last_region;
+
+use std::{
+    ffi::OsString,
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use skia_safe::Image as SkiaImage;
+
+use crate::{
+    capture::DesktopCapture,
+    clipboard::{ClipboardError, SystemClipboard, copy_selection},
+    config::CaptureAction,
+    coords::PhysicalSize,
+    export::{OutputLocation, export_png},
+    hotkey::{HotkeyBinding, HotkeyError},
+    types::{drawing::DrawingShape, ui::Selection},
+};
+
+pub const USAGE: &str = "\
+用法: freya_test [模式] [选项] [图片]
+
+模式:
+  region              交互式框选区域（默认）
+  fullscreen          截取整个桌面
+  monitor <N>         截取第 N 个显示器（从 0 开始）
+  rect <x,y,w,h>      截取固定区域（桌面截图上的物理像素）
+  last-region         截取上一次使用的区域
+
+选项:
+  -o, --output <路径>  保存为 PNG 文件
+  -c, --clipboard      复制到剪贴板（Linux 上由后台进程持有，直到剪贴板内容被替换）
+      --stdout         将 PNG 写到标准输出
+                       交互式框选时按 Enter 或保存按钮都会写到以上目标
+  -d, --delay <秒>     截图前等待
+      --tray           常驻系统托盘，从托盘菜单或全局热键截图
+      --hotkey <动作=热键>
+                       托盘模式的全局热键，可重复指定，例如 region=Ctrl+Shift+A
+                       动作为 region、fullscreen、last-region
+                       默认 PrintScreen、Shift+PrintScreen、Ctrl+PrintScreen
+  -h, --help           显示帮助
+
+图片:
+  指定图片路径时打开该图片进行标注，而不是截取屏幕
+
+配置文件:
+  $XDG_CONFIG_HOME/freya_test/config.toml（默认 ~/.config/freya_test/config.toml）
+  可设置标注样式、保存目录和文件名、遮罩颜色、编辑器快捷键、全局热键和截图后的动作
+  截图时按 ? 查看所有快捷键";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureMode {
+    Region,
+    Fullscreen,
+    Monitor(usize),
+    Rect(Selection),
+    LastRegion,
+}
+
+impl CaptureMode {
+    // 除交互式框选外的模式都不显示遮罩窗口
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, CaptureMode::Region)
+    }
+}
+
+// 截图完成后的输出目标，location 为未指定任何目标时的保存位置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputTargets {
+    pub path: Option<PathBuf>,
+    pub clipboard: bool,
+    pub stdout: bool,
+    pub location: OutputLocation,
+}
+
+impl OutputTargets {
+    pub fn is_empty(&self) -> bool {
+        self.path.is_none() && !self.clipboard && !self.stdout
+    }
+
+    // 按 Enter 确认时的动作：指定了输出目标时由保存动作写到所有目标，
+    // 否则执行配置文件中的动作
+    pub fn confirm_actions(&self, configured: &[CaptureAction]) -> Vec<CaptureAction> {
+        if self.is_empty() {
+            configured.to_vec()
+        } else {
+            vec![CaptureAction::Save]
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub mode: CaptureMode,
+    pub outputs: OutputTargets,
+    pub delay: Duration,
+    pub image: Option<PathBuf>,
+    // 常驻托盘，截图后隐藏遮罩而不退出
+    pub tray: bool,
+    // 命令行指定的全局热键，替换同一动作的默认热键
+    pub hotkeys: Vec<HotkeyBinding>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Run(Options),
+    Help,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    // 命令行参数错误
+    Usage(String),
+    // 选择区域为空或合成失败
+    Render,
+    Io(io::Error),
+    Clipboard(ClipboardError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{message}"),
+            CliError::Render => write!(f, "无法将选择区域编码为 PNG"),
+            CliError::Io(err) => write!(f, "写入失败: {err}"),
+            CliError::Clipboard(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Io(err)
+    }
+}
+
+impl From<HotkeyError> for CliError {
+    fn from(err: HotkeyError) -> Self {
+        CliError::Usage(err.to_string())
+    }
+}
+
+impl From<ClipboardError> for CliError {
+    fn from(err: ClipboardError) -> Self {
+        CliError::Clipboard(err)
+    }
+}
+
+fn usage_error(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+fn next_value(args: &mut impl Iterator<Item = OsString>, flag: &str) -> Result<OsString, CliError> {
+    args.next()
+        .ok_or_else(|| usage_error(format!("{flag} 缺少参数")))
+}
+
+// 解析 x,y,w,h 形式的矩形
+pub fn parse_rect(value: &str) -> Option<Selection> {
+    let parts = value
+        .split(',')
+        .map(|part| part.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let [x, y, width, height] = parts.as_slice() else {
+        return None;
+    };
+    if !(x.is_finite() && y.is_finite() && *width > 0.0 && *height > 0.0) {
+        return None;
+    }
+    Some(Selection {
+        start: (*x, *y),
+        end: (x + width, y + height),
+    })
+}
+
+pub fn format_rect(selection: &Selection) -> String {
+    let (left, top, _, _) = selection.bounds();
+    let (width, height) = selection.size();
+    format!("{left},{top},{width},{height}")
+}
+
+// 解析命令行参数（不含程序名）
+pub fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<CliCommand, CliError> {
+    let mut args = args.into_iter();
+    let mut mode = None;
+    let mut outputs = OutputTargets::default();
+    let mut delay = Duration::ZERO;
+    let mut image = None;
+    let mut tray = false;
+    let mut hotkeys = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let Some(text) = arg.to_str() else {
+            // 非 UTF-8 参数只可能是图片路径
+            image = Some(PathBuf::from(arg));
+            continue;
+        };
+
+        match text {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-o" | "--output" => {
+                outputs.path = Some(PathBuf::from(next_value(&mut args, text)?));
+            }
+            "-c" | "--clipboard" => outputs.clipboard = true,
+            "--stdout" => outputs.stdout = true,
+            "--tray" => tray = true,
+            "--hotkey" => {
+                let value = next_value(&mut args, text)?;
+                let binding = value
+                    .to_str()
+                    .ok_or_else(|| usage_error(format!("无效的热键: {value:?}")))?
+                    .parse::<HotkeyBinding>()?;
+                hotkeys.push(binding);
+            }
+            "-d" | "--delay" => {
+                let value = next_value(&mut args, text)?;
+                let seconds = value
+                    .to_str()
+                    .and_then(|value| value.parse::<f64>().ok())
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| usage_error(format!("无效的等待时间: {value:?}")))?;
+                delay = Duration::from_secs_f64(seconds);
+            }
+            "region" | "fullscreen" | "monitor" | "rect" | "last-region" if mode.is_none() => {
+                mode = Some(match text {
+                    "fullscreen" => CaptureMode::Fullscreen,
+                    "last-region" => CaptureMode::LastRegion,
+                    "monitor" => {
+                        let value = next_value(&mut args, text)?;
+                        let index = value
+                            .to_str()
+                            .and_then(|value| value.parse::<usize>().ok())
+                            .ok_or_else(|| usage_error(format!("无效的显示器编号: {value:?}")))?;
+                        CaptureMode::Monitor(index)
+                    }
+                    "rect" => {
+                        let value = next_value(&mut args, text)?;
+                        let rect = value.to_str().and_then(parse_rect).ok_or_else(|| {
+                            usage_error(format!("无效的区域: {value:?}，应为 x,y,w,h"))
+                        })?;
+                        CaptureMode::Rect(rect)
+                    }
+                    _ => CaptureMode::Region,
+                });
+            }
+            "region" | "fullscreen" | "monitor" | "rect" | "last-region" => {
+                return Err(usage_error(format!("只能指定一种模式: {text}")));
+            }
+            flag if flag.starts_with('-') => {
+                return Err(usage_error(format!("未知选项: {flag}")));
+            }
+            _ if image.is_none() => image = Some(PathBuf::from(text)),
+            _ => return Err(usage_error(format!("多余的参数: {text}"))),
+        }
+    }
+
+    let mode = mode.unwrap_or(CaptureMode::Region);
+    if image.is_some() && !mode.is_interactive() {
+        return Err(usage_error("打开图片时只能使用 region 模式"));
+    }
+    if tray && (image.is_some() || !mode.is_interactive() || outputs.stdout || !delay.is_zero()) {
+        return Err(usage_error(
+            "托盘模式不能指定图片、非 region 模式、--stdout 或 --delay",
+        ));
+    }
+    if !tray && !hotkeys.is_empty() {
+        return Err(usage_error("--hotkey 只能在托盘模式下使用"));
+    }
+
+    Ok(CliCommand::Run(Options {
+        mode,
+        outputs,
+        delay,
+        image,
+        tray,
+        hotkeys,
+    }))
+}
+
+// 非交互模式使用的固定区域；交互式框选返回 None
+pub fn fixed_selection(
+    mode: &CaptureMode,
+    capture: &DesktopCapture,
+) -> Result<Option<Selection>, CliError> {
+    let selection = match mode {
+        CaptureMode::Region => return Ok(None),
+        CaptureMode::Fullscreen => Selection::covering(PhysicalSize::of_image(&capture.image)),
+        CaptureMode::Monitor(index) => {
+            let display = capture
+                .desktop
+                .displays
+                .get(*index)
+                .ok_or_else(|| usage_error(format!("没有编号为 {index} 的显示器")))?;
+            let rect = capture.desktop.display_rect(display);
+            Selection {
+                start: (rect.left, rect.top),
+                end: (rect.right, rect.bottom),
+            }
+        }
+        CaptureMode::Rect(selection) => *selection,
+        CaptureMode::LastRegion => {
+            last_region::load_last_region().ok_or_else(|| usage_error("没有上一次的截图区域"))?
+        }
+    };
+    Ok(Some(selection))
+}
+
+// 将选择区域合成后写到所有输出目标，未指定目标时按配置的目录和文件名保存
+// 返回写出的文件路径
+pub fn write_outputs(
+    targets: &OutputTargets,
+    image: &SkiaImage,
+    selection: &Selection,
+    shapes: &[DrawingShape],
+) -> Result<Option<PathBuf>, CliError> {
+    let path = match (&targets.path, targets.is_empty()) {
+        (Some(path), _) => Some(path.clone()),
+        (None, true) => Some(targets.location.path()),
+        (None, false) => None,
+    };
+
+    if path.is_some() || targets.stdout {
+        let bytes = export_png(image, selection, shapes).ok_or(CliError::Render)?;
+        if let Some(path) = &path {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, &bytes)?;
+        }
+        if targets.stdout {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        }
+    }
+
+    if targets.clipboard {
+        let mut clipboard = SystemClipboard::new()?;
+        copy_selection(&mut clipboard, image, selection, shapes)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hotkey::HotkeyAction;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, CliError> {
+        parse_args(args.iter().map(OsString::from))
+    }
+
+    fn options(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(CliCommand::Run(options)) => options,
+            other => panic!("{args:?}: {other:?}"),
+        }
+    }
+
+    fn usage_message(args: &[&str]) -> String {
+        match parse(args) {
+            Err(CliError::Usage(message)) => message,
+            other => panic!("{args:?}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_rect_accepts_position_and_size() {
+        let cases = [
+            ("10,20,300,200", (10.0, 20.0), (310.0, 220.0)),
+            (" 1.5 , 2 ,3,4 ", (1.5, 2.0), (4.5, 6.0)),
+            // 多显示器时虚拟桌面的坐标可以为负
+            ("-1920,-100,50,60", (-1920.0, -100.0), (-1870.0, -40.0)),
+        ];
+        for (input, start, end) in cases {
+            assert_eq!(parse_rect(input), Some(Selection { start, end }), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_rect_rejects_malformed_input() {
+        let cases = [
+            "",
+            "1,2,3",
+            "1,2,3,4,5",
+            "a,2,3,4",
+            "1;2;3;4",
+            "1,2,0,4",
+            "1,2,3,-4",
+            "NaN,0,1,1",
+            "inf,0,1,1",
+        ];
+        for input in cases {
+            assert_eq!(parse_rect(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn format_rect_round_trips() {
+        let selection = parse_rect("10,20,300,200").unwrap();
+        assert_eq!(format_rect(&selection), "10,20,300,200");
+        assert_eq!(parse_rect(&format_rect(&selection)), Some(selection));
+    }
+
+    #[test]
+    fn parse_args_defaults_to_interactive_region() {
+        let options = options(&[]);
+        assert_eq!(options.mode, CaptureMode::Region);
+        assert!(options.outputs.is_empty());
+        assert_eq!(options.delay, Duration::ZERO);
+        assert_eq!(options.image, None);
+        assert!(!options.tray);
+        assert!(options.hotkeys.is_empty());
+    }
+
+    #[test]
+    fn confirm_uses_command_line_targets() {
+        let configured = [CaptureAction::Copy];
+        assert_eq!(
+            options(&[]).outputs.confirm_actions(&configured),
+            [CaptureAction::Copy]
+        );
+        for args in [&["-o", "shot.png"][..], &["-c"], &["--stdout"]] {
+            assert_eq!(
+                options(args).outputs.confirm_actions(&configured),
+                [CaptureAction::Save],
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_args_accepts_valid_combinations() {
+        let options = options(&["fullscreen", "-c", "--stdout"]);
+        assert_eq!(options.mode, CaptureMode::Fullscreen);
+        assert!(options.outputs.clipboard && options.outputs.stdout);
+
+        let options = options(&["monitor", "1", "--output", "shot.png"]);
+        assert_eq!(options.mode, CaptureMode::Monitor(1));
+        assert_eq!(options.outputs.path, Some(PathBuf::from("shot.png")));
+
+        let options = options(&["rect", "0,0,10,10", "-d", "1.5"]);
+        assert_eq!(
+            options.mode,
+            CaptureMode::Rect(parse_rect("0,0,10,10").unwrap())
+        );
+        assert_eq!(options.delay, Duration::from_millis(1500));
+
+        let options = options(&["last-region", "-o", "out/last.png"]);
+        assert_eq!(options.mode, CaptureMode::LastRegion);
+        assert_eq!(options.outputs.path, Some(PathBuf::from("out/last.png")));
+
+        let options = options(&["picture.png"]);
+        assert_eq!(options.mode, CaptureMode::Region);
+        assert_eq!(options.image, Some(PathBuf::from("picture.png")));
+
+        let options = options(&["--tray", "--hotkey", "fullscreen=Ctrl+Shift+A"]);
+        assert!(options.tray);
+        assert_eq!(options.hotkeys.len(), 1);
+        assert_eq!(options.hotkeys[0].action, HotkeyAction::CaptureFullscreen);
+    }
+
+    #[test]
+    fn help_wins_over_other_arguments() {
+        for args in [
+            &["-h"][..],
+            &["--help"],
+            &["fullscreen", "--help"],
+            &["-c", "-h"],
+        ] {
+            assert!(matches!(parse(args), Ok(CliCommand::Help)), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn parse_args_rejects_malformed_arguments() {
+        let cases: [&[&str]; 10] = [
+            &["-o"],
+            &["monitor"],
+            &["monitor", "first"],
+            &["rect", "1,2,3"],
+            &["-d", "-1"],
+            &["-d", "soon"],
+            &["--bogus"],
+            &["a.png", "b.png"],
+            &["--tray", "--hotkey", "region"],
+            &["--tray", "--hotkey", "scroll=Ctrl+A"],
+        ];
+        for args in cases {
+            usage_message(args);
+        }
+    }
+
+    #[test]
+    fn parse_args_rejects_conflicting_arguments() {
+        let cases: [(&[&str], &str); 7] = [
+            (&["region", "fullscreen"], "只能指定一种模式"),
+            (&["monitor", "0", "rect", "0,0,1,1"], "只能指定一种模式"),
+            (
+                &["picture.png", "fullscreen"],
+                "打开图片时只能使用 region 模式",
+            ),
+            (&["--tray", "--stdout"], "托盘模式"),
+            (&["--tray", "-d", "2"], "托盘模式"),
+            (&["--tray", "picture.png"], "托盘模式"),
+            (
+                &["--hotkey", "region=Ctrl+Shift+A"],
+                "--hotkey 只能在托盘模式下使用",
+            ),
+        ];
+        for (args, expected) in cases {
+            let message = usage_message(args);
+            assert!(message.contains(expected), "{args:?}: {message}");
+        }
+    }
+}
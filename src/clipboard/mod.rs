@@ -0,0 +1,399 @@
+use std::{borrow::Cow, fmt};
+#[cfg(target_os = "linux")]
+use std::{
+    io::{self, BufRead, Read, Write},
+    process::Child,
+    sync::Mutex,
+};
+
+use skia_safe::Image as SkiaImage;
+
+use crate::{
+    editor::Editor,
+    export::{read_rgba, render_selection},
+    types::{drawing::DrawingShape, ui::Selection},
+};
+
+// 剪贴板中的图片，像素为非预乘的 RGBA8888
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum ClipboardError {
+    // 选择区域为空或离屏合成失败
+    Render,
+    // 系统剪贴板不可用或写入失败
+    Backend(String),
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardError::Render => write!(f, "无法合成选择区域"),
+            ClipboardError::Backend(message) => write!(f, "剪贴板错误: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+pub trait Clipboard {
+    fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError>;
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+}
+
+/// 系统剪贴板
+pub struct SystemClipboard {
+    // Linux 上每次写入都交给子进程，见 hand_over
+    #[cfg(not(target_os = "linux"))]
+    inner: arboard::Clipboard,
+}
+
+// 写入系统剪贴板的内容
+#[derive(Debug, Clone)]
+enum Data {
+    Image(arboard::ImageData<'static>),
+    Text(String),
+}
+
+fn write_to(set: arboard::Set<'_>, data: Data) -> Result<(), arboard::Error> {
+    match data {
+        Data::Image(image) => set.image(image),
+        Data::Text(text) => set.text(text),
+    }
+}
+
+impl SystemClipboard {
+    #[cfg(target_os = "linux")]
+    pub fn new() -> Result<Self, ClipboardError> {
+        Ok(Self {})
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn new() -> Result<Self, ClipboardError> {
+        let inner =
+            arboard::Clipboard::new().map_err(|err| ClipboardError::Backend(err.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn write(&mut self, data: Data) -> Result<(), ClipboardError> {
+        hand_over(data)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn write(&mut self, data: Data) -> Result<(), ClipboardError> {
+        write_to(self.inner.set(), data).map_err(|err| ClipboardError::Backend(err.to_string()))
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError> {
+        self.write(Data::Image(arboard::ImageData {
+            width: image.width,
+            height: image.height,
+            bytes: Cow::Owned(image.rgba),
+        }))
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.write(Data::Text(text.to_owned()))
+    }
+}
+
+// X11 和 Wayland 的剪贴板内容由写入的进程提供，进程退出后内容就丢失了
+// 因此和 xclip 一样交给一个子进程持有，直到其他程序替换剪贴板，调用方可以直接退出
+// 设置该环境变量启动的进程只运行 serve_handover
+#[cfg(target_os = "linux")]
+pub const HANDOVER_ENV: &str = "SCREENSHOT_CLIPBOARD_HANDOVER";
+
+// 持有剪贴板的子进程，托盘模式下需要回收已经退出的
+#[cfg(target_os = "linux")]
+static OWNERS: Mutex<Vec<Child>> = Mutex::new(Vec::new());
+
+#[cfg(target_os = "linux")]
+fn hand_over(data: Data) -> Result<(), ClipboardError> {
+    use std::{
+        io::BufReader,
+        os::unix::process::CommandExt,
+        process::{Command, Stdio},
+    };
+
+    let backend = |err: io::Error| ClipboardError::Backend(err.to_string());
+    let mut child = Command::new(std::env::current_exe().map_err(backend)?)
+        .env(HANDOVER_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        // 不占用调用方的标准错误，否则 $(...) 要等子进程退出才返回
+        .stderr(Stdio::null())
+        // 单独的进程组，在终端中按 Ctrl+C 不会结束它
+        .process_group(0)
+        .spawn()
+        .map_err(backend)?;
+
+    let sent = child
+        .stdin
+        .take()
+        .map_or(Ok(()), |mut stdin| write_data(&mut stdin, &data));
+    // 子进程写入剪贴板后报告 ok，否则报告错误信息
+    let mut status = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        BufReader::new(stdout)
+            .read_line(&mut status)
+            .map_err(backend)?;
+    }
+
+    let mut owners = OWNERS.lock().unwrap_or_else(|err| err.into_inner());
+    owners.retain_mut(|owner| matches!(owner.try_wait(), Ok(None)));
+    owners.push(child);
+    match status.trim_end() {
+        "ok" => Ok(()),
+        "" => Err(sent.err().map_or_else(
+            || ClipboardError::Backend("剪贴板进程意外退出".into()),
+            backend,
+        )),
+        message => Err(ClipboardError::Backend(message.into())),
+    }
+}
+
+// hand_over 启动的子进程：从标准输入读取内容写入剪贴板，在标准输出报告结果，
+// 之后一直持有，直到剪贴板内容被替换（包括再次复制）
+#[cfg(target_os = "linux")]
+pub fn serve_handover() {
+    use arboard::SetExtLinux;
+
+    let result = read_data(&mut io::stdin().lock())
+        .map_err(|err| err.to_string())
+        .and_then(|data| {
+            let mut clipboard = arboard::Clipboard::new().map_err(|err| err.to_string())?;
+            // 先写入一次，报告 ok 时剪贴板已经是新内容
+            write_to(clipboard.set(), data.clone()).map_err(|err| err.to_string())?;
+            Ok((clipboard, data))
+        });
+    match result {
+        Ok((mut clipboard, data)) => {
+            let _ = writeln!(io::stdout(), "ok");
+            // 再以等待模式写入同样的内容，被替换时才返回
+            let _ = write_to(clipboard.set().wait(), data);
+        }
+        Err(message) => {
+            let _ = writeln!(io::stdout(), "{message}");
+        }
+    }
+}
+
+// 交给子进程的内容：一行头部（image 宽 高，或 text），之后是原始字节
+#[cfg(target_os = "linux")]
+fn write_data(writer: &mut impl Write, data: &Data) -> io::Result<()> {
+    match data {
+        Data::Image(image) => {
+            writeln!(writer, "image {} {}", image.width, image.height)?;
+            writer.write_all(&image.bytes)
+        }
+        Data::Text(text) => {
+            writeln!(writer, "text")?;
+            writer.write_all(text.as_bytes())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_data(reader: &mut impl BufRead) -> io::Result<Data> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "无效的剪贴板内容");
+    let mut header = String::new();
+    reader.read_line(&mut header)?;
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body)?;
+
+    match header.split_whitespace().collect::<Vec<_>>()[..] {
+        ["image", width, height] => {
+            let width = width.parse::<usize>().map_err(|_| invalid())?;
+            let height = height.parse::<usize>().map_err(|_| invalid())?;
+            if width
+                .checked_mul(height)
+                .and_then(|pixels| pixels.checked_mul(4))
+                != Some(body.len())
+            {
+                return Err(invalid());
+            }
+            Ok(Data::Image(arboard::ImageData {
+                width,
+                height,
+                bytes: Cow::Owned(body),
+            }))
+        }
+        ["text"] => String::from_utf8(body)
+            .map(Data::Text)
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// 内存剪贴板，用于测试
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    pub image: Option<ClipboardImage>,
+    pub text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError> {
+        self.image = Some(image);
+        Ok(())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.text = Some(text.to_owned());
+        Ok(())
+    }
+}
+
+// 与文件导出共用离屏合成路径，而不是读取屏幕上的画布
+pub fn copy_selection(
+    clipboard: &mut dyn Clipboard,
+    image: &SkiaImage,
+    selection: &Selection,
+    shapes: &[DrawingShape],
+) -> Result<(), ClipboardError> {
+    let rendered = render_selection(image, selection, shapes).ok_or(ClipboardError::Render)?;
+    let rgba = read_rgba(&rendered).ok_or(ClipboardError::Render)?;
+
+    clipboard.set_image(ClipboardImage {
+        width: rendered.width() as usize,
+        height: rendered.height() as usize,
+        rgba,
+    })
+}
+
+// 复制取色器取到的颜色，按当前的颜色格式写为文字；还没有取色时返回 None
+pub fn copy_picked_color(
+    clipboard: &mut dyn Clipboard,
+    editor: &Editor,
+) -> Result<Option<String>, ClipboardError> {
+    let Some(text) = editor.picked_color_text() else {
+        return Ok(None);
+    };
+    clipboard.set_text(&text)?;
+    Ok(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::Color;
+
+    use super::*;
+    use crate::{
+        editor::{EditorAction, EditorKey, EditorSettings, KeyModifiers, PointerButton},
+        test_support::solid_image,
+    };
+
+    #[test]
+    fn copy_selection_writes_rendered_pixels() {
+        let image = solid_image(40, 30, Color::GREEN);
+        let selection = Selection {
+            start: (5.0, 5.0),
+            end: (25.0, 15.0),
+        };
+
+        let mut clipboard = MemoryClipboard::default();
+        copy_selection(&mut clipboard, &image, &selection, &[]).unwrap();
+
+        let copied = clipboard.image.unwrap();
+        assert_eq!((copied.width, copied.height), (20, 10));
+        assert_eq!(copied.rgba.len(), 20 * 10 * 4);
+        assert!(
+            copied
+                .rgba
+                .chunks_exact(4)
+                .all(|pixel| pixel == [0, 255, 0, 255])
+        );
+        assert!(clipboard.text.is_none());
+    }
+
+    #[test]
+    fn copy_empty_selection_fails() {
+        let image = solid_image(40, 30, Color::GREEN);
+        let selection = Selection {
+            start: (10.0, 10.0),
+            end: (10.0, 20.0),
+        };
+
+        let mut clipboard = MemoryClipboard::default();
+        let result = copy_selection(&mut clipboard, &image, &selection, &[]);
+        assert!(matches!(result, Err(ClipboardError::Render)));
+        assert!(clipboard.image.is_none());
+    }
+
+    #[test]
+    fn picked_color_is_copied_as_text() {
+        let image = solid_image(40, 30, Color::from_rgb(255, 59, 48));
+        let mut editor = Editor::new(image, None, &EditorSettings::default());
+        let mut clipboard = MemoryClipboard::default();
+        let press = |editor: &mut Editor, c: &str| {
+            editor.key(&EditorKey::Character(c.into()), KeyModifiers::default());
+        };
+
+        // 还没有取色时不复制
+        assert_eq!(copy_picked_color(&mut clipboard, &editor).unwrap(), None);
+        assert!(clipboard.text.is_none());
+
+        press(&mut editor, "i");
+        let response = editor.pointer_down((10.0, 10.0), PointerButton::Primary);
+        assert_eq!(response.action, Some(EditorAction::CopyColor));
+        let copied = copy_picked_color(&mut clipboard, &editor).unwrap();
+        assert_eq!(copied.as_deref(), Some("#FF3B30"));
+        assert_eq!(clipboard.text.as_deref(), Some("#FF3B30"));
+
+        // 复制的文字跟随当前的颜色格式
+        press(&mut editor, "f");
+        copy_picked_color(&mut clipboard, &editor).unwrap();
+        assert_eq!(clipboard.text.as_deref(), Some("rgb(255, 59, 48)"));
+        assert!(clipboard.image.is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn handover_data_round_trip() {
+        let image = Data::Image(arboard::ImageData {
+            width: 2,
+            height: 1,
+            bytes: Cow::Owned(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        });
+        let mut buffer = Vec::new();
+        write_data(&mut buffer, &image).unwrap();
+        match read_data(&mut buffer.as_slice()).unwrap() {
+            Data::Image(image) => {
+                assert_eq!((image.width, image.height), (2, 1));
+                assert_eq!(&image.bytes[..], [1, 2, 3, 4, 5, 6, 7, 8]);
+            }
+            data => panic!("{data:?}"),
+        }
+
+        // 文字中的换行原样保留
+        let mut buffer = Vec::new();
+        write_data(&mut buffer, &Data::Text("#FF3B30\n".into())).unwrap();
+        match read_data(&mut buffer.as_slice()).unwrap() {
+            Data::Text(text) => assert_eq!(text, "#FF3B30\n"),
+            data => panic!("{data:?}"),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn invalid_handover_data_is_rejected() {
+        let cases: [&[u8]; 5] = [
+            b"",
+            b"image 2 1\n1234",
+            b"image x 1\n12345678",
+            b"text\n\xff",
+            b"html\n<b>",
+        ];
+        for input in cases {
+            assert!(read_data(&mut &input[..]).is_err(), "{input:?}");
+        }
+    }
+}
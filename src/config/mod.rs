@@ -0,0 +1,589 @@
+pub mod watch;
+
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use skia_safe::Color;
+
+use crate::{
+    editor::{EditorCommand, EditorSettings, KeyChord},
+    export::{OutputLocation, validate_filename_template},
+    hotkey::{HotkeyAction, HotkeyBinding},
+    shapes::{
+        redact::{MAX_BLUR_SIGMA, MAX_MOSAIC_BLOCK_SIZE, MIN_BLUR_SIGMA, MIN_MOSAIC_BLOCK_SIZE},
+        style::parse_hex_color,
+    },
+    types::ui::SelectionRatio,
+};
+
+const CONFIG_FILE: &str = "config.toml";
+
+const STROKE_WIDTH_RANGE: RangeInclusive<f32> = 1.0..=64.0;
+const FONT_SIZE_RANGE: RangeInclusive<f32> = 6.0..=200.0;
+
+// 首次打开设置时写入的配置文件，所有项都注释掉，取值即为默认值
+pub const DEFAULT_CONFIG: &str = r##"# 截图工具配置，修改后托盘模式会自动重新加载
+
+[style]
+# 新建标注的颜色（RGB、RRGGBB 或 RRGGBBAA）和线宽
+# color = "#ff3b30"
+# stroke_width = 4
+# 文字字号，以及是否给文字加背景
+# font_size = 20
+# text_background = false
+# 马赛克块大小和模糊强度
+# mosaic_block_size = 12
+# blur_sigma = 12
+
+[output]
+# 未指定 -o 时保存截图的目录，默认为当前目录；~ 表示主目录
+# dir = "~/Pictures/Screenshots"
+# 文件名模板，可用 {timestamp}、{date}、{time}（UTC），自动补上 .png
+# filename = "screenshot_{timestamp}"
+
+[overlay]
+# 选择框以外的遮罩颜色
+# mask_color = "#000000a0"
+
+[selection]
+# 工具栏比例按钮在 16:9、4:3、1:1 之外的自定义比例，以及只能移动位置的固定大小
+# ratio = "21:9"
+# fixed_size = "1280x720"
+
+[keys]
+# 编辑器快捷键，一个命令可以写多个快捷键，写成空数组表示取消绑定
+# 命令: rectangle ellipse arrow brush text mosaic blur undo redo save copy paste
+#       duplicate confirm delete nudge-left nudge-right nudge-up nudge-down
+#       grow-left grow-right grow-up grow-down shrink-left shrink-right shrink-up
+#       shrink-down selection-input toggle-handles cancel weaker-redaction stronger-redaction color-picker
+#       color-format sample-size help
+# rectangle = "R"
+# redo = ["Ctrl+Shift+Z", "Ctrl+Y"]
+# help = "?"
+
+[hotkeys]
+# 托盘模式的全局热键，命令行的 --hotkey 优先
+# region = "PrintScreen"
+# fullscreen = "Shift+PrintScreen"
+# last-region = "Ctrl+PrintScreen"
+
+[after_capture]
+# 按 Enter 确认选择后执行的动作：copy、save
+# 命令行指定了 --output、--clipboard 或 --stdout 时 Enter 改为写到这些目标
+# confirm = ["copy"]
+# 完成后关闭遮罩（托盘模式下隐藏），为 false 时可以继续标注
+# close = true
+"##;
+
+// 确认选择后可执行的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureAction {
+    Save,
+    Copy,
+}
+
+impl CaptureAction {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "save" => Some(CaptureAction::Save),
+            "copy" => Some(CaptureAction::Copy),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AfterCapture {
+    pub confirm: Vec<CaptureAction>,
+    pub close: bool,
+}
+
+impl Default for AfterCapture {
+    fn default() -> Self {
+        Self {
+            confirm: vec![CaptureAction::Copy],
+            close: true,
+        }
+    }
+}
+
+/// 校验后的配置，配置文件中没有写的项取默认值
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub editor: EditorSettings,
+    pub output: OutputLocation,
+    // 覆盖默认热键的绑定
+    pub hotkeys: Vec<HotkeyBinding>,
+    pub after_capture: AfterCapture,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    // 无法确定配置目录（没有 HOME 等环境变量）
+    NoConfigDir,
+    Io(PathBuf, io::Error),
+    // 语法错误、未知的键或取值无效
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoConfigDir => write!(f, "无法确定配置目录"),
+            ConfigError::Io(path, err) => {
+                write!(f, "无法读写配置文件 {}: {err}", path.display())
+            }
+            ConfigError::Invalid(path, message) => {
+                write!(f, "配置文件 {} 无效: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// 配置文件的原始结构，未知的键直接报错
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    style: StyleSection,
+    output: OutputSection,
+    overlay: OverlaySection,
+    selection: SelectionSection,
+    keys: BTreeMap<String, KeyList>,
+    hotkeys: BTreeMap<String, String>,
+    after_capture: AfterCaptureSection,
+}
+
+// 快捷键可以写成单个字符串或字符串数组
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            KeyList::One(key) => vec![key],
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleSection {
+    color: Option<String>,
+    stroke_width: Option<f32>,
+    font_size: Option<f32>,
+    text_background: Option<bool>,
+    mosaic_block_size: Option<f32>,
+    blur_sigma: Option<f32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OutputSection {
+    dir: Option<PathBuf>,
+    filename: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OverlaySection {
+    mask_color: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SelectionSection {
+    ratio: Option<String>,
+    fixed_size: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AfterCaptureSection {
+    confirm: Option<Vec<String>>,
+    close: Option<bool>,
+}
+
+// 配置目录：优先 XDG_CONFIG_HOME，其次 ~/.config，Windows 上使用 APPDATA
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(base.join(env!("CARGO_PKG_NAME")))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+// 读取配置文件，文件不存在时使用默认配置
+pub fn load() -> Result<Config, ConfigError> {
+    match config_path() {
+        Some(path) => load_from(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+pub fn load_from(path: &Path) -> Result<Config, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            parse_config(&text).map_err(|message| ConfigError::Invalid(path.into(), message))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => Err(ConfigError::Io(path.into(), err)),
+    }
+}
+
+// 配置文件不存在时写入带注释的默认配置，返回其路径
+pub fn ensure_config_file() -> Result<PathBuf, ConfigError> {
+    let path = config_path().ok_or(ConfigError::NoConfigDir)?;
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| ConfigError::Io(dir.into(), err))?;
+        }
+        std::fs::write(&path, DEFAULT_CONFIG).map_err(|err| ConfigError::Io(path.clone(), err))?;
+    }
+    Ok(path)
+}
+
+// 用系统默认程序打开配置文件
+pub fn open_in_editor(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(path).spawn().map(|_| ())
+}
+
+pub fn parse_config(text: &str) -> Result<Config, String> {
+    let file: ConfigFile = toml::from_str(text).map_err(|err| err.to_string())?;
+    let defaults = EditorSettings::default();
+
+    let style = file.style;
+    let mut editor = EditorSettings {
+        font_size: in_range("style.font_size", style.font_size, FONT_SIZE_RANGE)?
+            .unwrap_or(defaults.font_size),
+        text_background: style.text_background.unwrap_or(defaults.text_background),
+        mosaic_block_size: in_range(
+            "style.mosaic_block_size",
+            style.mosaic_block_size,
+            MIN_MOSAIC_BLOCK_SIZE..=MAX_MOSAIC_BLOCK_SIZE,
+        )?
+        .unwrap_or(defaults.mosaic_block_size),
+        blur_sigma: in_range(
+            "style.blur_sigma",
+            style.blur_sigma,
+            MIN_BLUR_SIGMA..=MAX_BLUR_SIGMA,
+        )?
+        .unwrap_or(defaults.blur_sigma),
+        mask_color: color("overlay.mask_color", file.overlay.mask_color)?
+            .unwrap_or(defaults.mask_color),
+        ..defaults
+    };
+    if let Some(color) = color("style.color", style.color)? {
+        editor.style.color = color;
+    }
+    if let Some(width) = in_range("style.stroke_width", style.stroke_width, STROKE_WIDTH_RANGE)? {
+        editor.style.stroke_width = width;
+    }
+
+    if let Some(ratio) = file.selection.ratio {
+        let ratio = SelectionRatio::parse_ratio(&ratio)
+            .ok_or_else(|| format!("selection.ratio: 无效的比例 {ratio}，应为 宽:高"))?;
+        editor.ratios.push(ratio);
+    }
+    if let Some(size) = file.selection.fixed_size {
+        let size = SelectionRatio::parse_size(&size)
+            .ok_or_else(|| format!("selection.fixed_size: 无效的大小 {size}，应为 宽x高"))?;
+        editor.ratios.push(size);
+    }
+
+    for (name, keys) in file.keys {
+        let command =
+            EditorCommand::from_name(&name).ok_or_else(|| format!("keys: 未知的命令 {name}"))?;
+        let chords = keys
+            .into_vec()
+            .iter()
+            .map(|key| {
+                KeyChord::parse(key).ok_or_else(|| format!("keys.{name}: 无效的快捷键 {key}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        editor.keymap.bind(command, chords);
+    }
+    if let Some((chord, first, second)) = editor.keymap.conflict() {
+        return Err(format!(
+            "keys: 快捷键 {chord} 同时绑定到 {} 和 {}",
+            first.name(),
+            second.name()
+        ));
+    }
+
+    let mut output = OutputLocation {
+        dir: file.output.dir.map(|dir| expand_home(&dir)),
+        ..OutputLocation::default()
+    };
+    if let Some(filename) = file.output.filename {
+        validate_filename_template(&filename).map_err(|err| format!("output.filename: {err}"))?;
+        output.template = filename;
+    }
+
+    let hotkeys = file
+        .hotkeys
+        .iter()
+        .map(|(action, hotkey)| {
+            let action = HotkeyAction::from_name(action).ok_or_else(|| {
+                format!("hotkeys: 未知的动作 {action}，应为 region、fullscreen 或 last-region")
+            })?;
+            let hotkey = hotkey
+                .parse()
+                .map_err(|err| format!("hotkeys.{}: {err}", action.name()))?;
+            Ok(HotkeyBinding { hotkey, action })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut after_capture = AfterCapture::default();
+    if let Some(confirm) = file.after_capture.confirm {
+        if confirm.is_empty() {
+            return Err("after_capture.confirm 不能为空".into());
+        }
+        after_capture.confirm = confirm
+            .iter()
+            .map(|name| {
+                CaptureAction::from_name(name).ok_or_else(|| {
+                    format!("after_capture.confirm: 未知的动作 {name}，应为 copy 或 save")
+                })
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(close) = file.after_capture.close {
+        after_capture.close = close;
+    }
+
+    Ok(Config {
+        editor,
+        output,
+        hotkeys,
+        after_capture,
+    })
+}
+
+fn in_range(
+    key: &str,
+    value: Option<f32>,
+    range: RangeInclusive<f32>,
+) -> Result<Option<f32>, String> {
+    match value {
+        Some(value) if !range.contains(&value) => Err(format!(
+            "{key} 应在 {} 到 {} 之间，实际为 {value}",
+            range.start(),
+            range.end()
+        )),
+        value => Ok(value),
+    }
+}
+
+fn color(key: &str, value: Option<String>) -> Result<Option<Color>, String> {
+    value
+        .map(|value| parse_hex_color(&value).ok_or_else(|| format!("{key}: 无效的颜色 {value}")))
+        .transpose()
+}
+
+// 展开开头的 ~
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{watch::ConfigWatcher, *};
+    use crate::editor::EditorKey;
+
+    // 取消注释指定节中的示例取值
+    fn uncomment(sections: &[&str]) -> String {
+        let mut section = "";
+        let mut lines = Vec::new();
+        for line in DEFAULT_CONFIG.lines() {
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                section = name;
+            }
+            let value = line
+                .strip_prefix("# ")
+                .filter(|value| value.contains(" = "));
+            match value {
+                Some(value) if sections.contains(&section) => lines.push(value),
+                _ => lines.push(line),
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn error(text: &str) -> String {
+        parse_config(text).expect_err(text)
+    }
+
+    #[test]
+    fn default_config_matches_defaults() {
+        assert_eq!(parse_config(DEFAULT_CONFIG), Ok(Config::default()));
+        assert_eq!(parse_config(""), Ok(Config::default()));
+
+        // 注释中写的取值就是默认值
+        let text = uncomment(&["style", "overlay", "after_capture"]);
+        assert!(text.contains("\nstroke_width = 4\n"));
+        assert_eq!(parse_config(&text), Ok(Config::default()));
+    }
+
+    #[test]
+    fn parses_values() {
+        let config = parse_config(
+            r##"
+            [style]
+            color = "#00ff00"
+            stroke_width = 8
+            [selection]
+            ratio = "21:9"
+            [keys]
+            undo = ["Ctrl+Z", "U"]
+            [after_capture]
+            confirm = ["save", "copy"]
+            close = false
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.editor.style.color, Color::from_rgb(0, 255, 0));
+        assert_eq!(config.editor.style.stroke_width, 8.0);
+        assert_eq!(
+            config.editor.ratios.last(),
+            Some(&SelectionRatio::Ratio(21.0, 9.0))
+        );
+        assert_eq!(
+            config
+                .editor
+                .keymap
+                .lookup(&EditorKey::Character("u".into()), Default::default()),
+            Some(EditorCommand::Undo)
+        );
+        assert_eq!(
+            config.after_capture,
+            AfterCapture {
+                confirm: vec![CaptureAction::Save, CaptureAction::Copy],
+                close: false,
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let cases = [
+            ("[style]\ncolour = \"#fff\"", "colour"),
+            ("[styles]\ncolor = \"#fff\"", "styles"),
+            ("[after_capture]\nquit = true", "quit"),
+            ("verbose = true", "verbose"),
+        ];
+        for (text, key) in cases {
+            let message = error(text);
+            assert!(message.contains(key), "{text}: {message}");
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let cases = [
+            ("[style]\nstroke_width = 0", "style.stroke_width"),
+            ("[style]\nstroke_width = 65", "style.stroke_width"),
+            ("[style]\nfont_size = 1", "style.font_size"),
+            ("[style]\nmosaic_block_size = 0", "style.mosaic_block_size"),
+            ("[style]\nblur_sigma = 1000", "style.blur_sigma"),
+            ("[style]\ncolor = \"red\"", "style.color"),
+            ("[style]\ncolor = \"#12345\"", "style.color"),
+            (
+                "[overlay]\nmask_color = \"#000000g0\"",
+                "overlay.mask_color",
+            ),
+            ("[overlay]\nmask_color = \"#0000000\"", "overlay.mask_color"),
+            ("[selection]\nratio = \"wide\"", "selection.ratio"),
+            (
+                "[selection]\nfixed_size = \"0x720\"",
+                "selection.fixed_size",
+            ),
+            ("[keys]\nzoom = \"Z\"", "zoom"),
+            ("[keys]\nundo = \"Ctrl+\"", "keys.undo"),
+            ("[hotkeys]\nwindow = \"F1\"", "window"),
+            ("[after_capture]\nconfirm = [\"print\"]", "print"),
+            ("[after_capture]\nconfirm = []", "after_capture.confirm"),
+        ];
+        for (text, key) in cases {
+            let message = error(text);
+            assert!(message.contains(key), "{text}: {message}");
+        }
+    }
+
+    #[test]
+    fn conflicting_chords_are_rejected() {
+        let message = error("[keys]\nundo = \"Ctrl+C\"");
+        assert!(message.contains("Ctrl+C"), "{message}");
+        assert!(
+            message.contains("copy") && message.contains("undo"),
+            "{message}"
+        );
+
+        // 同时把原来的命令改到别的键上就没有冲突
+        assert!(parse_config("[keys]\nundo = \"Ctrl+C\"\ncopy = \"Ctrl+Shift+C\"").is_ok());
+    }
+
+    #[test]
+    fn watcher_reports_changes() {
+        let dir = std::env::temp_dir().join(format!("config-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, "[style]\nstroke_width = 8").unwrap();
+
+        let mut watcher = ConfigWatcher::new(path.clone());
+        assert!(watcher.poll().is_none());
+
+        // 显式推后修改时间，不依赖文件系统的时间精度
+        std::fs::write(&path, "[style]\nstroke_width = 12").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        let config = watcher.poll().unwrap().unwrap();
+        assert_eq!(config.editor.style.stroke_width, 12.0);
+        assert!(watcher.poll().is_none());
+
+        // 文件被删除后恢复为默认配置
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll().unwrap().unwrap(), Config::default());
+        assert!(watcher.poll().is_none());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
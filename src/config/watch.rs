@@ -0,0 +1,55 @@
+use std::{
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+use super::{Config, ConfigError, load_from};
+
+// 检查配置文件修改时间的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 通过修改时间检测配置文件的变化，文件被删除后恢复为默认配置
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    // 以文件当前的状态为基准，之后的修改才算变化
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modified_time(&path);
+        Self { path, modified }
+    }
+
+    // 文件有变化时重新读取，没有变化返回 None
+    pub fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(load_from(&self.path))
+    }
+
+    // 在后台线程中定期检查，每次变化都调用 on_change
+    pub fn spawn(
+        mut self,
+        on_change: impl Fn(Result<Config, ConfigError>) + Send + 'static,
+    ) -> JoinHandle<()> {
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                if let Some(result) = self.poll() {
+                    on_change(result);
+                }
+            }
+        })
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
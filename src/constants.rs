@@ -5,8 +5,19 @@ pub mod constants {
     pub const BUTTON_WIDTH: f32 = 40.0;
     pub const BUTTON_HEIGHT: f32 = 30.0;
     pub const BUTTON_SPACING: f32 = 5.0;
-    pub const TOTAL_BUTTONS: f32 = 5.0;
+    pub const TOTAL_BUTTONS: f32 = 10.0;
     pub const MIN_SELECTION_SIZE: f32 = 10.0;
     pub const TOOLBAR_MARGIN: f32 = 15.0;
     pub const SCREEN_MARGIN: f32 = 10.0;
+    pub const PROPERTY_BUTTON_SIZE: f32 = 26.0;
+    pub const PROPERTY_BUTTON_SPACING: f32 = 4.0;
+    pub const PROPERTY_BAR_MARGIN: f32 = 6.0;
+    pub const COLOR_INPUT_WIDTH: f32 = 110.0;
+    pub const PASTE_OFFSET: f32 = 20.0;
+    pub const MAGNIFIER_PIXELS: f32 = 25.0;
+    pub const MAGNIFIER_ZOOM: f32 = 8.0;
+    pub const MAGNIFIER_MARGIN: f32 = 20.0;
+    pub const MAGNIFIER_INFO_HEIGHT: f32 = 76.0;
+    pub const SELECTION_INPUT_WIDTH: f32 = 220.0;
+    pub const SELECTION_INPUT_HEIGHT: f32 = 28.0;
 }
\ No newline at end of file
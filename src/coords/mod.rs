@@ -0,0 +1,270 @@
+use skia_safe::{Canvas, Image as SkiaImage};
+
+// 坐标空间：
+// - 逻辑像素：窗口布局和鼠标事件使用的坐标
+// - 物理像素：截图、画布和导出使用的坐标
+// 选择框和所有图形都保存为物理像素，只在输入和绘制的边界处转换
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleFactor(f32);
+
+impl ScaleFactor {
+    pub const IDENTITY: ScaleFactor = ScaleFactor(1.0);
+
+    // 非正数或非有限值的缩放比例按 1.0 处理
+    pub fn new(value: f32) -> Self {
+        if value.is_finite() && value > 0.0 {
+            Self(value)
+        } else {
+            Self::IDENTITY
+        }
+    }
+
+    pub fn get(self) -> f32 {
+        self.0
+    }
+}
+
+impl Default for ScaleFactor {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PhysicalPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl LogicalPoint {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn to_physical(self, scale: ScaleFactor) -> PhysicalPoint {
+        PhysicalPoint::new(self.x * scale.get(), self.y * scale.get())
+    }
+}
+
+impl PhysicalPoint {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn to_logical(self, scale: ScaleFactor) -> LogicalPoint {
+        LogicalPoint::new(self.x / scale.get(), self.y / scale.get())
+    }
+}
+
+impl From<(f32, f32)> for PhysicalPoint {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<PhysicalPoint> for (f32, f32) {
+    fn from(point: PhysicalPoint) -> Self {
+        (point.x, point.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+// 物理尺寸总是整像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl LogicalSize {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn to_physical(self, scale: ScaleFactor) -> PhysicalSize {
+        PhysicalSize::new(
+            (self.width * scale.get()).round().max(0.0) as u32,
+            (self.height * scale.get()).round().max(0.0) as u32,
+        )
+    }
+}
+
+impl PhysicalSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn of_image(image: &SkiaImage) -> Self {
+        Self::new(image.width().max(0) as u32, image.height().max(0) as u32)
+    }
+
+    pub fn to_logical(self, scale: ScaleFactor) -> LogicalSize {
+        LogicalSize::new(
+            self.width as f32 / scale.get(),
+            self.height as f32 / scale.get(),
+        )
+    }
+
+    pub fn width_f32(self) -> f32 {
+        self.width as f32
+    }
+
+    pub fn height_f32(self) -> f32 {
+        self.height as f32
+    }
+}
+
+// 物理像素上的整数矩形（左上闭、右下开）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl PixelRect {
+    // 包含给定物理坐标范围的最小像素矩形（向外取整并裁剪到 bounds_size 内）
+    pub fn enclosing(
+        (left, top, right, bottom): (f32, f32, f32, f32),
+        bounds_size: PhysicalSize,
+    ) -> Option<Self> {
+        let rect = Self {
+            left: (left.floor() as i32).max(0),
+            top: (top.floor() as i32).max(0),
+            right: (right.ceil() as i32).min(bounds_size.width as i32),
+            bottom: (bottom.ceil() as i32).min(bounds_size.height as i32),
+        };
+        (rect.right > rect.left && rect.bottom > rect.top).then_some(rect)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+}
+
+// 将画布坐标系从逻辑像素切换为物理像素，之后按截图像素 1:1 绘制
+// origin 为画布元素左上角的逻辑坐标，scale 为渲染时使用的缩放比例
+pub fn scale_to_physical(canvas: &Canvas, origin: LogicalPoint, scale: ScaleFactor) {
+    canvas.translate((origin.x, origin.y));
+    canvas.scale((1.0 / scale.get(), 1.0 / scale.get()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALES: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+
+    #[test]
+    fn invalid_scale_is_identity() {
+        for value in [0.0, -1.5, f32::NAN, f32::INFINITY] {
+            assert_eq!(ScaleFactor::new(value), ScaleFactor::IDENTITY);
+        }
+        assert_eq!(ScaleFactor::new(1.5).get(), 1.5);
+    }
+
+    #[test]
+    fn point_round_trip() {
+        for scale in SCALES.map(ScaleFactor::new) {
+            for (x, y) in [(0.0, 0.0), (13.0, 7.5), (1919.0, 1079.0), (-320.0, 45.25)] {
+                let logical = LogicalPoint::new(x, y);
+                let physical = logical.to_physical(scale);
+                assert_eq!(
+                    physical,
+                    PhysicalPoint::new(x * scale.get(), y * scale.get())
+                );
+
+                let back = physical.to_logical(scale);
+                assert!((back.x - x).abs() < 1e-3, "scale {scale:?}: {back:?}");
+                assert!((back.y - y).abs() < 1e-3, "scale {scale:?}: {back:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn logical_size_rounds_to_whole_pixels() {
+        let cases = [
+            (1.0, (1366.0, 767.0), (1366, 767)),
+            (1.25, (1366.0, 101.0), (1708, 126)),
+            (1.5, (101.0, 33.0), (152, 50)),
+            (2.0, (960.5, 0.0), (1921, 0)),
+        ];
+        for (scale, (width, height), expected) in cases {
+            let physical = LogicalSize::new(width, height).to_physical(ScaleFactor::new(scale));
+            assert_eq!(
+                physical,
+                PhysicalSize::new(expected.0, expected.1),
+                "scale {scale}"
+            );
+        }
+        // 负数尺寸按 0 处理
+        assert_eq!(
+            LogicalSize::new(-10.0, 5.0).to_physical(ScaleFactor::new(1.5)),
+            PhysicalSize::new(0, 8)
+        );
+    }
+
+    #[test]
+    fn physical_size_round_trip() {
+        for scale in SCALES.map(ScaleFactor::new) {
+            let physical = PhysicalSize::new(2560, 1440);
+            assert_eq!(physical.to_logical(scale).to_physical(scale), physical);
+        }
+    }
+
+    #[test]
+    fn pixel_rect_encloses_scaled_selection() {
+        let bounds = PhysicalSize::new(3840, 2160);
+        for scale in SCALES.map(ScaleFactor::new) {
+            let start = LogicalPoint::new(10.3, 20.6).to_physical(scale);
+            let end = LogicalPoint::new(50.2, 40.1).to_physical(scale);
+            let rect = PixelRect::enclosing((start.x, start.y, end.x, end.y), bounds).unwrap();
+
+            assert_eq!(rect.left, start.x.floor() as i32);
+            assert_eq!(rect.top, start.y.floor() as i32);
+            assert_eq!(rect.right, end.x.ceil() as i32);
+            assert_eq!(rect.bottom, end.y.ceil() as i32);
+            assert!(rect.left as f32 <= start.x && rect.right as f32 >= end.x);
+            assert!(rect.top as f32 <= start.y && rect.bottom as f32 >= end.y);
+        }
+    }
+
+    #[test]
+    fn pixel_rect_is_clamped_to_bounds() {
+        let bounds = PhysicalSize::new(100, 50);
+        let rect = PixelRect::enclosing((-4.5, -1.0, 120.2, 49.5), bounds).unwrap();
+        assert_eq!(
+            rect,
+            PixelRect {
+                left: 0,
+                top: 0,
+                right: 100,
+                bottom: 50,
+            }
+        );
+        assert_eq!((rect.width(), rect.height()), (100, 50));
+
+        assert_eq!(PixelRect::enclosing((10.0, 10.0, 10.0, 30.0), bounds), None);
+        assert_eq!(
+            PixelRect::enclosing((150.0, 10.0, 200.0, 30.0), bounds),
+            None
+        );
+    }
+}
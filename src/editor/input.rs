@@ -0,0 +1,39 @@
+// 编辑器使用的输入事件，与具体的界面框架无关
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerButton {
+    Primary,
+    // 右键直接退出
+    Secondary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorKey {
+    Escape,
+    Enter,
+    Backspace,
+    Delete,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    ArrowDown,
+    Home,
+    End,
+    // 输入的文字（可能是多个字符，例如输入法提交的内容）
+    Character(String),
+    Other,
+}
+
+impl EditorKey {
+    // 字符键，不区分大小写
+    pub fn is_char(&self, key: &str) -> bool {
+        matches!(self, EditorKey::Character(c) if c.eq_ignore_ascii_case(key))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyModifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
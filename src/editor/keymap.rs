@@ -0,0 +1,601 @@
+use std::fmt;
+
+use super::{EditorKey, KeyModifiers};
+use crate::types::drawing::DrawingTool;
+
+// 微调的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn offset(&self, distance: f32) -> (f32, f32) {
+        match self {
+            Direction::Left => (-distance, 0.0),
+            Direction::Right => (distance, 0.0),
+            Direction::Up => (0.0, -distance),
+            Direction::Down => (0.0, distance),
+        }
+    }
+}
+
+// 可以绑定快捷键的编辑器命令
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorCommand {
+    Tool(DrawingTool),
+    Undo,
+    Redo,
+    Save,
+    // 选中图形时复制图形，否则复制截图
+    Copy,
+    Paste,
+    Duplicate,
+    Confirm,
+    Delete,
+    // 移动选中的图形或选择框，按住 Shift 时移动 10 像素
+    Nudge(Direction),
+    // 把选择框朝该方向的边向外移动，或把相对的边向内移动，按住 Shift 时 10 像素
+    GrowSelection(Direction),
+    ShrinkSelection(Direction),
+    // 输入选择框的位置和大小
+    SelectionInput,
+    ToggleHandles,
+    // 依次关闭帮助、取消工具和选中的图形，最后退出
+    Cancel,
+    WeakerRedaction,
+    StrongerRedaction,
+    // 进入/退出取色模式，单击复制颜色并作为标注颜色
+    ColorPicker,
+    ColorFormat,
+    SampleSize,
+    Help,
+}
+
+// 命令在配置文件中的名称和帮助中的说明，顺序即帮助中的顺序
+const COMMANDS: [(EditorCommand, &str, &str); 36] = [
+    (
+        EditorCommand::Tool(DrawingTool::Rectangle),
+        "rectangle",
+        "矩形",
+    ),
+    (EditorCommand::Tool(DrawingTool::Circle), "ellipse", "椭圆"),
+    (EditorCommand::Tool(DrawingTool::Arrow), "arrow", "箭头"),
+    (EditorCommand::Tool(DrawingTool::Brush), "brush", "画笔"),
+    (EditorCommand::Tool(DrawingTool::Text), "text", "文字"),
+    (EditorCommand::Tool(DrawingTool::Mosaic), "mosaic", "马赛克"),
+    (EditorCommand::Tool(DrawingTool::Blur), "blur", "模糊"),
+    (EditorCommand::Undo, "undo", "撤销"),
+    (EditorCommand::Redo, "redo", "重做"),
+    (EditorCommand::Save, "save", "保存"),
+    (EditorCommand::Copy, "copy", "复制图形或截图"),
+    (EditorCommand::Paste, "paste", "粘贴图形"),
+    (EditorCommand::Duplicate, "duplicate", "复制选中的图形"),
+    (EditorCommand::Confirm, "confirm", "完成截图"),
+    (EditorCommand::Delete, "delete", "删除选中的图形"),
+    (
+        EditorCommand::Nudge(Direction::Left),
+        "nudge-left",
+        "左移（Shift 10 像素）",
+    ),
+    (
+        EditorCommand::Nudge(Direction::Right),
+        "nudge-right",
+        "右移（Shift 10 像素）",
+    ),
+    (
+        EditorCommand::Nudge(Direction::Up),
+        "nudge-up",
+        "上移（Shift 10 像素）",
+    ),
+    (
+        EditorCommand::Nudge(Direction::Down),
+        "nudge-down",
+        "下移（Shift 10 像素）",
+    ),
+    (
+        EditorCommand::GrowSelection(Direction::Left),
+        "grow-left",
+        "选择框向左扩大",
+    ),
+    (
+        EditorCommand::GrowSelection(Direction::Right),
+        "grow-right",
+        "选择框向右扩大",
+    ),
+    (
+        EditorCommand::GrowSelection(Direction::Up),
+        "grow-up",
+        "选择框向上扩大",
+    ),
+    (
+        EditorCommand::GrowSelection(Direction::Down),
+        "grow-down",
+        "选择框向下扩大",
+    ),
+    (
+        EditorCommand::ShrinkSelection(Direction::Left),
+        "shrink-left",
+        "选择框向左缩小",
+    ),
+    (
+        EditorCommand::ShrinkSelection(Direction::Right),
+        "shrink-right",
+        "选择框向右缩小",
+    ),
+    (
+        EditorCommand::ShrinkSelection(Direction::Up),
+        "shrink-up",
+        "选择框向上缩小",
+    ),
+    (
+        EditorCommand::ShrinkSelection(Direction::Down),
+        "shrink-down",
+        "选择框向下缩小",
+    ),
+    (
+        EditorCommand::SelectionInput,
+        "selection-input",
+        "输入选择框的位置和大小",
+    ),
+    (
+        EditorCommand::ToggleHandles,
+        "toggle-handles",
+        "显示/隐藏调整手柄",
+    ),
+    (EditorCommand::Cancel, "cancel", "取消 / 退出"),
+    (
+        EditorCommand::WeakerRedaction,
+        "weaker-redaction",
+        "减弱马赛克和模糊",
+    ),
+    (
+        EditorCommand::StrongerRedaction,
+        "stronger-redaction",
+        "加强马赛克和模糊",
+    ),
+    (EditorCommand::ColorPicker, "color-picker", "取色器"),
+    (
+        EditorCommand::ColorFormat,
+        "color-format",
+        "切换取色的复制格式",
+    ),
+    (EditorCommand::SampleSize, "sample-size", "切换取色范围"),
+    (EditorCommand::Help, "help", "显示/隐藏快捷键"),
+];
+
+impl EditorCommand {
+    pub fn name(&self) -> &'static str {
+        COMMANDS
+            .into_iter()
+            .find(|(command, ..)| command == self)
+            .map_or("", |(_, name, _)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        COMMANDS
+            .into_iter()
+            .find(|(_, command_name, _)| *command_name == name)
+            .map(|(command, ..)| command)
+    }
+}
+
+/// 一个快捷键：按键加修饰键，字符键保存为小写
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: EditorKey,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(key: EditorKey, ctrl: bool, shift: bool) -> Self {
+        Self {
+            key,
+            modifiers: KeyModifiers {
+                ctrl,
+                shift,
+                alt: false,
+            },
+        }
+    }
+
+    fn char(c: &str) -> Self {
+        Self::new(EditorKey::Character(c.into()), false, false)
+    }
+
+    fn ctrl(c: &str) -> Self {
+        Self::new(EditorKey::Character(c.into()), true, false)
+    }
+
+    // 解析 Ctrl+Shift+Z、Delete、? 这样的快捷键，不区分大小写
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        // 最后一段是按键本身，允许按键为 + 号
+        let (prefix, key) = match text.strip_suffix('+') {
+            Some(prefix) if prefix.is_empty() || prefix.ends_with('+') => {
+                (prefix.trim_end_matches('+'), "+")
+            }
+            _ => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+
+        let mut modifiers = KeyModifiers::default();
+        for part in prefix.split('+').filter(|part| !part.is_empty()) {
+            match part.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                _ => return None,
+            }
+        }
+
+        let key = match key.trim().to_ascii_lowercase().as_str() {
+            "esc" | "escape" => EditorKey::Escape,
+            "enter" | "return" => EditorKey::Enter,
+            "backspace" => EditorKey::Backspace,
+            "del" | "delete" => EditorKey::Delete,
+            "left" => EditorKey::ArrowLeft,
+            "right" => EditorKey::ArrowRight,
+            "up" => EditorKey::ArrowUp,
+            "down" => EditorKey::ArrowDown,
+            "home" => EditorKey::Home,
+            "end" => EditorKey::End,
+            "space" => EditorKey::Character(" ".into()),
+            key if key.chars().count() == 1 => EditorKey::Character(key.into()),
+            _ => return None,
+        };
+        Some(Self { key, modifiers })
+    }
+
+    // 字母键区分 Shift；符号键本身已经体现了 Shift（例如 ?），忽略 Shift
+    pub fn matches(&self, key: &EditorKey, modifiers: KeyModifiers) -> bool {
+        if self.modifiers.ctrl != modifiers.ctrl || self.modifiers.alt != modifiers.alt {
+            return false;
+        }
+        match (&self.key, key) {
+            (EditorKey::Character(expected), EditorKey::Character(actual)) => {
+                let is_letter = expected.chars().all(|c| c.is_ascii_alphabetic());
+                expected.eq_ignore_ascii_case(actual)
+                    && (!is_letter || self.modifiers.shift == modifiers.shift)
+            }
+            (expected, actual) => expected == actual && self.modifiers.shift == modifiers.shift,
+        }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (enabled, name) in [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.shift, "Shift+"),
+            (self.modifiers.alt, "Alt+"),
+        ] {
+            if enabled {
+                f.write_str(name)?;
+            }
+        }
+        match &self.key {
+            EditorKey::Escape => f.write_str("Esc"),
+            EditorKey::Enter => f.write_str("Enter"),
+            EditorKey::Backspace => f.write_str("Backspace"),
+            EditorKey::Delete => f.write_str("Delete"),
+            EditorKey::ArrowLeft => f.write_str("Left"),
+            EditorKey::ArrowRight => f.write_str("Right"),
+            EditorKey::ArrowUp => f.write_str("Up"),
+            EditorKey::ArrowDown => f.write_str("Down"),
+            EditorKey::Home => f.write_str("Home"),
+            EditorKey::End => f.write_str("End"),
+            EditorKey::Character(c) if c == " " => f.write_str("Space"),
+            EditorKey::Character(c) => f.write_str(&c.to_uppercase()),
+            EditorKey::Other => f.write_str("未知"),
+        }
+    }
+}
+
+/// 快捷键到编辑器命令的映射，默认值参照常见的截图工具
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, EditorCommand)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let arrow = |key, direction| {
+            (
+                KeyChord::new(key, false, false),
+                EditorCommand::Nudge(direction),
+            )
+        };
+        let alt_arrow = |key, direction| {
+            let mut chord = KeyChord::new(key, false, false);
+            chord.modifiers.alt = true;
+            (chord, EditorCommand::GrowSelection(direction))
+        };
+        let ctrl_arrow = |key, direction| {
+            (
+                KeyChord::new(key, true, false),
+                EditorCommand::ShrinkSelection(direction),
+            )
+        };
+        let bindings = vec![
+            (
+                KeyChord::char("r"),
+                EditorCommand::Tool(DrawingTool::Rectangle),
+            ),
+            (
+                KeyChord::char("e"),
+                EditorCommand::Tool(DrawingTool::Circle),
+            ),
+            (KeyChord::char("a"), EditorCommand::Tool(DrawingTool::Arrow)),
+            (KeyChord::char("p"), EditorCommand::Tool(DrawingTool::Brush)),
+            (KeyChord::char("t"), EditorCommand::Tool(DrawingTool::Text)),
+            (
+                KeyChord::char("m"),
+                EditorCommand::Tool(DrawingTool::Mosaic),
+            ),
+            (KeyChord::char("b"), EditorCommand::Tool(DrawingTool::Blur)),
+            (KeyChord::ctrl("z"), EditorCommand::Undo),
+            (
+                KeyChord::new(EditorKey::Character("z".into()), true, true),
+                EditorCommand::Redo,
+            ),
+            (KeyChord::ctrl("y"), EditorCommand::Redo),
+            (KeyChord::ctrl("s"), EditorCommand::Save),
+            (KeyChord::ctrl("c"), EditorCommand::Copy),
+            (KeyChord::ctrl("v"), EditorCommand::Paste),
+            (KeyChord::ctrl("d"), EditorCommand::Duplicate),
+            (
+                KeyChord::new(EditorKey::Enter, false, false),
+                EditorCommand::Confirm,
+            ),
+            (
+                KeyChord::new(EditorKey::Delete, false, false),
+                EditorCommand::Delete,
+            ),
+            (
+                KeyChord::new(EditorKey::Backspace, false, false),
+                EditorCommand::Delete,
+            ),
+            arrow(EditorKey::ArrowLeft, Direction::Left),
+            arrow(EditorKey::ArrowRight, Direction::Right),
+            arrow(EditorKey::ArrowUp, Direction::Up),
+            arrow(EditorKey::ArrowDown, Direction::Down),
+            alt_arrow(EditorKey::ArrowLeft, Direction::Left),
+            alt_arrow(EditorKey::ArrowRight, Direction::Right),
+            alt_arrow(EditorKey::ArrowUp, Direction::Up),
+            alt_arrow(EditorKey::ArrowDown, Direction::Down),
+            ctrl_arrow(EditorKey::ArrowLeft, Direction::Left),
+            ctrl_arrow(EditorKey::ArrowRight, Direction::Right),
+            ctrl_arrow(EditorKey::ArrowUp, Direction::Up),
+            ctrl_arrow(EditorKey::ArrowDown, Direction::Down),
+            (KeyChord::char("g"), EditorCommand::SelectionInput),
+            (KeyChord::char("h"), EditorCommand::ToggleHandles),
+            (
+                KeyChord::new(EditorKey::Escape, false, false),
+                EditorCommand::Cancel,
+            ),
+            (KeyChord::char("["), EditorCommand::WeakerRedaction),
+            (KeyChord::char("]"), EditorCommand::StrongerRedaction),
+            (KeyChord::char("i"), EditorCommand::ColorPicker),
+            (KeyChord::char("f"), EditorCommand::ColorFormat),
+            (KeyChord::char("n"), EditorCommand::SampleSize),
+            (KeyChord::char("?"), EditorCommand::Help),
+        ];
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // 按下的键对应的命令；方向键加 Shift 没有单独绑定时按 10 像素的微调或缩放处理
+    pub fn lookup(&self, key: &EditorKey, modifiers: KeyModifiers) -> Option<EditorCommand> {
+        let find = |modifiers| {
+            self.bindings
+                .iter()
+                .find(|(chord, _)| chord.matches(key, modifiers))
+                .map(|(_, command)| *command)
+        };
+        find(modifiers).or_else(|| {
+            let unshifted = KeyModifiers {
+                shift: false,
+                ..modifiers
+            };
+            find(unshifted).filter(|command| {
+                modifiers.shift
+                    && matches!(
+                        command,
+                        EditorCommand::Nudge(_)
+                            | EditorCommand::GrowSelection(_)
+                            | EditorCommand::ShrinkSelection(_)
+                    )
+            })
+        })
+    }
+
+    // 替换一个命令的所有快捷键，chords 为空时取消绑定
+    pub fn bind(&mut self, command: EditorCommand, chords: Vec<KeyChord>) {
+        self.bindings.retain(|(_, bound)| *bound != command);
+        self.bindings
+            .extend(chords.into_iter().map(|chord| (chord, command)));
+    }
+
+    // 第一个同时绑定到两个命令的快捷键
+    pub fn conflict(&self) -> Option<(&KeyChord, EditorCommand, EditorCommand)> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .find_map(|(i, (chord, command))| {
+                self.bindings[i + 1..]
+                    .iter()
+                    .find(|(other, other_command)| other == chord && other_command != command)
+                    .map(|(_, other_command)| (chord, *command, *other_command))
+            })
+    }
+
+    // 帮助中列出的 (快捷键, 说明)，同一命令的多个快捷键用 / 分隔
+    pub fn entries(&self) -> Vec<(String, &'static str)> {
+        COMMANDS
+            .into_iter()
+            .filter_map(|(command, _, description)| {
+                let chords: Vec<String> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, bound)| *bound == command)
+                    .map(|(chord, _)| chord.to_string())
+                    .collect();
+                (!chords.is_empty()).then(|| (chords.join(" / "), description))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(key: EditorKey, ctrl: bool, shift: bool, alt: bool) -> KeyChord {
+        KeyChord {
+            key,
+            modifiers: KeyModifiers { ctrl, shift, alt },
+        }
+    }
+
+    fn character(c: &str) -> EditorKey {
+        EditorKey::Character(c.into())
+    }
+
+    #[test]
+    fn parse_valid_chords() {
+        let cases = [
+            ("Ctrl+Shift+Z", chord(character("z"), true, true, false)),
+            ("ctrl+z", chord(character("z"), true, false, false)),
+            (
+                "Control+Return",
+                chord(EditorKey::Enter, true, false, false),
+            ),
+            ("Alt+Left", chord(EditorKey::ArrowLeft, false, false, true)),
+            ("Delete", chord(EditorKey::Delete, false, false, false)),
+            ("del", chord(EditorKey::Delete, false, false, false)),
+            ("Esc", chord(EditorKey::Escape, false, false, false)),
+            ("?", chord(character("?"), false, false, false)),
+            ("+", chord(character("+"), false, false, false)),
+            ("Ctrl++", chord(character("+"), true, false, false)),
+            (" Shift + Space ", chord(character(" "), false, true, false)),
+            ("[", chord(character("["), false, false, false)),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(KeyChord::parse(text), Some(expected), "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_chords() {
+        let cases = [
+            "",
+            "Ctrl+",
+            "Ctrl+Shift",
+            "Super+A",
+            "Hyper+Z",
+            "F13",
+            "ab",
+            "Ctrl+Left+Right",
+        ];
+        for text in cases {
+            assert_eq!(KeyChord::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in [
+            "Ctrl+Shift+Z",
+            "Alt+Up",
+            "Delete",
+            "Space",
+            "?",
+            "Ctrl++",
+            "Esc",
+        ] {
+            let chord = KeyChord::parse(text).unwrap();
+            assert_eq!(chord.to_string(), text);
+            assert_eq!(KeyChord::parse(&chord.to_string()), Some(chord));
+        }
+    }
+
+    #[test]
+    fn letters_match_shift_but_symbols_ignore_it() {
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let r = KeyChord::parse("r").unwrap();
+        assert!(r.matches(&character("R"), KeyModifiers::default()));
+        assert!(!r.matches(&character("R"), shift));
+
+        let help = KeyChord::parse("?").unwrap();
+        assert!(help.matches(&character("?"), shift));
+    }
+
+    #[test]
+    fn command_names_round_trip() {
+        for (command, name, _) in COMMANDS {
+            assert_eq!(command.name(), name);
+            assert_eq!(EditorCommand::from_name(name), Some(command), "{name}");
+        }
+        assert_eq!(EditorCommand::from_name("teleport"), None);
+    }
+
+    #[test]
+    fn default_keymap_has_no_conflicts() {
+        assert_eq!(Keymap::default().conflict(), None);
+    }
+
+    #[test]
+    fn conflict_reports_both_commands() {
+        let mut keymap = Keymap::default();
+        keymap.bind(
+            EditorCommand::Undo,
+            vec![KeyChord::parse("Ctrl+C").unwrap()],
+        );
+        let (chord, first, second) = keymap.conflict().unwrap();
+        assert_eq!(chord.to_string(), "Ctrl+C");
+        assert_eq!((first, second), (EditorCommand::Copy, EditorCommand::Undo));
+
+        // 把冲突的命令改绑后不再冲突
+        keymap.bind(EditorCommand::Copy, vec![]);
+        assert_eq!(keymap.conflict(), None);
+        assert_eq!(
+            keymap.lookup(
+                &character("c"),
+                KeyModifiers {
+                    ctrl: true,
+                    ..Default::default()
+                }
+            ),
+            Some(EditorCommand::Undo)
+        );
+    }
+
+    #[test]
+    fn same_chord_twice_for_one_command_is_not_a_conflict() {
+        let mut keymap = Keymap::default();
+        let chord = KeyChord::parse("Ctrl+Y").unwrap();
+        keymap.bind(EditorCommand::Redo, vec![chord.clone(), chord]);
+        assert_eq!(keymap.conflict(), None);
+    }
+
+    #[test]
+    fn shift_falls_back_only_for_movement() {
+        let keymap = Keymap::default();
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            keymap.lookup(&EditorKey::ArrowLeft, shift),
+            Some(EditorCommand::Nudge(Direction::Left))
+        );
+        assert_eq!(keymap.lookup(&character("R"), shift), None);
+    }
+}
@@ -0,0 +1,821 @@
+use super::{
+    Editor, EditorAction, EditorCommand, EditorKey, EditorResponse, KeyModifiers, keymap::Direction,
+};
+use crate::{
+    constants::constants::MIN_SELECTION_SIZE,
+    coords::PhysicalSize,
+    geometry::{constrain_to_screen, get_resize_anchor, resize_with_ratio},
+    history::Command,
+    picker::SAMPLE_SIZES,
+    shapes::style::parse_hex_color,
+    types::{
+        app_state::{AppState, ResizeHandle},
+        drawing::DrawingTool,
+        ui::{Selection, SelectionRatio, ToolbarButton},
+    },
+};
+
+// 方向键微调的距离，按住 Shift 时使用较大的距离
+const NUDGE_DISTANCE: f32 = 1.0;
+const NUDGE_FAR_DISTANCE: f32 = 10.0;
+
+impl Editor {
+    // 键盘输入：颜色输入框和文字编辑优先，其余按快捷键映射执行命令
+    pub fn key(&mut self, key: &EditorKey, modifiers: KeyModifiers) -> EditorResponse {
+        // 输入自定义颜色时键盘输入全部交给颜色输入框
+        if self.color_input.is_some() {
+            let redraw = self.color_input_key(key);
+            return self.respond(redraw, None);
+        }
+
+        // 输入选择框位置和大小时键盘输入全部交给输入框
+        if self.selection_input.is_some() {
+            let redraw = self.selection_input_key(key);
+            return self.respond(redraw, None);
+        }
+
+        // 编辑文字时键盘输入全部交给文字标注
+        if self.text_editing.is_some() {
+            self.text_editing_key(key, modifiers);
+            return self.respond(true, None);
+        }
+
+        // 显示帮助时按任意键关闭帮助（单独按下修饰键除外）
+        if self.show_help {
+            if *key == EditorKey::Other {
+                return self.respond(false, None);
+            }
+            self.show_help = false;
+            return self.respond(true, None);
+        }
+
+        let (redraw, action) = match self.keymap.lookup(key, modifiers) {
+            Some(command) => self.run_command(command, modifiers),
+            None => (false, None),
+        };
+        self.respond(redraw, action)
+    }
+
+    // 执行快捷键对应的命令，返回 (是否重绘, 需要界面层完成的操作)
+    fn run_command(
+        &mut self,
+        command: EditorCommand,
+        modifiers: KeyModifiers,
+    ) -> (bool, Option<EditorAction>) {
+        match command {
+            EditorCommand::Tool(tool) => (self.select_tool(tool), None),
+            EditorCommand::Undo => (self.apply_history(false), None),
+            EditorCommand::Redo => (self.apply_history(true), None),
+            EditorCommand::Save => (false, Some(EditorAction::Save)),
+            EditorCommand::Copy => {
+                // 选中图形时复制图形，否则复制截图
+                match self.selected_shape() {
+                    Some(shape) => {
+                        self.shape_clipboard = Some(shape);
+                        (false, None)
+                    }
+                    None => (false, Some(EditorAction::Copy)),
+                }
+            }
+            EditorCommand::Paste => {
+                let pasted = self
+                    .shape_clipboard
+                    .clone()
+                    .and_then(|shape| self.insert_shape_copy(&shape));
+                // 记录粘贴后的位置，连续粘贴时依次错开
+                if let Some(pasted) = &pasted {
+                    self.shape_clipboard = Some(pasted.clone());
+                }
+                (pasted.is_some(), None)
+            }
+            EditorCommand::Duplicate => {
+                let copied = self
+                    .selected_shape()
+                    .and_then(|shape| self.insert_shape_copy(&shape));
+                (copied.is_some(), None)
+            }
+            EditorCommand::Confirm => (false, Some(EditorAction::Confirm)),
+            EditorCommand::Delete => (self.delete_selected_shape(), None),
+            EditorCommand::Nudge(direction) => (
+                self.nudge(direction.offset(nudge_distance(modifiers))),
+                None,
+            ),
+            EditorCommand::GrowSelection(direction) => (
+                self.resize_selection_edge(direction, true, nudge_distance(modifiers)),
+                None,
+            ),
+            EditorCommand::ShrinkSelection(direction) => (
+                self.resize_selection_edge(direction, false, nudge_distance(modifiers)),
+                None,
+            ),
+            EditorCommand::SelectionInput => (self.open_selection_input(), None),
+            EditorCommand::ToggleHandles => {
+                self.show_handles = !self.show_handles;
+                (true, None)
+            }
+            EditorCommand::Cancel => self.cancel(),
+            EditorCommand::WeakerRedaction => {
+                self.adjust_redact_strength(-2.0);
+                (true, None)
+            }
+            EditorCommand::StrongerRedaction => {
+                self.adjust_redact_strength(2.0);
+                (true, None)
+            }
+            EditorCommand::ColorPicker => (self.toggle_color_picker(), None),
+            EditorCommand::ColorFormat => {
+                self.color_format = self.color_format.next();
+                (self.color_picker, None)
+            }
+            EditorCommand::SampleSize => {
+                let index = SAMPLE_SIZES
+                    .iter()
+                    .position(|size| *size == self.sample_size)
+                    .map_or(0, |index| (index + 1) % SAMPLE_SIZES.len());
+                self.sample_size = SAMPLE_SIZES[index];
+                (self.color_picker, None)
+            }
+            EditorCommand::Help => {
+                self.show_help = true;
+                (true, None)
+            }
+        }
+    }
+
+    // 与点击工具栏按钮相同，需要先有选择框
+    fn select_tool(&mut self, tool: DrawingTool) -> bool {
+        if self.app_state != AppState::Idle || self.current_selection.is_none() {
+            return false;
+        }
+        let button = match tool {
+            DrawingTool::Rectangle => ToolbarButton::Rectangle,
+            DrawingTool::Circle => ToolbarButton::Circle,
+            DrawingTool::Arrow => ToolbarButton::Arrow,
+            DrawingTool::Brush => ToolbarButton::Brush,
+            DrawingTool::Text => ToolbarButton::Text,
+            DrawingTool::Mosaic => ToolbarButton::Mosaic,
+            DrawingTool::Blur => ToolbarButton::Blur,
+            DrawingTool::None => {
+                self.current_tool = DrawingTool::None;
+                return true;
+            }
+        };
+        self.press_toolbar_button(button);
+        true
+    }
+
+    // 移动选中的图形（限制在选择区域内），没有选中图形时移动选择框（限制在屏幕内）
+    fn nudge(&mut self, (dx, dy): (f32, f32)) -> bool {
+        if self.app_state != AppState::Idle {
+            return false;
+        }
+        let Some(selection) = self.current_selection else {
+            return false;
+        };
+
+        let Some(index) = self.selected_shape_index else {
+            return self.change_selection(Selection {
+                start: (selection.start.0 + dx, selection.start.1 + dy),
+                end: (selection.end.0 + dx, selection.end.1 + dy),
+            });
+        };
+        let Some(before) = self.shapes.get(index).cloned() else {
+            return false;
+        };
+        let mut after = before.clone();
+        after.translate(dx, dy);
+        after.constrain_to_selection(selection.bounds());
+        if after == before {
+            return false;
+        }
+        self.shapes[index] = after.clone();
+        self.history.push(Command::MoveShape {
+            index,
+            before,
+            after,
+        });
+        true
+    }
+
+    // 扩大时移动朝向 direction 的边，缩小时把相对的边朝 direction 移动，不小于最小尺寸
+    // 固定大小时不能调整；限制比例时与拖动该边的手柄相同，另一边以中心对称缩放
+    fn resize_selection_edge(&mut self, direction: Direction, grow: bool, distance: f32) -> bool {
+        if self.app_state != AppState::Idle || matches!(self.ratio, SelectionRatio::Fixed(..)) {
+            return false;
+        }
+        let Some(selection) = self.current_selection else {
+            return false;
+        };
+
+        let (mut left, mut top, mut right, mut bottom) = selection.bounds();
+        match (direction, grow) {
+            (Direction::Left, true) => left = (left - distance).max(0.0),
+            (Direction::Right, true) => {
+                right = (right + distance).min(self.screen_size.width_f32())
+            }
+            (Direction::Up, true) => top = (top - distance).max(0.0),
+            (Direction::Down, true) => {
+                bottom = (bottom + distance).min(self.screen_size.height_f32())
+            }
+            (Direction::Left, false) => right = (right - distance).max(left + MIN_SELECTION_SIZE),
+            (Direction::Right, false) => left = (left + distance).min(right - MIN_SELECTION_SIZE),
+            (Direction::Up, false) => bottom = (bottom - distance).max(top + MIN_SELECTION_SIZE),
+            (Direction::Down, false) => top = (top + distance).min(bottom - MIN_SELECTION_SIZE),
+        }
+        let resized = Selection {
+            start: (left, top),
+            end: (right, bottom),
+        };
+        let Some(ratio) = self.ratio.aspect() else {
+            return self.change_selection(resized);
+        };
+
+        let handle = match (direction, grow) {
+            (Direction::Left, true) | (Direction::Right, false) => ResizeHandle::Left,
+            (Direction::Right, true) | (Direction::Left, false) => ResizeHandle::Right,
+            (Direction::Up, true) | (Direction::Down, false) => ResizeHandle::Top,
+            (Direction::Down, true) | (Direction::Up, false) => ResizeHandle::Bottom,
+        };
+        let (center_x, center_y) = resized.center();
+        let pos = match handle {
+            ResizeHandle::Left => (left, center_y),
+            ResizeHandle::Right => (right, center_y),
+            ResizeHandle::Top => (center_x, top),
+            _ => (center_x, bottom),
+        };
+        self.change_selection(resize_with_ratio(
+            handle,
+            get_resize_anchor(handle, &selection),
+            pos,
+            ratio,
+            MIN_SELECTION_SIZE,
+            self.screen_size,
+        ))
+    }
+
+    // 限制在屏幕内后替换选择框，有变化时记录一条历史
+    pub(super) fn change_selection(&mut self, selection: Selection) -> bool {
+        let after = constrain_to_screen(selection, self.screen_size);
+        let before = self.current_selection;
+        if before == Some(after) {
+            return false;
+        }
+        self.current_selection = Some(after);
+        self.history.push(Command::ChangeSelection {
+            before,
+            after: Some(after),
+        });
+        true
+    }
+
+    // 打开选择框输入框，已有选择框时预先填入当前的位置和大小
+    fn open_selection_input(&mut self) -> bool {
+        if self.app_state != AppState::Idle {
+            return false;
+        }
+        let input = self
+            .current_selection
+            .map_or_else(String::new, |selection| {
+                let (left, top, _, _) = selection.bounds();
+                let (width, height) = selection.size();
+                format!(
+                    "{}, {}, {}, {}",
+                    left.round(),
+                    top.round(),
+                    width.round(),
+                    height.round()
+                )
+            });
+        self.selection_input = Some(input);
+        true
+    }
+
+    fn selection_input_key(&mut self, key: &EditorKey) -> bool {
+        match key {
+            EditorKey::Escape => self.selection_input = None,
+            EditorKey::Enter => {
+                // 无效的输入保留在输入框中，方便修改
+                let input = self.selection_input.take().unwrap_or_default();
+                let parsed = parse_selection_input(
+                    &input,
+                    self.current_selection,
+                    self.ratio,
+                    self.screen_size,
+                );
+                match parsed {
+                    Some(selection) => {
+                        self.change_selection(selection);
+                    }
+                    None => self.selection_input = Some(input),
+                }
+            }
+            EditorKey::Backspace => {
+                if let Some(input) = &mut self.selection_input {
+                    input.pop();
+                }
+            }
+            EditorKey::Character(text) => {
+                if let Some(input) = &mut self.selection_input {
+                    input.extend(text.chars().filter(|c| {
+                        c.is_ascii_digit() || matches!(c, ',' | ' ' | 'x' | 'X' | '×')
+                    }));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // 取色模式下单击任意位置取色，不需要先有选择框
+    fn toggle_color_picker(&mut self) -> bool {
+        if self.app_state != AppState::Idle {
+            return false;
+        }
+        self.color_picker = !self.color_picker;
+        true
+    }
+
+    // Esc：先退出取色模式，再取消选中的图形和绘图工具，没有可取消的内容时退出
+    fn cancel(&mut self) -> (bool, Option<EditorAction>) {
+        if self.color_picker {
+            self.color_picker = false;
+            (true, None)
+        } else if self.selected_shape_index.is_some() {
+            self.selected_shape_index = None;
+            (true, None)
+        } else if self.current_tool != DrawingTool::None {
+            self.current_tool = DrawingTool::None;
+            (true, None)
+        } else {
+            (false, Some(EditorAction::Exit))
+        }
+    }
+
+    fn color_input_key(&mut self, key: &EditorKey) -> bool {
+        match key {
+            EditorKey::Escape => self.color_input = None,
+            EditorKey::Enter => {
+                // 无效的输入保留在输入框中，方便修改
+                let input = self.color_input.take().unwrap_or_default();
+                match parse_hex_color(&input) {
+                    Some(color) => self.apply_style(Some(color), None),
+                    None => self.color_input = Some(input),
+                }
+            }
+            EditorKey::Backspace => {
+                if let Some(input) = &mut self.color_input {
+                    input.pop();
+                }
+            }
+            EditorKey::Character(text) => {
+                if let Some(input) = &mut self.color_input {
+                    for c in text.chars().filter(char::is_ascii_hexdigit) {
+                        if input.len() < 8 {
+                            input.push(c);
+                        }
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn text_editing_key(&mut self, key: &EditorKey, modifiers: KeyModifiers) {
+        if *key == EditorKey::Escape || (*key == EditorKey::Enter && modifiers.ctrl) {
+            self.finish_text_editing();
+            return;
+        }
+
+        let selection_bounds = self.current_selection.map(|sel| sel.bounds());
+        let Some(editing) = &mut self.text_editing else {
+            return;
+        };
+        let Some(shape) = self.shapes.get_mut(editing.index) else {
+            return;
+        };
+
+        match key {
+            EditorKey::Enter => editing.insert(shape, "\n"),
+            EditorKey::Backspace => editing.backspace(shape),
+            EditorKey::Delete => editing.delete(shape),
+            EditorKey::ArrowLeft => editing.move_left(),
+            EditorKey::ArrowRight => editing.move_right(shape),
+            EditorKey::ArrowUp => editing.move_up(shape),
+            EditorKey::ArrowDown => editing.move_down(shape),
+            EditorKey::Home => editing.move_line_start(shape),
+            EditorKey::End => editing.move_line_end(shape),
+            EditorKey::Character(text) if !modifiers.ctrl && !modifiers.alt => {
+                editing.insert(shape, text)
+            }
+            _ => {}
+        }
+        // 文字变长后仍保持在选择区域内
+        if let Some(bounds) = selection_bounds {
+            shape.constrain_to_selection(bounds);
+        }
+    }
+}
+
+fn nudge_distance(modifiers: KeyModifiers) -> f32 {
+    if modifiers.shift {
+        NUDGE_FAR_DISTANCE
+    } else {
+        NUDGE_DISTANCE
+    }
+}
+
+// 解析 "x, y, 宽, 高"，或者只有 "宽x高" 时保持当前选择框的左上角
+// 大小不小于最小选择尺寸，也不超过屏幕，位置留给 constrain_to_screen 限制
+// 固定大小时忽略输入的大小；限制比例时与从左上角拖动右下角手柄相同
+fn parse_selection_input(
+    input: &str,
+    current: Option<Selection>,
+    ratio: SelectionRatio,
+    screen_size: PhysicalSize,
+) -> Option<Selection> {
+    let numbers = input
+        .split([',', ' ', 'x', 'X', '×'])
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (left, top, width, height) = match numbers[..] {
+        [left, top, width, height] => (left, top, width, height),
+        [width, height] => {
+            let (left, top, _, _) = current.map_or((0.0, 0.0, 0.0, 0.0), |sel| sel.bounds());
+            (left, top, width, height)
+        }
+        _ => return None,
+    };
+    let (width, height) = match ratio {
+        SelectionRatio::Fixed(width, height) => (width, height),
+        SelectionRatio::Ratio(..) => resize_with_ratio(
+            ResizeHandle::BottomRight,
+            (0.0, 0.0),
+            (width, height),
+            ratio.aspect()?,
+            MIN_SELECTION_SIZE,
+            screen_size,
+        )
+        .size(),
+        SelectionRatio::Free => (width, height),
+    };
+    let width = width.max(MIN_SELECTION_SIZE).min(screen_size.width_f32());
+    let height = height.max(MIN_SELECTION_SIZE).min(screen_size.height_f32());
+    Some(Selection {
+        start: (left, top),
+        end: (left + width, top + height),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::Color;
+
+    use super::*;
+    use crate::{
+        constants::constants::PASTE_OFFSET,
+        editor::tests::{SELECTION, click, ctrl, editor, press, rectangle},
+        types::drawing::{DrawingShape, TextEditing},
+    };
+
+    const SHAPE: ((f32, f32), (f32, f32)) = ((250.0, 200.0), (300.0, 260.0));
+
+    // 带一个选中矩形的编辑器
+    fn editor_with_shape() -> Editor {
+        let mut editor = editor(Some(SELECTION));
+        editor.shapes.push(rectangle(SHAPE.0, SHAPE.1));
+        click(&mut editor, (275.0, 230.0));
+        assert_eq!(editor.selected_shape_index(), Some(0));
+        editor
+    }
+
+    fn offset(distance: f32) -> DrawingShape {
+        rectangle(
+            (SHAPE.0.0 + distance, SHAPE.0.1 + distance),
+            (SHAPE.1.0 + distance, SHAPE.1.1 + distance),
+        )
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut editor = editor(Some(SELECTION));
+        press(&mut editor, EditorKey::ArrowRight);
+        assert_eq!(editor.selection().unwrap().start, (201.0, 150.0));
+
+        assert!(ctrl(&mut editor, "z").redraw);
+        assert_eq!(editor.selection(), Some(SELECTION));
+        // 已经没有可以撤销的操作
+        assert!(!ctrl(&mut editor, "z").redraw);
+
+        assert!(ctrl(&mut editor, "y").redraw);
+        assert_eq!(editor.selection().unwrap().start, (201.0, 150.0));
+
+        ctrl(&mut editor, "z");
+        let ctrl_shift = KeyModifiers {
+            ctrl: true,
+            shift: true,
+            ..Default::default()
+        };
+        editor.key(&EditorKey::Character("Z".into()), ctrl_shift);
+        assert_eq!(editor.selection().unwrap().start, (201.0, 150.0));
+    }
+
+    #[test]
+    fn delete_removes_selected_shape() {
+        let mut editor = editor_with_shape();
+        assert!(press(&mut editor, EditorKey::Delete).redraw);
+        assert!(editor.shapes().is_empty());
+        assert_eq!(editor.selected_shape_index(), None);
+
+        ctrl(&mut editor, "z");
+        assert_eq!(editor.shapes(), [rectangle(SHAPE.0, SHAPE.1)]);
+
+        // 没有选中图形时不删除
+        assert!(!press(&mut editor, EditorKey::Backspace).redraw);
+        assert_eq!(editor.shapes().len(), 1);
+    }
+
+    #[test]
+    fn duplicate_inserts_offset_copy() {
+        let mut editor = editor_with_shape();
+        assert!(ctrl(&mut editor, "d").redraw);
+        assert_eq!(
+            editor.shapes(),
+            [rectangle(SHAPE.0, SHAPE.1), offset(PASTE_OFFSET)]
+        );
+        assert_eq!(editor.selected_shape_index(), Some(1));
+
+        ctrl(&mut editor, "z");
+        assert_eq!(editor.shapes().len(), 1);
+    }
+
+    #[test]
+    fn copy_and_paste_shapes() {
+        let mut editor = editor_with_shape();
+        assert_eq!(ctrl(&mut editor, "c").action, None);
+
+        // 连续粘贴时依次错开
+        ctrl(&mut editor, "v");
+        ctrl(&mut editor, "v");
+        assert_eq!(
+            editor.shapes(),
+            [
+                rectangle(SHAPE.0, SHAPE.1),
+                offset(PASTE_OFFSET),
+                offset(2.0 * PASTE_OFFSET)
+            ]
+        );
+        assert_eq!(editor.selected_shape_index(), Some(2));
+    }
+
+    #[test]
+    fn copy_without_shape_copies_capture() {
+        let mut editor = editor(Some(SELECTION));
+        assert_eq!(ctrl(&mut editor, "c").action, Some(EditorAction::Copy));
+        // 没有复制过图形时粘贴不做任何事
+        assert!(!ctrl(&mut editor, "v").redraw);
+        assert!(editor.shapes().is_empty());
+    }
+
+    #[test]
+    fn enter_confirms() {
+        let mut editor = editor(Some(SELECTION));
+        assert_eq!(
+            press(&mut editor, EditorKey::Enter).action,
+            Some(EditorAction::Confirm)
+        );
+    }
+
+    #[test]
+    fn escape_cancels_in_order() {
+        let mut editor = editor_with_shape();
+        editor.current_tool = DrawingTool::Rectangle;
+        editor.color_picker = true;
+
+        let response = press(&mut editor, EditorKey::Escape);
+        assert_eq!(response.action, None);
+        assert!(!editor.color_picker);
+
+        press(&mut editor, EditorKey::Escape);
+        assert_eq!(editor.selected_shape_index(), None);
+        assert_eq!(editor.tool(), DrawingTool::Rectangle);
+
+        press(&mut editor, EditorKey::Escape);
+        assert_eq!(editor.tool(), DrawingTool::None);
+
+        let response = press(&mut editor, EditorKey::Escape);
+        assert_eq!(response.action, Some(EditorAction::Exit));
+    }
+
+    #[test]
+    fn escape_closes_help_first() {
+        let mut editor = editor(Some(SELECTION));
+        press(&mut editor, EditorKey::Character("?".into()));
+        assert!(editor.view().help.is_some());
+
+        let response = press(&mut editor, EditorKey::Escape);
+        assert_eq!(response.action, None);
+        assert!(editor.view().help.is_none());
+    }
+
+    #[test]
+    fn arrows_nudge_selected_shape() {
+        let mut editor = editor_with_shape();
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        editor.key(&EditorKey::ArrowDown, shift);
+        press(&mut editor, EditorKey::ArrowRight);
+        assert_eq!(
+            editor.shapes()[0],
+            rectangle((251.0, 210.0), (301.0, 270.0))
+        );
+        assert_eq!(editor.selection(), Some(SELECTION));
+    }
+
+    #[test]
+    fn grow_and_shrink_selection_edges() {
+        let mut editor = editor(Some(SELECTION));
+        let alt = KeyModifiers {
+            alt: true,
+            ..Default::default()
+        };
+        editor.key(&EditorKey::ArrowLeft, alt);
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (199.0, 150.0, 500.0, 400.0)
+        );
+
+        let ctrl_shift = KeyModifiers {
+            ctrl: true,
+            shift: true,
+            ..Default::default()
+        };
+        editor.key(&EditorKey::ArrowUp, ctrl_shift);
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (199.0, 150.0, 500.0, 390.0)
+        );
+    }
+
+    #[test]
+    fn selection_input_sets_position_and_size() {
+        let mut editor = editor(Some(SELECTION));
+        press(&mut editor, EditorKey::Character("g".into()));
+        assert_eq!(
+            editor.view().selection_input.as_deref(),
+            Some("200, 150, 300, 250")
+        );
+
+        editor.selection_input = Some(String::new());
+        press(&mut editor, EditorKey::Character("10, 20, 640x480".into()));
+        press(&mut editor, EditorKey::Enter);
+        assert_eq!(editor.view().selection_input, None);
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (10.0, 20.0, 650.0, 500.0)
+        );
+    }
+
+    #[test]
+    fn fixed_size_ignores_edge_keys() {
+        let mut editor = editor(Some(SELECTION));
+        editor.ratio = SelectionRatio::Fixed(300.0, 250.0);
+        for modifiers in [
+            KeyModifiers {
+                alt: true,
+                ..Default::default()
+            },
+            KeyModifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+        ] {
+            for key in [EditorKey::ArrowLeft, EditorKey::ArrowDown] {
+                assert!(!editor.key(&key, modifiers).redraw);
+            }
+        }
+        assert_eq!(editor.selection(), Some(SELECTION));
+        assert!(!editor.history().can_undo());
+    }
+
+    #[test]
+    fn ratio_edge_keys_keep_aspect() {
+        let selection = Selection {
+            start: (200.0, 150.0),
+            end: (520.0, 330.0),
+        };
+        let mut editor = editor(Some(selection));
+        editor.ratio = SelectionRatio::Ratio(16.0, 9.0);
+        let aspect = |editor: &Editor| {
+            let (width, height) = editor.selection().unwrap().size();
+            width / height
+        };
+
+        // 扩大右边：左边不动，上下以中心对称扩展
+        let alt_shift = KeyModifiers {
+            alt: true,
+            shift: true,
+            ..Default::default()
+        };
+        assert!(editor.key(&EditorKey::ArrowRight, alt_shift).redraw);
+        let (left, top, right, bottom) = editor.selection().unwrap().bounds();
+        assert_eq!((left, right), (200.0, 530.0));
+        assert!(((top + bottom) / 2.0 - 240.0).abs() < 1e-3);
+        assert!((aspect(&editor) - 16.0 / 9.0).abs() < 1e-4);
+
+        // 向上缩小：上边不动
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        assert!(editor.key(&EditorKey::ArrowUp, ctrl).redraw);
+        assert_eq!(editor.selection().unwrap().bounds().1, top);
+        assert!((editor.selection().unwrap().size().1 - (bottom - top - 1.0)).abs() < 1e-3);
+        assert!((aspect(&editor) - 16.0 / 9.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parse_free_selection_input() {
+        let screen = PhysicalSize::new(800, 600);
+        let parse = |input: &str| {
+            parse_selection_input(input, Some(SELECTION), SelectionRatio::Free, screen)
+        };
+        let bounds = |input: &str| parse(input).map(|selection| selection.bounds());
+
+        assert_eq!(bounds("10, 20, 300, 200"), Some((10.0, 20.0, 310.0, 220.0)));
+        assert_eq!(bounds("10 20 300x200"), Some((10.0, 20.0, 310.0, 220.0)));
+        // 只有大小时保持当前的左上角
+        assert_eq!(bounds("300x200"), Some((200.0, 150.0, 500.0, 350.0)));
+        assert_eq!(bounds("300 × 200"), Some((200.0, 150.0, 500.0, 350.0)));
+        // 大小限制在最小尺寸和屏幕之间
+        assert_eq!(bounds("0, 0, 5, 5"), Some((0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(bounds("0, 0, 9999, 9999"), Some((0.0, 0.0, 800.0, 600.0)));
+
+        for input in ["", "1, 2, 3", "1, 2, 3, 4, 5", "a, b", "10,,20,30,"] {
+            assert_eq!(parse(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_selection_input_keeps_fixed_size() {
+        let screen = PhysicalSize::new(800, 600);
+        let fixed = SelectionRatio::Fixed(320.0, 240.0);
+        let bounds = |input: &str| {
+            parse_selection_input(input, Some(SELECTION), fixed, screen)
+                .map(|selection| selection.bounds())
+        };
+        assert_eq!(bounds("10, 20, 999, 999"), Some((10.0, 20.0, 330.0, 260.0)));
+        assert_eq!(bounds("640x480"), Some((200.0, 150.0, 520.0, 390.0)));
+    }
+
+    #[test]
+    fn parse_selection_input_keeps_ratio() {
+        let screen = PhysicalSize::new(800, 600);
+        let ratio = SelectionRatio::Ratio(16.0, 9.0);
+        let size = |input: &str| {
+            parse_selection_input(input, Some(SELECTION), ratio, screen)
+                .map(|selection| selection.size())
+        };
+        // 与拖动右下角手柄相同，取拖动距离较大的一边
+        assert_eq!(size("0, 0, 320, 90"), Some((320.0, 180.0)));
+        assert_eq!(size("0, 0, 100, 180"), Some((320.0, 180.0)));
+        // 放不下时缩小到屏幕内
+        assert_eq!(size("0, 0, 1600, 100"), Some((800.0, 450.0)));
+    }
+
+    #[test]
+    fn invalid_selection_input_stays_open() {
+        let mut editor = editor(Some(SELECTION));
+        editor.selection_input = Some("1, 2, 3".into());
+        press(&mut editor, EditorKey::Enter);
+        assert_eq!(editor.view().selection_input.as_deref(), Some("1, 2, 3"));
+        assert_eq!(editor.selection(), Some(SELECTION));
+    }
+
+    #[test]
+    fn arrows_move_text_caret_between_lines() {
+        let mut editor = editor(Some(SELECTION));
+        editor.shapes.push(DrawingShape::Text {
+            position: (250.0, 200.0),
+            content: "截图中\nab".into(),
+            font_size: 20.0,
+            color: Color::RED,
+            background: None,
+        });
+        editor.text_editing = Some(TextEditing {
+            index: 0,
+            caret: 3,
+            before: None,
+        });
+
+        // 编辑文字时方向键移动光标，而不是移动选择框
+        press(&mut editor, EditorKey::ArrowDown);
+        assert_eq!(editor.view().text_caret, Some((0, 6)));
+        press(&mut editor, EditorKey::ArrowUp);
+        assert_eq!(editor.view().text_caret, Some((0, 2)));
+        assert_eq!(editor.selection(), Some(SELECTION));
+    }
+}
@@ -0,0 +1,660 @@
+pub mod input;
+pub mod keymap;
+mod keys;
+mod pointer;
+pub mod view;
+
+use skia_safe::{Color, Image as SkiaImage};
+use winit::window::CursorIcon;
+
+pub use self::input::{EditorKey, KeyModifiers, PointerButton};
+pub use self::keymap::{EditorCommand, KeyChord, Keymap};
+use self::view::EditorView;
+use crate::{
+    constants::constants::PASTE_OFFSET,
+    coords::{PhysicalSize, ScaleFactor},
+    history::{Command, History},
+    picker::{ColorFormat, SAMPLE_SIZES},
+    rendering::overlay::DEFAULT_MASK_COLOR,
+    shapes::{
+        redact::{
+            DEFAULT_BLUR_SIGMA, DEFAULT_MOSAIC_BLOCK_SIZE, MAX_BLUR_SIGMA, MAX_MOSAIC_BLOCK_SIZE,
+            MIN_BLUR_SIGMA, MIN_MOSAIC_BLOCK_SIZE,
+        },
+        style::shows_property_bar,
+        text::DEFAULT_FONT_SIZE,
+    },
+    snapping::EdgeAnalysis,
+    types::{
+        app_state::{AppState, ResizeHandle},
+        drawing::{DrawingShape, DrawingTool, RedactAreaKind, ShapeStyle, TextEditing},
+        ui::{DEFAULT_RATIOS, Selection, SelectionRatio},
+    },
+    utils::cursor::CursorManager,
+};
+
+// 需要由界面层完成的操作（涉及文件、剪贴板或窗口）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorAction {
+    Save,
+    Copy,
+    // 按 Enter 确认选择，执行配置的截图后动作
+    Confirm,
+    // 复制取色器取到的颜色，内容见 Editor::picked_color_text
+    CopyColor,
+    Exit,
+}
+
+// 新建编辑器时的默认值，来自配置文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorSettings {
+    pub style: ShapeStyle,
+    pub font_size: f32,
+    pub text_background: bool,
+    pub mosaic_block_size: f32,
+    pub blur_sigma: f32,
+    pub mask_color: Color,
+    pub keymap: Keymap,
+    // 工具栏比例按钮依次切换的选择框限制
+    pub ratios: Vec<SelectionRatio>,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            style: ShapeStyle::default(),
+            font_size: DEFAULT_FONT_SIZE,
+            text_background: false,
+            mosaic_block_size: DEFAULT_MOSAIC_BLOCK_SIZE,
+            blur_sigma: DEFAULT_BLUR_SIGMA,
+            mask_color: DEFAULT_MASK_COLOR,
+            keymap: Keymap::default(),
+            ratios: DEFAULT_RATIOS.to_vec(),
+        }
+    }
+}
+
+// 一次输入事件的处理结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EditorResponse {
+    // 画面是否有变化
+    pub redraw: bool,
+    // 处理后应显示的光标
+    pub cursor: CursorIcon,
+    pub action: Option<EditorAction>,
+}
+
+/// 截图编辑器的交互状态机：选择框、绘图工具、标注和编辑历史
+/// 所有坐标都是截图的物理像素，界面层只负责转发输入事件
+pub struct Editor {
+    screenshot: SkiaImage,
+    screen_size: PhysicalSize,
+    // 截图物理像素与逻辑像素之比，工具栏、属性栏、手柄和放大镜按逻辑像素布局
+    ui_scale: ScaleFactor,
+    mouse_pos: (f32, f32),
+    app_state: AppState,
+
+    // 选择框，以及拖拽、调整大小时的锚点
+    current_selection: Option<Selection>,
+    temp_selection: Option<Selection>,
+    drag_offset: Option<(f32, f32)>,
+    resize_handle: Option<ResizeHandle>,
+    resize_anchor: Option<(f32, f32)>,
+
+    // 绘图工具与标注
+    current_tool: DrawingTool,
+    shapes: Vec<DrawingShape>,
+    current_drawing: Option<DrawingShape>,
+    selected_shape_index: Option<usize>,
+    shape_drag_offset: Option<(f32, f32)>,
+    shape_resize_handle: Option<ResizeHandle>,
+    shape_resize_anchor: Option<(f32, f32)>,
+
+    // 撤销/重做历史，以及拖拽开始时的快照（每次拖拽只记录一条历史）
+    history: History,
+    shape_before_edit: Option<DrawingShape>,
+    selection_before_edit: Option<Selection>,
+
+    // 文字标注编辑状态，以及新建文字的字号
+    text_editing: Option<TextEditing>,
+    text_background: bool,
+    font_size: f32,
+
+    // 马赛克和模糊的区域形状与强度
+    mosaic_area: RedactAreaKind,
+    mosaic_block_size: f32,
+    blur_area: RedactAreaKind,
+    blur_sigma: f32,
+
+    // 新建图形的颜色与线宽，以及正在输入的自定义颜色
+    shape_style: ShapeStyle,
+    color_input: Option<String>,
+
+    // 正在输入的选择框位置和大小（x, y, 宽, 高）
+    selection_input: Option<String>,
+
+    // 会话内复制的图形（Ctrl+C / Ctrl+V）
+    shape_clipboard: Option<DrawingShape>,
+
+    mask_color: Color,
+
+    // 截图时的顶层窗口区域（从上到下），以及还没有选择框时鼠标下的窗口
+    windows: Vec<Selection>,
+    hovered_window: Option<Selection>,
+
+    // 快捷键，以及快捷键帮助和选择框调整手柄的显示状态
+    keymap: Keymap,
+    show_help: bool,
+    show_handles: bool,
+
+    // 截图中的直线（后台分析），以及当前按住的修饰键（按住 Alt 时不吸附）
+    edges: EdgeAnalysis,
+    modifiers: KeyModifiers,
+
+    // 取色模式、复制的颜色格式、取色范围，以及最近一次取到的颜色
+    color_picker: bool,
+    color_format: ColorFormat,
+    sample_size: u32,
+    picked_color: Option<Color>,
+
+    // 可选的选择框限制和当前使用的限制，按住 Shift 时锁定当前比例
+    ratios: Vec<SelectionRatio>,
+    ratio: SelectionRatio,
+}
+
+impl Editor {
+    pub fn new(
+        screenshot: SkiaImage,
+        selection: Option<Selection>,
+        settings: &EditorSettings,
+    ) -> Self {
+        Self {
+            screen_size: PhysicalSize::of_image(&screenshot),
+            ui_scale: ScaleFactor::IDENTITY,
+            edges: EdgeAnalysis::start(&screenshot),
+            modifiers: KeyModifiers::default(),
+            color_picker: false,
+            color_format: ColorFormat::default(),
+            sample_size: SAMPLE_SIZES[0],
+            picked_color: None,
+            ratios: settings.ratios.clone(),
+            ratio: SelectionRatio::Free,
+            screenshot,
+            mouse_pos: (0.0, 0.0),
+            app_state: AppState::Idle,
+            current_selection: selection,
+            temp_selection: None,
+            drag_offset: None,
+            resize_handle: None,
+            resize_anchor: None,
+            current_tool: DrawingTool::None,
+            shapes: Vec::new(),
+            current_drawing: None,
+            selected_shape_index: None,
+            shape_drag_offset: None,
+            shape_resize_handle: None,
+            shape_resize_anchor: None,
+            history: History::default(),
+            shape_before_edit: None,
+            selection_before_edit: None,
+            text_editing: None,
+            text_background: settings.text_background,
+            font_size: settings.font_size,
+            mosaic_area: RedactAreaKind::Rectangle,
+            mosaic_block_size: settings.mosaic_block_size,
+            blur_area: RedactAreaKind::Rectangle,
+            blur_sigma: settings.blur_sigma,
+            shape_style: settings.style,
+            color_input: None,
+            selection_input: None,
+            shape_clipboard: None,
+            mask_color: settings.mask_color,
+            windows: Vec::new(),
+            hovered_window: None,
+            keymap: settings.keymap.clone(),
+            show_help: false,
+            show_handles: true,
+        }
+    }
+
+    // 替换截图并清空所有标注，选择框覆盖整张图片
+    pub fn open_image(&mut self, image: SkiaImage) {
+        let image_size = PhysicalSize::of_image(&image);
+        self.edges = EdgeAnalysis::start(&image);
+        self.screenshot = image;
+        self.screen_size = image_size;
+        self.current_selection = Some(Selection::covering(image_size));
+        self.temp_selection = None;
+        self.shapes.clear();
+        self.current_drawing = None;
+        self.selected_shape_index = None;
+        self.text_editing = None;
+        self.history.clear();
+        // 输入框、取色模式和快捷键帮助属于上一张图片的操作，一并关闭
+        self.color_input = None;
+        self.selection_input = None;
+        self.color_picker = false;
+        self.show_help = false;
+        self.windows.clear();
+        self.hovered_window = None;
+        self.app_state = AppState::Idle;
+    }
+
+    // 设置可以单击选中的窗口区域，打开图片时没有窗口
+    pub fn set_windows(&mut self, windows: Vec<Selection>) {
+        self.windows = windows;
+        self.hovered_window = None;
+    }
+
+    // 设置界面缩放比例，通常是虚拟桌面的缩放比例
+    pub fn set_ui_scale(&mut self, scale: ScaleFactor) {
+        self.ui_scale = scale;
+    }
+
+    // 修饰键状态变化；拖拽选择框时立即按新状态重新吸附和锁定比例
+    pub fn set_modifiers(&mut self, modifiers: KeyModifiers) -> EditorResponse {
+        let changed =
+            modifiers.alt != self.modifiers.alt || modifiers.shift != self.modifiers.shift;
+        self.modifiers = modifiers;
+        if changed && matches!(self.app_state, AppState::Selecting | AppState::Resizing) {
+            return self.pointer_move(self.mouse_pos);
+        }
+        self.respond(false, None)
+    }
+
+    pub fn screenshot(&self) -> &SkiaImage {
+        &self.screenshot
+    }
+
+    pub fn screen_size(&self) -> PhysicalSize {
+        self.screen_size
+    }
+
+    pub fn state(&self) -> AppState {
+        self.app_state
+    }
+
+    pub fn selection(&self) -> Option<Selection> {
+        self.current_selection
+    }
+
+    pub fn tool(&self) -> DrawingTool {
+        self.current_tool
+    }
+
+    pub fn shapes(&self) -> &[DrawingShape] {
+        &self.shapes
+    }
+
+    pub fn selected_shape_index(&self) -> Option<usize> {
+        self.selected_shape_index
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    // 按当前格式写出最近一次取到的颜色
+    pub fn picked_color_text(&self) -> Option<String> {
+        self.picked_color
+            .map(|color| self.color_format.format(color))
+    }
+
+    // 当前鼠标位置和状态下应显示的光标
+    pub fn cursor(&self) -> CursorIcon {
+        if self.color_picker {
+            return CursorIcon::Crosshair;
+        }
+        CursorManager::get_cursor_icon(
+            self.mouse_pos,
+            self.app_state,
+            self.current_selection,
+            self.current_tool,
+            &self.shapes,
+            self.selected_shape_index,
+            self.resize_handle,
+            self.shape_resize_handle,
+            self.screen_size,
+            self.ui_scale,
+        )
+    }
+
+    // 当前画面的快照，可以在界面线程之外绘制
+    pub fn view(&self) -> EditorView {
+        let selected_shape = self.selected_shape();
+        let property_style = selected_shape
+            .as_ref()
+            .map_or(self.shape_style, |shape| shape.style_or(self.shape_style));
+
+        EditorView {
+            screenshot: self.screenshot.clone(),
+            screen_size: self.screen_size,
+            ui_scale: self.ui_scale,
+            mouse_pos: self.mouse_pos,
+            state: self.app_state,
+            // 还没有选择框时高亮鼠标下的窗口
+            selection: self
+                .current_selection
+                .or(self.temp_selection)
+                .or(self.hovered_window),
+            // 只有在空闲且没有选择绘图工具时才显示选择框的调整手柄，可以用快捷键隐藏
+            // 固定大小时只能移动，不显示手柄
+            selection_handles: self.show_handles
+                && !matches!(self.ratio, SelectionRatio::Fixed(..))
+                && self.current_selection.is_some()
+                && self.app_state == AppState::Idle
+                && self.current_tool == DrawingTool::None,
+            shapes: self.shapes.clone(),
+            selected_shape: self.selected_shape_index,
+            drawing: self.current_drawing.clone(),
+            text_caret: self
+                .text_editing
+                .as_ref()
+                .map(|editing| (editing.index, editing.caret)),
+            property_bar: shows_property_bar(self.current_tool, selected_shape.as_ref())
+                .then_some(property_style),
+            color_input: self.color_input.clone(),
+            selection_input: self.selection_input.clone(),
+            ratio: self.ratio,
+            mask_color: self.mask_color,
+            help: self.show_help.then(|| self.keymap.entries()),
+            magnifier: self.color_picker
+                || matches!(
+                    self.app_state,
+                    AppState::Selecting | AppState::Resizing | AppState::Drawing
+                ),
+            picker: self
+                .color_picker
+                .then_some((self.color_format, self.sample_size)),
+        }
+    }
+
+    fn respond(&self, redraw: bool, action: Option<EditorAction>) -> EditorResponse {
+        EditorResponse {
+            redraw,
+            cursor: self.cursor(),
+            action,
+        }
+    }
+
+    fn selected_shape(&self) -> Option<DrawingShape> {
+        self.selected_shape_index
+            .and_then(|index| self.shapes.get(index).cloned())
+    }
+
+    // 结束文字编辑：空文字直接删除，否则记录一条历史
+    fn finish_text_editing(&mut self) -> bool {
+        let Some(editing) = self.text_editing.take() else {
+            return false;
+        };
+        let Some(shape) = self.shapes.get(editing.index).cloned() else {
+            return true;
+        };
+
+        if shape.is_empty_text() {
+            self.shapes.remove(editing.index);
+            self.selected_shape_index = None;
+            if let Some(before) = editing.before {
+                self.history.push(Command::DeleteShape {
+                    index: editing.index,
+                    shape: before,
+                });
+            }
+        } else if let Some(before) = editing.before {
+            self.history.push(Command::EditText {
+                index: editing.index,
+                before,
+                after: shape,
+            });
+        } else {
+            self.history.push(Command::AddShape {
+                index: editing.index,
+                shape,
+            });
+        }
+        true
+    }
+
+    // 修改颜色或线宽：作用于选中的图形，同时作为之后新建图形的默认值
+    fn apply_style(&mut self, color: Option<Color>, stroke_width: Option<f32>) {
+        if let Some(color) = color {
+            self.shape_style.color = color;
+        }
+        if let Some(stroke_width) = stroke_width {
+            self.shape_style.stroke_width = stroke_width;
+        }
+
+        let Some(index) = self.selected_shape_index else {
+            return;
+        };
+        let Some(before) = self.shapes.get(index).cloned() else {
+            return;
+        };
+        let mut after = before.clone();
+        if let Some(color) = color {
+            after.set_color(color);
+        }
+        if let Some(stroke_width) = stroke_width {
+            after.set_stroke_width(stroke_width);
+        }
+        self.shapes[index] = after.clone();
+        self.history.push(Command::RestyleShape {
+            index,
+            before,
+            after,
+        });
+    }
+
+    // 撤销/重做后清理依赖于图形下标的临时状态
+    fn apply_history(&mut self, redo: bool) -> bool {
+        if self.app_state != AppState::Idle {
+            return false;
+        }
+
+        let changed = if redo {
+            self.history
+                .redo(&mut self.shapes, &mut self.current_selection)
+        } else {
+            self.history
+                .undo(&mut self.shapes, &mut self.current_selection)
+        };
+
+        if changed {
+            self.selected_shape_index = None;
+            self.current_drawing = None;
+        }
+        changed
+    }
+
+    // 删除选中的图形
+    fn delete_selected_shape(&mut self) -> bool {
+        if self.app_state != AppState::Idle {
+            return false;
+        }
+        let Some(index) = self.selected_shape_index.take() else {
+            return false;
+        };
+        if index < self.shapes.len() {
+            let shape = self.shapes.remove(index);
+            self.history.push(Command::DeleteShape { index, shape });
+        }
+        true
+    }
+
+    // 在原图形基础上偏移后插入副本并选中，副本限制在选择区域内
+    fn insert_shape_copy(&mut self, shape: &DrawingShape) -> Option<DrawingShape> {
+        if self.app_state != AppState::Idle {
+            return None;
+        }
+        let selection = self.current_selection?;
+        let mut copy = shape.clone();
+        copy.translate(PASTE_OFFSET, PASTE_OFFSET);
+        copy.constrain_to_selection(selection.bounds());
+
+        self.shapes.push(copy.clone());
+        let index = self.shapes.len() - 1;
+        self.history.push(Command::AddShape {
+            index,
+            shape: copy.clone(),
+        });
+        self.selected_shape_index = Some(index);
+        Some(copy)
+    }
+
+    // 调整打码强度（马赛克块大小或模糊半径）：选中打码图形时作用于该图形，
+    // 否则按当前工具调整之后新建图形的默认值
+    fn adjust_redact_strength(&mut self, delta: f32) {
+        let selected = self
+            .selected_shape_index
+            .and_then(|index| Some((index, self.shapes.get(index)?.clone())));
+
+        let Some((index, before)) = selected else {
+            match self.current_tool {
+                DrawingTool::Mosaic => {
+                    self.mosaic_block_size = (self.mosaic_block_size + delta)
+                        .clamp(MIN_MOSAIC_BLOCK_SIZE, MAX_MOSAIC_BLOCK_SIZE);
+                }
+                DrawingTool::Blur => {
+                    self.blur_sigma =
+                        (self.blur_sigma + delta).clamp(MIN_BLUR_SIGMA, MAX_BLUR_SIGMA);
+                }
+                _ => {}
+            }
+            return;
+        };
+
+        let mut after = before.clone();
+        match &mut after {
+            DrawingShape::Mosaic { block_size, .. } => {
+                *block_size =
+                    (*block_size + delta).clamp(MIN_MOSAIC_BLOCK_SIZE, MAX_MOSAIC_BLOCK_SIZE);
+                self.mosaic_block_size = *block_size;
+            }
+            DrawingShape::Blur { sigma, .. } => {
+                *sigma = (*sigma + delta).clamp(MIN_BLUR_SIGMA, MAX_BLUR_SIGMA);
+                self.blur_sigma = *sigma;
+            }
+            _ => return,
+        }
+
+        self.shapes[index] = after.clone();
+        self.history.push(Command::RestyleShape {
+            index,
+            before,
+            after,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::constants::{BUTTON_SPACING, BUTTON_WIDTH},
+        test_support::solid_image,
+        types::ui::{Toolbar, ToolbarButton},
+    };
+
+    pub(super) const SCREEN: (i32, i32) = (800, 600);
+    pub(super) const SELECTION: Selection = Selection {
+        start: (200.0, 150.0),
+        end: (500.0, 400.0),
+    };
+
+    pub(super) fn editor(selection: Option<Selection>) -> Editor {
+        let image = solid_image(SCREEN.0, SCREEN.1, Color::WHITE);
+        Editor::new(image, selection, &EditorSettings::default())
+    }
+
+    pub(super) fn drag(editor: &mut Editor, from: (f32, f32), to: (f32, f32)) -> EditorResponse {
+        editor.pointer_down(from, PointerButton::Primary);
+        editor.pointer_move(to);
+        editor.pointer_up(to)
+    }
+
+    pub(super) fn click(editor: &mut Editor, pos: (f32, f32)) -> EditorResponse {
+        editor.pointer_down(pos, PointerButton::Primary);
+        editor.pointer_up(pos)
+    }
+
+    // 工具栏按钮的中心（截图的物理像素）
+    pub(super) fn button_center(editor: &Editor, button: ToolbarButton) -> (f32, f32) {
+        let scale = editor.ui_scale;
+        let selection = editor.selection().unwrap().to_logical(scale);
+        let toolbar = Toolbar::calculate(&selection, editor.screen_size().to_logical(scale));
+        let index = ToolbarButton::ALL
+            .iter()
+            .position(|candidate| *candidate == button)
+            .unwrap();
+        (
+            (toolbar.x + index as f32 * (BUTTON_WIDTH + BUTTON_SPACING) + BUTTON_WIDTH / 2.0)
+                * scale.get(),
+            (toolbar.y + toolbar.height / 2.0) * scale.get(),
+        )
+    }
+
+    pub(super) fn press(editor: &mut Editor, key: EditorKey) -> EditorResponse {
+        editor.key(&key, KeyModifiers::default())
+    }
+
+    pub(super) fn ctrl(editor: &mut Editor, c: &str) -> EditorResponse {
+        let modifiers = KeyModifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        editor.key(&EditorKey::Character(c.into()), modifiers)
+    }
+
+    pub(super) fn rectangle(start: (f32, f32), end: (f32, f32)) -> DrawingShape {
+        DrawingShape::Rectangle {
+            start,
+            end,
+            color: Color::RED,
+            stroke_width: 3.0,
+        }
+    }
+
+    #[test]
+    fn open_image_resets_transient_modes() {
+        let mut editor = editor(Some(SELECTION));
+        editor
+            .shapes
+            .push(rectangle((250.0, 200.0), (300.0, 260.0)));
+        editor.color_input = Some("ff".into());
+        editor.selection_input = Some("1, 2".into());
+        editor.color_picker = true;
+        editor.show_help = true;
+
+        editor.open_image(solid_image(320, 200, Color::BLACK));
+
+        assert_eq!(editor.screen_size(), PhysicalSize::new(320, 200));
+        assert_eq!(
+            editor.selection(),
+            Some(Selection::covering(PhysicalSize::new(320, 200)))
+        );
+        assert!(editor.shapes().is_empty());
+        assert!(!editor.history().can_undo());
+        let view = editor.view();
+        assert_eq!(view.color_input, None);
+        assert_eq!(view.selection_input, None);
+        assert_eq!(view.picker, None);
+        assert!(view.help.is_none());
+        assert!(!view.magnifier);
+    }
+
+    #[test]
+    fn modifiers_reapply_while_selecting() {
+        let mut editor = editor(None);
+        editor.pointer_down((100.0, 100.0), PointerButton::Primary);
+        editor.pointer_move((300.0, 150.0));
+        assert_eq!(editor.temp_selection.unwrap().size(), (200.0, 50.0));
+
+        // 按下 Shift 后立即锁定为 1:1
+        let response = editor.set_modifiers(KeyModifiers {
+            shift: true,
+            ..Default::default()
+        });
+        assert!(response.redraw);
+        assert_eq!(editor.temp_selection.unwrap().size(), (200.0, 200.0));
+    }
+}
@@ -0,0 +1,1318 @@
+use super::{Editor, EditorAction, EditorResponse, PointerButton};
+use crate::{
+    constants::constants::MIN_SELECTION_SIZE,
+    geometry::{
+        constrain_to_screen, corner_toward, get_resize_anchor, get_resize_handle, point_in_rect,
+        resize_with_ratio,
+    },
+    history::Command,
+    picker::sample_color,
+    shapes::{
+        redact::DEFAULT_REDACT_BRUSH_WIDTH, style::shows_property_bar, text::TEXT_BACKGROUND_COLOR,
+    },
+    types::{
+        app_state::{AppState, ResizeHandle},
+        drawing::{DrawingShape, DrawingTool, RedactArea, RedactAreaKind, ShapeStyle, TextEditing},
+        ui::{PropertyBar, PropertyButton, Selection, SelectionRatio, Toolbar, ToolbarButton},
+    },
+    windows::window_at,
+};
+
+impl Editor {
+    // 鼠标按下：工具栏、属性栏、选择框手柄、图形手柄、图形本身依次优先
+    pub fn pointer_down(&mut self, pos: (f32, f32), button: PointerButton) -> EditorResponse {
+        self.mouse_pos = pos;
+        if button == PointerButton::Secondary {
+            return self.respond(false, Some(EditorAction::Exit));
+        }
+
+        // 点击任意位置都会结束自定义颜色输入和选择框输入
+        let editing_color = self.color_input.take().is_some();
+        if self.selection_input.take().is_some() {
+            return self.respond(true, None);
+        }
+        let finished_text = self.finish_text_editing();
+
+        if self.color_picker {
+            return self.pick_color(pos);
+        }
+
+        let Some(selection) = self.current_selection else {
+            // 没有选择框时，允许新建选择
+            self.app_state = AppState::Selecting;
+            self.resize_anchor = Some(pos);
+            self.temp_selection = Some(self.new_selection(pos, pos));
+            self.current_selection = None;
+            self.current_tool = DrawingTool::None;
+            self.selected_shape_index = None;
+            return self.respond(true, None);
+        };
+
+        let (toolbar, property_bar) = self.bars(&selection);
+        let ui_pos = self.to_ui(pos);
+
+        // 1. 检查工具栏按钮点击
+        if let Some(button) = toolbar.get_button(ui_pos.0, ui_pos.1) {
+            let action = self.press_toolbar_button(button);
+            return self.respond(true, action);
+        }
+
+        let tool = self.current_tool;
+
+        // 属性栏：颜色与线宽
+        if shows_property_bar(tool, self.selected_shape().as_ref()) {
+            if let Some(button) = property_bar.get_button(ui_pos.0, ui_pos.1) {
+                match button {
+                    PropertyButton::Color(color) => self.apply_style(Some(color), None),
+                    PropertyButton::CustomColor => {
+                        // 再次点击自定义颜色按钮时收起输入框
+                        if !editing_color {
+                            self.color_input = Some(String::new());
+                        }
+                    }
+                    PropertyButton::StrokeWidth(width) => self.apply_style(None, Some(width)),
+                }
+                return self.respond(true, None);
+            }
+        }
+
+        // 2. 只有在没有绘图工具时才检查选择框的调整大小手柄，固定大小时只能移动
+        if tool == DrawingTool::None && !matches!(self.ratio, SelectionRatio::Fixed(..)) {
+            if let Some(handle) = get_resize_handle(pos.0, pos.1, &selection, self.ui_scale) {
+                self.app_state = AppState::Resizing;
+                self.selection_before_edit = Some(selection);
+                self.resize_handle = Some(handle);
+                self.resize_anchor = Some(get_resize_anchor(handle, &selection));
+                return self.respond(true, None);
+            }
+        }
+
+        // 3. 点击在选择框外部 - 直接忽略，不做任何操作
+        if !point_in_rect(pos.0, pos.1, &selection) {
+            return self.respond(editing_color || finished_text, None);
+        }
+
+        // 绝对优先检查选中图形的调整手柄，然后检查任何图形的调整手柄（不管是否选中）
+        let selected_handle = self.selected_shape_index.and_then(|index| {
+            let handle = self
+                .shapes
+                .get(index)?
+                .get_resize_handle(pos.0, pos.1, self.ui_scale)?;
+            Some((index, handle))
+        });
+        let handle_hit = selected_handle.or_else(|| {
+            self.shapes.iter().enumerate().rev().find_map(|(i, shape)| {
+                shape
+                    .get_resize_handle(pos.0, pos.1, self.ui_scale)
+                    .map(|handle| (i, handle))
+            })
+        });
+        if let Some((index, handle)) = handle_hit {
+            let shape = &self.shapes[index];
+            self.shape_before_edit = Some(shape.clone());
+            self.shape_resize_anchor = Some(shape.get_resize_anchor(handle));
+            self.shape_resize_handle = Some(handle);
+            self.selected_shape_index = Some(index);
+            self.app_state = AppState::ResizingShape;
+            return self.respond(true, None);
+        }
+
+        // 文字工具：点击已有文字进入编辑，否则在点击处新建文字
+        if tool == DrawingTool::Text {
+            self.begin_text_editing(pos, &selection);
+            return self.respond(true, None);
+        }
+
+        // 点击了图形本身，进入编辑模式
+        let shape_hit = self
+            .shapes
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, shape)| shape.contains_point(pos.0, pos.1))
+            .map(|(i, shape)| (i, shape.clone()));
+        if let Some((index, shape)) = shape_hit {
+            let (left, top, _, _) = shape.bounds();
+            self.selected_shape_index = Some(index);
+            self.app_state = AppState::EditingShape;
+            self.shape_drag_offset = Some((pos.0 - left, pos.1 - top));
+            self.shape_before_edit = Some(shape);
+            return self.respond(true, None);
+        }
+
+        if tool != DrawingTool::None {
+            // 如果没有点击到图形，开始新的绘制
+            if let Some(shape) = self.new_shape(tool, pos) {
+                self.app_state = AppState::Drawing;
+                self.current_drawing = Some(shape);
+            }
+        } else {
+            // 没有点击到图形，开始拖拽选择框
+            self.app_state = AppState::Dragging;
+            self.selection_before_edit = Some(selection);
+            let (left, top, _, _) = selection.bounds();
+            self.drag_offset = Some((pos.0 - left, pos.1 - top));
+        }
+        self.respond(true, None)
+    }
+
+    // 取色：复制颜色并作为之后新建图形的颜色，然后退出取色模式
+    fn pick_color(&mut self, pos: (f32, f32)) -> EditorResponse {
+        let pixel = (pos.0.floor() as i32, pos.1.floor() as i32);
+        let Some(color) = sample_color(&self.screenshot, pixel, self.sample_size) else {
+            return self.respond(false, None);
+        };
+        self.picked_color = Some(color);
+        self.shape_style.color = color;
+        self.color_picker = false;
+        self.respond(true, Some(EditorAction::CopyColor))
+    }
+
+    // 工具栏按钮：切换工具，或返回需要界面层完成的操作
+    pub(super) fn press_toolbar_button(&mut self, button: ToolbarButton) -> Option<EditorAction> {
+        let tool = match button {
+            ToolbarButton::Rectangle => DrawingTool::Rectangle,
+            ToolbarButton::Circle => DrawingTool::Circle,
+            ToolbarButton::Arrow => DrawingTool::Arrow,
+            ToolbarButton::Brush => DrawingTool::Brush,
+            ToolbarButton::Text => {
+                // 再次点击文字工具时切换背景框
+                if self.current_tool == DrawingTool::Text {
+                    self.text_background = !self.text_background;
+                }
+                DrawingTool::Text
+            }
+            ToolbarButton::Mosaic => {
+                // 再次点击马赛克工具时在矩形和笔刷区域之间切换
+                if self.current_tool == DrawingTool::Mosaic {
+                    self.mosaic_area = match self.mosaic_area {
+                        RedactAreaKind::Rectangle => RedactAreaKind::Brush,
+                        _ => RedactAreaKind::Rectangle,
+                    };
+                }
+                DrawingTool::Mosaic
+            }
+            ToolbarButton::Blur => {
+                // 再次点击模糊工具时在矩形和椭圆区域之间切换
+                if self.current_tool == DrawingTool::Blur {
+                    self.blur_area = match self.blur_area {
+                        RedactAreaKind::Rectangle => RedactAreaKind::Ellipse,
+                        _ => RedactAreaKind::Rectangle,
+                    };
+                }
+                DrawingTool::Blur
+            }
+            ToolbarButton::Ratio => {
+                self.next_ratio();
+                return None;
+            }
+            ToolbarButton::Save => return Some(EditorAction::Save),
+            ToolbarButton::Close => {
+                self.app_state = AppState::Idle;
+                return Some(EditorAction::Exit);
+            }
+        };
+
+        self.current_tool = tool;
+        self.selected_shape_index = None;
+        None
+    }
+
+    // 切换到下一个选择框限制，并立即按新的限制调整已有的选择框
+    fn next_ratio(&mut self) {
+        let index = self
+            .ratios
+            .iter()
+            .position(|ratio| *ratio == self.ratio)
+            .map_or(0, |index| (index + 1) % self.ratios.len());
+        self.ratio = self
+            .ratios
+            .get(index)
+            .copied()
+            .unwrap_or(SelectionRatio::Free);
+
+        let Some(selection) = self.current_selection else {
+            return;
+        };
+        let (left, top, right, bottom) = selection.bounds();
+        let reshaped = match self.ratio {
+            SelectionRatio::Free => return,
+            SelectionRatio::Fixed(..) => self.new_selection((left, top), (left, top)),
+            SelectionRatio::Ratio(width, height) => resize_with_ratio(
+                ResizeHandle::BottomRight,
+                (left, top),
+                (right, bottom),
+                width / height,
+                MIN_SELECTION_SIZE,
+                self.screen_size,
+            ),
+        };
+        self.change_selection(reshaped);
+    }
+
+    // 从 anchor 拖到 pos 时新建的选择框：固定大小时左上角跟随鼠标，限制比例时按比例拉出
+    fn new_selection(&self, anchor: (f32, f32), pos: (f32, f32)) -> Selection {
+        if let SelectionRatio::Fixed(width, height) = self.ratio {
+            let width = width.min(self.screen_size.width_f32());
+            let height = height.min(self.screen_size.height_f32());
+            return constrain_to_screen(
+                Selection {
+                    start: pos,
+                    end: (pos.0 + width, pos.1 + height),
+                },
+                self.screen_size,
+            );
+        }
+        match self.locked_ratio(None) {
+            Some(ratio) => resize_with_ratio(
+                corner_toward(anchor, pos),
+                anchor,
+                pos,
+                ratio,
+                0.0,
+                self.screen_size,
+            ),
+            None => Selection {
+                start: anchor,
+                end: pos,
+            },
+        }
+    }
+
+    // 当前要保持的宽高比：预设比例优先，否则按住 Shift 时锁定 current 的比例（没有时为 1:1）
+    fn locked_ratio(&self, current: Option<Selection>) -> Option<f32> {
+        match self.ratio {
+            SelectionRatio::Free => self.modifiers.shift.then(|| {
+                current
+                    .map(|selection| selection.size())
+                    .filter(|(_, height)| *height > 0.0)
+                    .map_or(1.0, |(width, height)| width / height)
+            }),
+            ratio => ratio.aspect(),
+        }
+    }
+
+    // 点击已有文字时继续编辑，否则在点击处新建一个空文字
+    fn begin_text_editing(&mut self, pos: (f32, f32), selection: &Selection) {
+        let hit = self
+            .shapes
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, shape)| {
+                matches!(shape, DrawingShape::Text { .. }) && shape.contains_point(pos.0, pos.1)
+            })
+            .map(|(i, shape)| (i, shape.clone()));
+
+        let editing = if let Some((index, shape)) = hit {
+            let caret = match &shape {
+                DrawingShape::Text { content, .. } => content.chars().count(),
+                _ => 0,
+            };
+            TextEditing {
+                index,
+                caret,
+                before: Some(shape),
+            }
+        } else {
+            let mut shape = DrawingShape::Text {
+                position: pos,
+                content: String::new(),
+                font_size: self.font_size,
+                color: self.shape_style.color,
+                background: self.text_background.then_some(TEXT_BACKGROUND_COLOR),
+            };
+            shape.constrain_to_selection(selection.bounds());
+
+            self.shapes.push(shape);
+            TextEditing {
+                index: self.shapes.len() - 1,
+                caret: 0,
+                before: None,
+            }
+        };
+
+        self.selected_shape_index = Some(editing.index);
+        self.text_editing = Some(editing);
+    }
+
+    // 按当前工具和样式在按下的位置新建图形
+    fn new_shape(&self, tool: DrawingTool, pos: (f32, f32)) -> Option<DrawingShape> {
+        let ShapeStyle {
+            color,
+            stroke_width,
+        } = self.shape_style;
+
+        let shape = match tool {
+            DrawingTool::Rectangle => DrawingShape::Rectangle {
+                start: pos,
+                end: pos,
+                color,
+                stroke_width,
+            },
+            DrawingTool::Circle => DrawingShape::Circle {
+                center: pos,
+                radius: 0.0,
+                color,
+                stroke_width,
+            },
+            DrawingTool::Arrow => DrawingShape::Arrow {
+                start: pos,
+                end: pos,
+                color,
+                stroke_width,
+            },
+            DrawingTool::Brush => DrawingShape::BrushStroke {
+                points: vec![pos],
+                color,
+                stroke_width,
+            },
+            DrawingTool::Mosaic => DrawingShape::Mosaic {
+                area: RedactArea::new(self.mosaic_area, pos, DEFAULT_REDACT_BRUSH_WIDTH),
+                block_size: self.mosaic_block_size,
+            },
+            DrawingTool::Blur => DrawingShape::Blur {
+                area: RedactArea::new(self.blur_area, pos, DEFAULT_REDACT_BRUSH_WIDTH),
+                sigma: self.blur_sigma,
+            },
+            DrawingTool::Text | DrawingTool::None => return None,
+        };
+        Some(shape)
+    }
+
+    // 鼠标移动：按当前状态更新正在绘制、拖拽或调整大小的对象
+    pub fn pointer_move(&mut self, pos: (f32, f32)) -> EditorResponse {
+        let previous = std::mem::replace(&mut self.mouse_pos, pos);
+
+        match self.app_state {
+            AppState::Drawing => self.update_drawing(pos),
+            AppState::ResizingShape => self.resize_selected_shape(pos),
+            AppState::EditingShape => self.move_selected_shape(pos),
+            AppState::Selecting => {
+                let pos = self.snap(pos);
+                if let Some(selection) = self.temp_selection {
+                    // 新建选择框时锚点为按下鼠标的位置
+                    let anchor = self.resize_anchor.unwrap_or(selection.start);
+                    self.temp_selection = Some(self.new_selection(anchor, pos));
+                }
+            }
+            AppState::Dragging => {
+                if let (Some(selection), Some(offset)) = (self.current_selection, self.drag_offset)
+                {
+                    let new_selection = Selection {
+                        start: (pos.0 - offset.0, pos.1 - offset.1),
+                        end: (
+                            pos.0 - offset.0 + selection.size().0,
+                            pos.1 - offset.1 + selection.size().1,
+                        ),
+                    };
+                    self.current_selection =
+                        Some(constrain_to_screen(new_selection, self.screen_size));
+                }
+            }
+            AppState::Resizing => {
+                let pos = self.snap(pos);
+                self.resize_selection(pos);
+            }
+            AppState::Idle => {
+                // 取色模式下放大镜跟随鼠标
+                if self.color_picker {
+                    return self.respond(true, None);
+                }
+                // 还没有选择框时高亮鼠标下的窗口
+                if self.current_selection.is_none() {
+                    let hovered = window_at(&self.windows, pos);
+                    let changed = hovered != self.hovered_window;
+                    self.hovered_window = hovered;
+                    return self.respond(changed, None);
+                }
+                // 空闲时只有工具栏和属性栏的悬停效果需要重绘
+                let redraw = self.hovers_toolbar(previous) || self.hovers_toolbar(pos);
+                return self.respond(redraw, None);
+            }
+        }
+        self.respond(true, None)
+    }
+
+    // 把拖拽中的选择框边吸附到截图中附近的直线，按住 Alt 或分析未完成时不吸附
+    fn snap(&mut self, pos: (f32, f32)) -> (f32, f32) {
+        if self.modifiers.alt {
+            return pos;
+        }
+        self.edges.edges().map_or(pos, |edges| edges.snap(pos))
+    }
+
+    // 鼠标是否在工具栏或属性栏上
+    fn hovers_toolbar(&self, pos: (f32, f32)) -> bool {
+        let Some(selection) = self.current_selection else {
+            return false;
+        };
+        let (toolbar, property_bar) = self.bars(&selection);
+        let (x, y) = self.to_ui(pos);
+        toolbar.contains_point(x, y)
+            || (shows_property_bar(self.current_tool, self.selected_shape().as_ref())
+                && property_bar.contains_point(x, y))
+    }
+
+    // 选择框旁边的工具栏和属性栏，按逻辑像素布局
+    fn bars(&self, selection: &Selection) -> (Toolbar, PropertyBar) {
+        let selection = selection.to_logical(self.ui_scale);
+        let screen_size = self.screen_size.to_logical(self.ui_scale);
+        let toolbar = Toolbar::calculate(&selection, screen_size);
+        let property_bar = PropertyBar::calculate(&toolbar, &selection, screen_size);
+        (toolbar, property_bar)
+    }
+
+    // 截图物理像素换算为界面控件的逻辑像素
+    fn to_ui(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x / self.ui_scale.get(), y / self.ui_scale.get())
+    }
+
+    fn update_drawing(&mut self, pos: (f32, f32)) {
+        let Some(selection) = self.current_selection else {
+            return;
+        };
+        let Some(shape) = &mut self.current_drawing else {
+            return;
+        };
+        let selection_bounds = selection.bounds();
+
+        // 限制绘制位置在选择区域内
+        let constrained_pos = (
+            pos.0.max(selection_bounds.0).min(selection_bounds.2),
+            pos.1.max(selection_bounds.1).min(selection_bounds.3),
+        );
+
+        match shape {
+            DrawingShape::Rectangle { end, .. } | DrawingShape::Arrow { end, .. } => {
+                *end = constrained_pos;
+            }
+            DrawingShape::Circle { center, radius, .. } => {
+                let dx = constrained_pos.0 - center.0;
+                let dy = constrained_pos.1 - center.1;
+                let new_radius = (dx * dx + dy * dy).sqrt();
+
+                // 限制圆不超出选择区域
+                let max_radius = (center.0 - selection_bounds.0)
+                    .min(selection_bounds.2 - center.0)
+                    .min(center.1 - selection_bounds.1)
+                    .min(selection_bounds.3 - center.1);
+
+                *radius = new_radius.min(max_radius);
+            }
+            DrawingShape::BrushStroke { points, .. } => {
+                // 画笔优化 - 减少点的数量和频率
+                if point_in_rect(pos.0, pos.1, &selection) {
+                    // 只有在距离上一个点足够远时才添加新点
+                    let should_add_point = if let Some(last_point) = points.last() {
+                        let dx = pos.0 - last_point.0;
+                        let dy = pos.1 - last_point.1;
+                        let distance = (dx * dx + dy * dy).sqrt();
+                        distance > 2.0 // 最小距离阈值
+                    } else {
+                        true
+                    };
+
+                    if should_add_point {
+                        points.push(pos);
+
+                        // 限制点的总数，防止内存无限增长
+                        const MAX_BRUSH_POINTS: usize = 1000;
+                        if points.len() > MAX_BRUSH_POINTS {
+                            // 移除最早的一些点，保持流畅度
+                            points.drain(0..100);
+                        }
+                    }
+                }
+            }
+            DrawingShape::Mosaic { area, .. } | DrawingShape::Blur { area, .. } => {
+                area.extend_to(constrained_pos);
+            }
+            DrawingShape::Text { .. } => {}
+        }
+    }
+
+    fn resize_selected_shape(&mut self, pos: (f32, f32)) {
+        let (Some(index), Some(handle), Some(anchor), Some(selection)) = (
+            self.selected_shape_index,
+            self.shape_resize_handle,
+            self.shape_resize_anchor,
+            self.current_selection,
+        ) else {
+            return;
+        };
+        let Some(shape) = self.shapes.get_mut(index) else {
+            return;
+        };
+        let selection_bounds = selection.bounds();
+
+        // 限制鼠标位置在选择区域内
+        let constrained_pos = (
+            pos.0.max(selection_bounds.0).min(selection_bounds.2),
+            pos.1.max(selection_bounds.1).min(selection_bounds.3),
+        );
+
+        let (left, top, right, bottom) = shape.bounds();
+
+        // 根据不同的手柄计算新的边界
+        let new_bounds = match handle {
+            ResizeHandle::TopLeft => (constrained_pos.0, constrained_pos.1, anchor.0, anchor.1),
+            ResizeHandle::TopRight => (anchor.0, constrained_pos.1, constrained_pos.0, anchor.1),
+            ResizeHandle::BottomRight => (anchor.0, anchor.1, constrained_pos.0, constrained_pos.1),
+            ResizeHandle::BottomLeft => (constrained_pos.0, anchor.1, anchor.0, constrained_pos.1),
+            ResizeHandle::Top => (left, constrained_pos.1, right, anchor.1),
+            ResizeHandle::Bottom => (left, anchor.1, right, constrained_pos.1),
+            ResizeHandle::Left => (constrained_pos.0, top, anchor.0, bottom),
+            ResizeHandle::Right => (anchor.0, top, constrained_pos.0, bottom),
+        };
+
+        // 确保新边界是有效的（左小于右，上小于下）
+        let (mut new_left, mut new_top, mut new_right, mut new_bottom) = new_bounds;
+
+        if new_left > new_right {
+            std::mem::swap(&mut new_left, &mut new_right);
+        }
+        if new_top > new_bottom {
+            std::mem::swap(&mut new_top, &mut new_bottom);
+        }
+
+        // 确保最小尺寸
+        let min_size = 10.0;
+        if new_right - new_left < min_size {
+            if handle == ResizeHandle::Left
+                || handle == ResizeHandle::TopLeft
+                || handle == ResizeHandle::BottomLeft
+            {
+                new_left = new_right - min_size;
+            } else {
+                new_right = new_left + min_size;
+            }
+        }
+        if new_bottom - new_top < min_size {
+            if handle == ResizeHandle::Top
+                || handle == ResizeHandle::TopLeft
+                || handle == ResizeHandle::TopRight
+            {
+                new_top = new_bottom - min_size;
+            } else {
+                new_bottom = new_top + min_size;
+            }
+        }
+
+        shape.resize_constrained((new_left, new_top, new_right, new_bottom), selection_bounds);
+    }
+
+    fn move_selected_shape(&mut self, pos: (f32, f32)) {
+        let (Some(index), Some(offset), Some(selection)) = (
+            self.selected_shape_index,
+            self.shape_drag_offset,
+            self.current_selection,
+        ) else {
+            return;
+        };
+        let Some(shape) = self.shapes.get_mut(index) else {
+            return;
+        };
+
+        let new_x = pos.0 - offset.0;
+        let new_y = pos.1 - offset.1;
+        let (old_left, old_top, _, _) = shape.bounds();
+        let dx = new_x - old_left;
+        let dy = new_y - old_top;
+
+        // 只有当移动距离足够大时才更新
+        if dx.abs() > 0.5 || dy.abs() > 0.5 {
+            shape.translate(dx, dy);
+            // 限制在选择区域内
+            shape.constrain_to_selection(selection.bounds());
+        }
+    }
+
+    fn resize_selection(&mut self, pos: (f32, f32)) {
+        let (Some(handle), Some(anchor), Some(selection)) = (
+            self.resize_handle,
+            self.resize_anchor,
+            self.current_selection,
+        ) else {
+            return;
+        };
+
+        let constrained_x = pos.0.max(0.0).min(self.screen_size.width_f32());
+        let constrained_y = pos.1.max(0.0).min(self.screen_size.height_f32());
+
+        // 限制比例时按比例缩放，锚点不变
+        if let Some(ratio) = self.locked_ratio(self.selection_before_edit) {
+            let resized = resize_with_ratio(
+                handle,
+                anchor,
+                pos,
+                ratio,
+                MIN_SELECTION_SIZE,
+                self.screen_size,
+            );
+            self.current_selection = Some(constrain_to_screen(resized, self.screen_size));
+            return;
+        }
+
+        let (left, top, right, bottom) = selection.bounds();
+
+        let new_selection = match handle {
+            ResizeHandle::TopLeft => Selection {
+                start: (
+                    constrained_x.min(anchor.0 - MIN_SELECTION_SIZE),
+                    constrained_y.min(anchor.1 - MIN_SELECTION_SIZE),
+                ),
+                end: anchor,
+            },
+            ResizeHandle::TopRight => Selection {
+                start: (anchor.0, constrained_y.min(anchor.1 - MIN_SELECTION_SIZE)),
+                end: (constrained_x.max(anchor.0 + MIN_SELECTION_SIZE), anchor.1),
+            },
+            ResizeHandle::BottomRight => Selection {
+                start: anchor,
+                end: (
+                    constrained_x.max(anchor.0 + MIN_SELECTION_SIZE),
+                    constrained_y.max(anchor.1 + MIN_SELECTION_SIZE),
+                ),
+            },
+            ResizeHandle::BottomLeft => Selection {
+                start: (constrained_x.min(anchor.0 - MIN_SELECTION_SIZE), anchor.1),
+                end: (anchor.0, constrained_y.max(anchor.1 + MIN_SELECTION_SIZE)),
+            },
+            ResizeHandle::Top => Selection {
+                start: (left, constrained_y.min(bottom - MIN_SELECTION_SIZE)),
+                end: (right, bottom),
+            },
+            ResizeHandle::Bottom => Selection {
+                start: (left, top),
+                end: (right, constrained_y.max(top + MIN_SELECTION_SIZE)),
+            },
+            ResizeHandle::Left => Selection {
+                start: (constrained_x.min(right - MIN_SELECTION_SIZE), top),
+                end: (right, bottom),
+            },
+            ResizeHandle::Right => Selection {
+                start: (left, top),
+                end: (constrained_x.max(left + MIN_SELECTION_SIZE), bottom),
+            },
+        };
+
+        self.current_selection = Some(constrain_to_screen(new_selection, self.screen_size));
+    }
+
+    // 鼠标释放：结束当前操作，每次拖拽只记录一条历史
+    pub fn pointer_up(&mut self, pos: (f32, f32)) -> EditorResponse {
+        self.mouse_pos = pos;
+
+        match self.app_state {
+            AppState::Drawing => {
+                if let Some(shape) = self.current_drawing.take() {
+                    self.history.push(Command::AddShape {
+                        index: self.shapes.len(),
+                        shape: shape.clone(),
+                    });
+                    self.shapes.push(shape);
+                }
+            }
+            AppState::ResizingShape => {
+                self.record_shape_edit(|index, before, after| Command::ResizeShape {
+                    index,
+                    before,
+                    after,
+                });
+                self.shape_resize_handle = None;
+                self.shape_resize_anchor = None;
+            }
+            AppState::EditingShape => {
+                self.record_shape_edit(|index, before, after| Command::MoveShape {
+                    index,
+                    before,
+                    after,
+                });
+                self.shape_drag_offset = None;
+            }
+            AppState::Selecting => {
+                // 单击（没有拖出选择框）时选中鼠标下的窗口
+                let clicked_window = self
+                    .temp_selection
+                    .filter(|selection| {
+                        let (width, height) = selection.size();
+                        width < MIN_SELECTION_SIZE && height < MIN_SELECTION_SIZE
+                    })
+                    .and_then(|_| {
+                        self.hovered_window
+                            .or_else(|| window_at(&self.windows, pos))
+                    });
+                if let Some(window) = clicked_window {
+                    self.temp_selection = Some(window);
+                }
+                self.hovered_window = None;
+                self.resize_anchor = None;
+                if let Some(selection) = self.temp_selection.take() {
+                    self.history.push(Command::ChangeSelection {
+                        before: self.current_selection,
+                        after: Some(selection),
+                    });
+                    self.current_selection = Some(selection);
+                }
+            }
+            AppState::Dragging | AppState::Resizing => {
+                let before = self.selection_before_edit.take();
+                if before.is_some() {
+                    self.history.push(Command::ChangeSelection {
+                        before,
+                        after: self.current_selection,
+                    });
+                }
+                self.drag_offset = None;
+                self.resize_handle = None;
+                self.resize_anchor = None;
+            }
+            AppState::Idle => return self.respond(false, None),
+        }
+
+        self.app_state = AppState::Idle;
+        self.respond(true, None)
+    }
+
+    // 拖拽结束时记录选中图形从快照到当前状态的变化
+    fn record_shape_edit(
+        &mut self,
+        command: impl FnOnce(usize, DrawingShape, DrawingShape) -> Command,
+    ) {
+        let before = self.shape_before_edit.take();
+        if let (Some(index), Some(before)) = (self.selected_shape_index, before) {
+            if let Some(after) = self.shapes.get(index).cloned() {
+                self.history.push(command(index, before, after));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use skia_safe::{Color, Paint, Rect, surfaces};
+
+    use super::*;
+    use crate::{
+        capture::{DisplayGeometry, VirtualDesktop},
+        coords::{LogicalPoint, LogicalSize, PhysicalPoint, PhysicalSize, ScaleFactor},
+        editor::{
+            EditorKey, EditorSettings, KeyModifiers,
+            tests::{SELECTION, button_center, click, drag, editor, press, rectangle},
+        },
+        test_support::solid_image,
+        windows::{WindowGeometry, WindowSource, fake::FakeWindows, window_regions},
+    };
+
+    #[test]
+    fn dragging_creates_selection() {
+        let mut editor = editor(None);
+        editor.pointer_down((100.0, 100.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::Selecting);
+        editor.pointer_move((300.0, 250.0));
+        editor.pointer_up((300.0, 250.0));
+
+        assert_eq!(editor.state(), AppState::Idle);
+        let selection = editor.selection().unwrap();
+        assert_eq!(selection.bounds(), (100.0, 100.0, 300.0, 250.0));
+
+        // 新建选择框可以撤销
+        assert!(editor.apply_history(false));
+        assert_eq!(editor.selection(), None);
+    }
+
+    #[test]
+    fn dragging_up_left_creates_selection() {
+        let mut editor = editor(None);
+        drag(&mut editor, (300.0, 250.0), (100.0, 100.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (100.0, 100.0, 300.0, 250.0)
+        );
+    }
+
+    #[test]
+    fn secondary_button_exits() {
+        let mut editor = editor(Some(SELECTION));
+        let response = editor.pointer_down((350.0, 275.0), PointerButton::Secondary);
+        assert_eq!(response.action, Some(EditorAction::Exit));
+    }
+
+    // 截图上有两个叠放的窗口：上层 (100, 100)-(300, 250)，下层 (50, 50)-(450, 400)
+    fn editor_with_windows() -> Editor {
+        let desktop = VirtualDesktop::new(vec![DisplayGeometry {
+            id: 0,
+            origin: LogicalPoint::default(),
+            size: LogicalSize::new(400.0, 300.0),
+            scale: ScaleFactor::new(2.0),
+        }])
+        .unwrap();
+        let window = |x, y, width, height| WindowGeometry {
+            origin: PhysicalPoint::new(x, y),
+            size: PhysicalSize::new(width, height),
+        };
+        let source = FakeWindows::new(vec![
+            window(100.0, 100.0, 200, 150),
+            window(50.0, 50.0, 400, 350),
+        ]);
+        let mut editor = editor(None);
+        editor.set_windows(window_regions(&source.windows().unwrap(), &desktop));
+        editor
+    }
+
+    #[test]
+    fn hovering_highlights_window() {
+        let mut editor = editor_with_windows();
+        assert!(editor.pointer_move((150.0, 150.0)).redraw);
+        assert_eq!(
+            editor.view().selection.map(|window| window.bounds()),
+            Some((100.0, 100.0, 300.0, 250.0))
+        );
+        // 仍在同一个窗口内不需要重绘
+        assert!(!editor.pointer_move((160.0, 160.0)).redraw);
+
+        assert!(editor.pointer_move((400.0, 350.0)).redraw);
+        assert_eq!(
+            editor.view().selection.map(|window| window.bounds()),
+            Some((50.0, 50.0, 450.0, 400.0))
+        );
+        assert!(editor.pointer_move((700.0, 500.0)).redraw);
+        assert_eq!(editor.view().selection, None);
+    }
+
+    #[test]
+    fn clicking_selects_hovered_window() {
+        let mut editor = editor_with_windows();
+        editor.pointer_move((150.0, 150.0));
+        click(&mut editor, (150.0, 150.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (100.0, 100.0, 300.0, 250.0)
+        );
+
+        // 选中窗口可以撤销
+        assert!(editor.apply_history(false));
+        assert_eq!(editor.selection(), None);
+
+        // 没有悬停过也按鼠标位置选中窗口
+        click(&mut editor, (400.0, 350.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (50.0, 50.0, 450.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn dragging_over_window_selects_freely() {
+        let mut editor = editor_with_windows();
+        editor.pointer_move((150.0, 150.0));
+        drag(&mut editor, (150.0, 150.0), (200.0, 180.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (150.0, 150.0, 200.0, 180.0)
+        );
+    }
+
+    // 左侧 300 像素为黑色的截图，x = 300 处有一条垂直的边缘，等后台分析完成
+    fn editor_with_edge() -> Editor {
+        let mut surface = surfaces::raster_n32_premul((800, 600)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(Color::WHITE);
+        let mut paint = Paint::default();
+        paint.set_color(Color::BLACK);
+        canvas.draw_rect(Rect::from_ltrb(0.0, 0.0, 300.0, 600.0), &paint);
+
+        let mut editor = Editor::new(surface.image_snapshot(), None, &EditorSettings::default());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while editor.edges.edges().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        editor
+    }
+
+    #[test]
+    fn selection_snaps_to_edges() {
+        let mut editor = editor_with_edge();
+        drag(&mut editor, (100.0, 100.0), (305.0, 200.0));
+        assert_eq!(editor.selection().unwrap().end, (300.0, 200.0));
+    }
+
+    #[test]
+    fn alt_bypasses_snapping() {
+        let mut editor = editor_with_edge();
+        editor.set_modifiers(KeyModifiers {
+            alt: true,
+            ..Default::default()
+        });
+        drag(&mut editor, (100.0, 100.0), (305.0, 200.0));
+        assert_eq!(editor.selection().unwrap().end, (305.0, 200.0));
+    }
+
+    #[test]
+    fn releasing_alt_snaps_again() {
+        let mut editor = editor_with_edge();
+        let alt = KeyModifiers {
+            alt: true,
+            ..Default::default()
+        };
+        editor.set_modifiers(alt);
+        editor.pointer_down((100.0, 100.0), PointerButton::Primary);
+        editor.pointer_move((305.0, 200.0));
+        assert_eq!(editor.temp_selection.unwrap().end, (305.0, 200.0));
+
+        editor.set_modifiers(KeyModifiers::default());
+        assert_eq!(editor.temp_selection.unwrap().end, (300.0, 200.0));
+    }
+
+    #[test]
+    fn dragging_inside_moves_selection() {
+        let mut editor = editor(Some(SELECTION));
+        editor.pointer_down((350.0, 275.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::Dragging);
+        editor.pointer_move((370.0, 285.0));
+        editor.pointer_up((370.0, 285.0));
+
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (220.0, 160.0, 520.0, 410.0)
+        );
+        assert!(editor.apply_history(false));
+        assert_eq!(editor.selection(), Some(SELECTION));
+    }
+
+    #[test]
+    fn moving_selection_stays_on_screen() {
+        let mut editor = editor(Some(SELECTION));
+        drag(&mut editor, (350.0, 275.0), (-500.0, -500.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (0.0, 0.0, 300.0, 250.0)
+        );
+    }
+
+    #[test]
+    fn every_handle_resizes_selection() {
+        let cases = [
+            (
+                ResizeHandle::TopLeft,
+                (200.0, 150.0),
+                (180.0, 130.0),
+                (180.0, 130.0, 500.0, 400.0),
+            ),
+            (
+                ResizeHandle::Top,
+                (350.0, 150.0),
+                (370.0, 130.0),
+                (200.0, 130.0, 500.0, 400.0),
+            ),
+            (
+                ResizeHandle::TopRight,
+                (500.0, 150.0),
+                (520.0, 130.0),
+                (200.0, 130.0, 520.0, 400.0),
+            ),
+            (
+                ResizeHandle::Right,
+                (500.0, 275.0),
+                (520.0, 295.0),
+                (200.0, 150.0, 520.0, 400.0),
+            ),
+            (
+                ResizeHandle::BottomRight,
+                (500.0, 400.0),
+                (520.0, 420.0),
+                (200.0, 150.0, 520.0, 420.0),
+            ),
+            (
+                ResizeHandle::Bottom,
+                (350.0, 400.0),
+                (330.0, 420.0),
+                (200.0, 150.0, 500.0, 420.0),
+            ),
+            (
+                ResizeHandle::BottomLeft,
+                (200.0, 400.0),
+                (180.0, 420.0),
+                (180.0, 150.0, 500.0, 420.0),
+            ),
+            (
+                ResizeHandle::Left,
+                (200.0, 275.0),
+                (180.0, 255.0),
+                (180.0, 150.0, 500.0, 400.0),
+            ),
+        ];
+        for (handle, from, to, expected) in cases {
+            let mut editor = editor(Some(SELECTION));
+            editor.pointer_down(from, PointerButton::Primary);
+            assert_eq!(editor.state(), AppState::Resizing, "{handle:?}");
+            assert_eq!(editor.resize_handle, Some(handle));
+            editor.pointer_move(to);
+            editor.pointer_up(to);
+
+            assert_eq!(editor.selection().unwrap().bounds(), expected, "{handle:?}");
+            assert!(editor.apply_history(false), "{handle:?}");
+            assert_eq!(editor.selection(), Some(SELECTION), "{handle:?}");
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_minimum_size() {
+        let mut editor = editor(Some(SELECTION));
+        drag(&mut editor, (500.0, 400.0), (50.0, 50.0));
+        assert_eq!(
+            editor.selection().unwrap().bounds(),
+            (
+                200.0,
+                150.0,
+                200.0 + MIN_SELECTION_SIZE,
+                150.0 + MIN_SELECTION_SIZE
+            )
+        );
+    }
+
+    #[test]
+    fn handles_are_ignored_for_fixed_size() {
+        let mut editor = editor(Some(SELECTION));
+        editor.ratio = SelectionRatio::Fixed(300.0, 250.0);
+        editor.pointer_down((500.0, 400.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::Dragging);
+    }
+
+    // 选择工具后在选择框内拖出图形
+    fn draw_with(button: ToolbarButton) -> Editor {
+        let mut editor = editor(Some(SELECTION));
+        let button_pos = button_center(&editor, button);
+        click(&mut editor, button_pos);
+        drag(&mut editor, (250.0, 200.0), (300.0, 260.0));
+        assert_eq!(editor.state(), AppState::Idle);
+        assert_eq!(editor.shapes().len(), 1, "{button:?}");
+        editor
+    }
+
+    #[test]
+    fn tools_draw_shapes() {
+        let editor = draw_with(ToolbarButton::Rectangle);
+        assert_eq!(editor.tool(), DrawingTool::Rectangle);
+        assert!(matches!(
+            editor.shapes()[0],
+            DrawingShape::Rectangle {
+                start: (250.0, 200.0),
+                end: (300.0, 260.0),
+                ..
+            }
+        ));
+
+        // 圆不超出选择框，半径受到离上边的距离限制
+        let editor = draw_with(ToolbarButton::Circle);
+        assert!(matches!(
+            editor.shapes()[0],
+            DrawingShape::Circle {
+                center: (250.0, 200.0),
+                radius: 50.0,
+                ..
+            }
+        ));
+
+        let editor = draw_with(ToolbarButton::Arrow);
+        assert!(matches!(
+            editor.shapes()[0],
+            DrawingShape::Arrow {
+                start: (250.0, 200.0),
+                end: (300.0, 260.0),
+                ..
+            }
+        ));
+
+        let editor = draw_with(ToolbarButton::Brush);
+        let DrawingShape::BrushStroke { points, .. } = &editor.shapes()[0] else {
+            panic!("{:?}", editor.shapes()[0]);
+        };
+        assert_eq!(points, &[(250.0, 200.0), (300.0, 260.0)]);
+
+        let editor = draw_with(ToolbarButton::Mosaic);
+        let DrawingShape::Mosaic { area, .. } = &editor.shapes()[0] else {
+            panic!("{:?}", editor.shapes()[0]);
+        };
+        assert_eq!(area.bounds(), (250.0, 200.0, 300.0, 260.0));
+
+        let editor = draw_with(ToolbarButton::Blur);
+        let DrawingShape::Blur { area, .. } = &editor.shapes()[0] else {
+            panic!("{:?}", editor.shapes()[0]);
+        };
+        assert_eq!(area.bounds(), (250.0, 200.0, 300.0, 260.0));
+    }
+
+    #[test]
+    fn drawing_is_clamped_to_selection() {
+        let mut editor = editor(Some(SELECTION));
+        let button_pos = button_center(&editor, ToolbarButton::Rectangle);
+        click(&mut editor, button_pos);
+        drag(&mut editor, (250.0, 200.0), (700.0, 20.0));
+        assert!(matches!(
+            editor.shapes()[0],
+            DrawingShape::Rectangle {
+                end: (500.0, 150.0),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn drawn_shape_can_be_undone() {
+        let mut editor = draw_with(ToolbarButton::Rectangle);
+        assert!(editor.apply_history(false));
+        assert!(editor.shapes().is_empty());
+        assert!(editor.apply_history(true));
+        assert_eq!(editor.shapes().len(), 1);
+    }
+
+    #[test]
+    fn text_tool_adds_text() {
+        let mut editor = editor(Some(SELECTION));
+        let button_pos = button_center(&editor, ToolbarButton::Text);
+        click(&mut editor, button_pos);
+        click(&mut editor, (250.0, 200.0));
+        assert!(editor.text_editing.is_some());
+
+        press(&mut editor, EditorKey::Character("hi".into()));
+        press(&mut editor, EditorKey::Escape);
+
+        assert!(editor.text_editing.is_none());
+        let DrawingShape::Text {
+            position, content, ..
+        } = &editor.shapes()[0]
+        else {
+            panic!("{:?}", editor.shapes()[0]);
+        };
+        assert_eq!((*position, content.as_str()), ((250.0, 200.0), "hi"));
+        assert!(editor.history().can_undo());
+    }
+
+    #[test]
+    fn empty_text_is_discarded() {
+        let mut editor = editor(Some(SELECTION));
+        let button_pos = button_center(&editor, ToolbarButton::Text);
+        click(&mut editor, button_pos);
+        click(&mut editor, (250.0, 200.0));
+        press(&mut editor, EditorKey::Escape);
+
+        assert!(editor.shapes().is_empty());
+        assert!(!editor.history().can_undo());
+    }
+
+    #[test]
+    fn dragging_shape_moves_it() {
+        let mut editor = editor(Some(SELECTION));
+        editor
+            .shapes
+            .push(rectangle((250.0, 200.0), (300.0, 260.0)));
+
+        editor.pointer_down((275.0, 230.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::EditingShape);
+        assert_eq!(editor.selected_shape_index(), Some(0));
+        editor.pointer_move((295.0, 250.0));
+        editor.pointer_up((295.0, 250.0));
+
+        assert_eq!(
+            editor.shapes()[0],
+            rectangle((270.0, 220.0), (320.0, 280.0))
+        );
+        assert!(editor.apply_history(false));
+        assert_eq!(
+            editor.shapes()[0],
+            rectangle((250.0, 200.0), (300.0, 260.0))
+        );
+    }
+
+    #[test]
+    fn dragging_shape_handle_resizes_it() {
+        let mut editor = editor(Some(SELECTION));
+        editor
+            .shapes
+            .push(rectangle((250.0, 200.0), (300.0, 260.0)));
+
+        editor.pointer_down((300.0, 260.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::ResizingShape);
+        editor.pointer_move((340.0, 300.0));
+        editor.pointer_up((340.0, 300.0));
+
+        assert_eq!(
+            editor.shapes()[0],
+            rectangle((250.0, 200.0), (340.0, 300.0))
+        );
+        assert!(editor.apply_history(false));
+        assert_eq!(
+            editor.shapes()[0],
+            rectangle((250.0, 200.0), (300.0, 260.0))
+        );
+    }
+
+    #[test]
+    fn toolbar_buttons_return_actions() {
+        let mut editor = editor(Some(SELECTION));
+        let save = button_center(&editor, ToolbarButton::Save);
+        assert_eq!(
+            editor.pointer_down(save, PointerButton::Primary).action,
+            Some(EditorAction::Save)
+        );
+        editor.pointer_up(save);
+
+        let close = button_center(&editor, ToolbarButton::Close);
+        assert_eq!(
+            editor.pointer_down(close, PointerButton::Primary).action,
+            Some(EditorAction::Exit)
+        );
+    }
+
+    #[test]
+    fn ratio_button_cycles_and_reshapes_selection() {
+        let mut editor = editor(Some(SELECTION));
+        let ratio = button_center(&editor, ToolbarButton::Ratio);
+        click(&mut editor, ratio);
+
+        assert_eq!(editor.ratio, SelectionRatio::Ratio(16.0, 9.0));
+        let (width, height) = editor.selection().unwrap().size();
+        assert!((width / height - 16.0 / 9.0).abs() < 1e-4);
+        assert_eq!(editor.selection().unwrap().start, SELECTION.start);
+    }
+
+    // 2 倍缩放的 1600x1200 截图，选择框是 SELECTION 的两倍
+    fn hidpi_editor() -> Editor {
+        let selection = Selection {
+            start: (400.0, 300.0),
+            end: (1000.0, 800.0),
+        };
+        let image = solid_image(1600, 1200, Color::WHITE);
+        let mut editor = Editor::new(image, Some(selection), &EditorSettings::default());
+        editor.set_ui_scale(ScaleFactor::new(2.0));
+        editor
+    }
+
+    #[test]
+    fn toolbar_keeps_logical_size_on_hidpi() {
+        let unscaled = button_center(&editor(Some(SELECTION)), ToolbarButton::Rectangle);
+        let mut editor = hidpi_editor();
+        let center = button_center(&editor, ToolbarButton::Rectangle);
+        assert_eq!(center, (unscaled.0 * 2.0, unscaled.1 * 2.0));
+
+        // 按钮在物理像素中是 80x60，离中心 (35, 25) 仍在同一个按钮上
+        click(&mut editor, (center.0 + 35.0, center.1 + 25.0));
+        assert_eq!(editor.tool(), DrawingTool::Rectangle);
+    }
+
+    #[test]
+    fn handles_keep_logical_size_on_hidpi() {
+        // 离右下角 12 个物理像素：2 倍时在手柄范围内，1 倍时已在选择框外
+        let mut unscaled = editor(Some(SELECTION));
+        unscaled.pointer_down((512.0, 412.0), PointerButton::Primary);
+        assert_eq!(unscaled.state(), AppState::Idle);
+
+        let mut editor = hidpi_editor();
+        editor.pointer_down((1012.0, 812.0), PointerButton::Primary);
+        assert_eq!(editor.state(), AppState::Resizing);
+    }
+}
@@ -0,0 +1,137 @@
+use skia_safe::{Canvas, Color, Image as SkiaImage};
+
+use crate::{
+    coords::{LogicalSize, PhysicalSize, ScaleFactor},
+    picker::ColorFormat,
+    rendering::{
+        help::draw_help,
+        magnifier::draw_magnifier,
+        overlay::{OverlayScene, draw_overlay},
+        property_bar::draw_property_bar,
+        selection_input::draw_selection_input,
+        toolbar::draw_toolbar,
+    },
+    types::{
+        app_state::AppState,
+        drawing::{DrawingShape, ShapeStyle},
+        ui::{Magnifier, PropertyBar, Selection, SelectionRatio, Toolbar},
+    },
+};
+
+// 编辑器某一时刻的画面，包含工具栏和属性栏
+#[derive(Clone)]
+pub struct EditorView {
+    pub screenshot: SkiaImage,
+    pub screen_size: PhysicalSize,
+    // 界面控件按逻辑像素绘制，截图和标注仍按物理像素绘制
+    pub ui_scale: ScaleFactor,
+    pub mouse_pos: (f32, f32),
+    pub state: AppState,
+    pub selection: Option<Selection>,
+    pub selection_handles: bool,
+    pub shapes: Vec<DrawingShape>,
+    pub selected_shape: Option<usize>,
+    pub drawing: Option<DrawingShape>,
+    pub text_caret: Option<(usize, usize)>,
+    // 显示属性栏时属性栏上高亮的样式
+    pub property_bar: Option<ShapeStyle>,
+    pub color_input: Option<String>,
+    pub selection_input: Option<String>,
+    // 工具栏比例按钮上显示的当前比例
+    pub ratio: SelectionRatio,
+    pub mask_color: Color,
+    // 显示快捷键帮助时的 (快捷键, 说明)
+    pub help: Option<Vec<(String, &'static str)>>,
+    // 精确选择和绘制时在鼠标旁边显示放大镜
+    pub magnifier: bool,
+    // 取色模式下复制的颜色格式和取色范围
+    pub picker: Option<(ColorFormat, u32)>,
+}
+
+impl EditorView {
+    pub fn scene(&self) -> OverlayScene<'_> {
+        OverlayScene {
+            state: self.state,
+            mask_color: self.mask_color,
+            selection_handles: self.selection_handles,
+            selected_shape: self.selected_shape,
+            drawing: self.drawing.as_ref(),
+            text_caret: self.text_caret,
+            ui_scale: self.ui_scale,
+            ..OverlayScene::new(&self.screenshot, self.selection, &self.shapes)
+        }
+    }
+
+    // 在物理像素坐标的画布上绘制编辑器画面，最上层为快捷键帮助
+    pub fn draw(&self, canvas: &Canvas) {
+        draw_overlay(canvas, &self.scene());
+
+        // 界面控件切换到逻辑像素绘制，在高 DPI 显示器上不会缩小
+        canvas.save();
+        canvas.scale((self.ui_scale.get(), self.ui_scale.get()));
+        let selection = self
+            .selection
+            .map(|selection| selection.to_logical(self.ui_scale));
+        let screen_size = self.screen_size.to_logical(self.ui_scale);
+        self.draw_controls(canvas, selection, screen_size);
+        if self.magnifier {
+            self.draw_magnifier(canvas, screen_size);
+        }
+        if let Some(input) = &self.selection_input {
+            draw_selection_input(canvas, input, selection, screen_size);
+        }
+        if let Some(entries) = &self.help {
+            draw_help(canvas, entries, screen_size);
+        }
+        canvas.restore();
+    }
+
+    fn ui_mouse_pos(&self) -> (f32, f32) {
+        let scale = self.ui_scale.get();
+        (self.mouse_pos.0 / scale, self.mouse_pos.1 / scale)
+    }
+
+    // 空闲时的工具栏和属性栏
+    fn draw_controls(
+        &self,
+        canvas: &Canvas,
+        selection: Option<Selection>,
+        screen_size: LogicalSize,
+    ) {
+        let (Some(selection), AppState::Idle) = (selection, self.state) else {
+            return;
+        };
+        let mouse_pos = self.ui_mouse_pos();
+        let toolbar = Toolbar::calculate(&selection, screen_size);
+        draw_toolbar(canvas, &toolbar, &selection, mouse_pos, self.ratio);
+
+        if let Some(style) = self.property_bar {
+            let property_bar = PropertyBar::calculate(&toolbar, &selection, screen_size);
+            draw_property_bar(
+                canvas,
+                &property_bar,
+                style,
+                self.color_input.as_deref(),
+                mouse_pos,
+                screen_size,
+            );
+        }
+    }
+
+    // 放大镜显示鼠标下的截图像素和选择框的物理像素大小
+    fn draw_magnifier(&self, canvas: &Canvas, screen_size: LogicalSize) {
+        let pixel = (
+            self.mouse_pos.0.floor() as i32,
+            self.mouse_pos.1.floor() as i32,
+        );
+        let magnifier = Magnifier::calculate(self.ui_mouse_pos(), screen_size);
+        draw_magnifier(
+            canvas,
+            &magnifier,
+            &self.screenshot,
+            pixel,
+            self.picker,
+            self.selection.map(|selection| selection.size()),
+        );
+    }
+}
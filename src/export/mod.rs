@@ -0,0 +1,445 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use skia_safe::{
+    AlphaType, ColorType, EncodedImageFormat, Image as SkiaImage, ImageInfo, image::CachingHint,
+    surfaces,
+};
+
+use crate::{
+    coords::{PhysicalSize, PixelRect},
+    rendering::shapes::paint_shape,
+    types::{drawing::DrawingShape, ui::Selection},
+};
+
+// 选择框在截图上对应的像素区域（向外取整并裁剪到图片范围内）
+pub fn selection_pixel_bounds(image: &SkiaImage, selection: &Selection) -> Option<PixelRect> {
+    PixelRect::enclosing(selection.bounds(), PhysicalSize::of_image(image))
+}
+
+// 在离屏光栅表面上按原始分辨率合成选择区域和所有标注
+pub fn render_selection(
+    image: &SkiaImage,
+    selection: &Selection,
+    shapes: &[DrawingShape],
+) -> Option<SkiaImage> {
+    let bounds = selection_pixel_bounds(image, selection)?;
+
+    let mut surface = surfaces::raster_n32_premul((bounds.width(), bounds.height()))?;
+    let canvas = surface.canvas();
+
+    // 平移坐标系，使图形仍可使用截图的物理像素坐标绘制
+    canvas.translate((-bounds.left as f32, -bounds.top as f32));
+    canvas.draw_image(image, (0.0, 0.0), None);
+
+    for shape in shapes {
+        paint_shape(canvas, image, shape);
+    }
+
+    Some(surface.image_snapshot())
+}
+
+pub fn encode_png(image: &SkiaImage) -> Option<Vec<u8>> {
+    image
+        .encode(None, EncodedImageFormat::PNG, None)
+        .map(|data| data.as_bytes().to_vec())
+}
+
+// 读取非预乘的 RGBA8888 像素
+pub fn read_rgba(image: &SkiaImage) -> Option<Vec<u8>> {
+    let info = ImageInfo::new(
+        image.dimensions(),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    );
+    let row_bytes = image.width() as usize * 4;
+    let mut pixels = vec![0u8; row_bytes * image.height() as usize];
+
+    image
+        .read_pixels(&info, &mut pixels, row_bytes, (0, 0), CachingHint::Allow)
+        .then_some(pixels)
+}
+
+pub fn export_png(
+    image: &SkiaImage,
+    selection: &Selection,
+    shapes: &[DrawingShape],
+) -> Option<Vec<u8>> {
+    let rendered = render_selection(image, selection, shapes)?;
+    encode_png(&rendered)
+}
+
+pub fn save_png(
+    path: &Path,
+    image: &SkiaImage,
+    selection: &Selection,
+    shapes: &[DrawingShape],
+) -> io::Result<()> {
+    let bytes = export_png(image, selection, shapes)
+        .ok_or_else(|| io::Error::other("无法将选择区域编码为 PNG"))?;
+    std::fs::write(path, bytes)
+}
+
+// 默认文件名模板，带时间戳避免覆盖
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "screenshot_{timestamp}";
+
+// 文件名模板中可用的占位符，日期和时间为 UTC
+const TEMPLATE_FIELDS: [&str; 3] = ["timestamp", "date", "time"];
+
+// 未指定输出路径时保存截图的位置：目录（默认为当前目录）和文件名模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputLocation {
+    pub dir: Option<PathBuf>,
+    pub template: String,
+}
+
+impl Default for OutputLocation {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl OutputLocation {
+    // 本次保存使用的路径，模板没有扩展名时补上 .png
+    pub fn path(&self) -> PathBuf {
+        self.path_at(SystemTime::now())
+    }
+
+    pub fn path_at(&self, time: SystemTime) -> PathBuf {
+        let mut name = expand_template(&self.template, time).unwrap_or_else(|_| {
+            expand_template(DEFAULT_FILENAME_TEMPLATE, time).unwrap_or_default()
+        });
+        if !name.to_ascii_lowercase().ends_with(".png") {
+            name.push_str(".png");
+        }
+        match &self.dir {
+            Some(dir) => dir.join(name),
+            None => PathBuf::from(name),
+        }
+    }
+}
+
+// 检查文件名模板：不能为空、不能包含路径分隔符，占位符只能是 {timestamp}、{date}、{time}
+pub fn validate_filename_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("文件名模板不能为空".into());
+    }
+    if template.contains(['/', '\\']) {
+        return Err(format!("文件名模板不能包含路径分隔符: {template}"));
+    }
+    expand_template(template, UNIX_EPOCH).map(|_| ())
+}
+
+fn expand_template(template: &str, time: SystemTime) -> Result<String, String> {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_date(seconds / 86_400);
+    let second_of_day = seconds % 86_400;
+
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("文件名模板缺少右括号: {template}"))?;
+        let field = &rest[start + 1..start + end];
+        match field {
+            "timestamp" => result.push_str(&seconds.to_string()),
+            "date" => result.push_str(&format!("{year:04}-{month:02}-{day:02}")),
+            "time" => result.push_str(&format!(
+                "{:02}-{:02}-{:02}",
+                second_of_day / 3600,
+                second_of_day / 60 % 60,
+                second_of_day % 60
+            )),
+            _ => {
+                return Err(format!(
+                    "未知的文件名占位符 {{{field}}}，可用: {}",
+                    TEMPLATE_FIELDS.map(|name| format!("{{{name}}}")).join("、")
+                ));
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+// 1970-01-01 起的天数转为公历年月日
+fn civil_date(days: u64) -> (i64, u32, u32) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use skia_safe::{Color, Data, ISize, Paint, Rect};
+
+    use super::*;
+    use crate::{
+        test_support::{pixel, solid_image},
+        types::drawing::RedactArea,
+    };
+
+    #[test]
+    fn render_selection_crops_and_draws_shapes() {
+        let image = solid_image(100, 80, Color::BLUE);
+        let selection = Selection {
+            start: (10.0, 20.0),
+            end: (60.0, 50.0),
+        };
+        let shapes = [DrawingShape::Rectangle {
+            start: (20.0, 30.0),
+            end: (40.0, 40.0),
+            color: Color::RED,
+            stroke_width: 4.0,
+        }];
+
+        let rendered = render_selection(&image, &selection, &shapes).unwrap();
+        assert_eq!(rendered.dimensions(), ISize::new(50, 30));
+        // 矩形左边框在截图的 (20, 35)，即输出中的 (10, 15)
+        assert_eq!(pixel(&rendered, 10, 15), [255, 0, 0, 255]);
+        assert_eq!(pixel(&rendered, 0, 0), [0, 0, 255, 255]);
+        assert_eq!(pixel(&rendered, 30, 15), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn export_png_encodes_selection_size() {
+        let image = solid_image(100, 80, Color::BLUE);
+        let selection = Selection {
+            start: (60.0, 50.0),
+            end: (10.0, 20.0),
+        };
+
+        let bytes = export_png(&image, &selection, &[]).unwrap();
+        let decoded = SkiaImage::from_encoded(Data::new_copy(&bytes)).unwrap();
+        assert_eq!(decoded.dimensions(), ISize::new(50, 30));
+        assert_eq!(pixel(&decoded, 25, 15), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn selection_outside_image_is_not_rendered() {
+        let image = solid_image(100, 80, Color::BLUE);
+        let selection = Selection {
+            start: (200.0, 200.0),
+            end: (300.0, 300.0),
+        };
+        assert!(render_selection(&image, &selection, &[]).is_none());
+    }
+
+    // 1 像素黑白相间的棋盘格，打码后任何残留的原始像素都很明显
+    fn checkerboard(width: i32, height: i32) -> SkiaImage {
+        let mut surface = surfaces::raster_n32_premul((width, height)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(Color::WHITE);
+        let mut paint = Paint::default();
+        paint.set_color(Color::BLACK);
+        for y in 0..height {
+            for x in (y % 2..width).step_by(2) {
+                canvas.draw_rect(Rect::from_xywh(x as f32, y as f32, 1.0, 1.0), &paint);
+            }
+        }
+        surface.image_snapshot()
+    }
+
+    // 区域内（含边界像素）的每个像素都与原图相差足够大，区域外保持不变
+    fn assert_redacted(
+        source: &SkiaImage,
+        rendered: &SkiaImage,
+        (left, top, right, bottom): (i32, i32, i32, i32),
+    ) {
+        let (before_pixels, after_pixels) =
+            (read_rgba(source).unwrap(), read_rgba(rendered).unwrap());
+        for y in 0..source.height() {
+            for x in 0..source.width() {
+                let offset = (y * source.width() + x) as usize * 4;
+                let (before, after) = (before_pixels[offset], after_pixels[offset]);
+                if (left..right).contains(&x) && (top..bottom).contains(&y) {
+                    assert!(
+                        before.abs_diff(after) >= 96,
+                        "({x}, {y}) 未被打码: {before} -> {after}"
+                    );
+                } else {
+                    assert_eq!(before, after, "({x}, {y}) 在区域外被修改");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mosaic_is_burned_into_export() {
+        let image = checkerboard(80, 60);
+        let selection = Selection {
+            start: (0.0, 0.0),
+            end: (80.0, 60.0),
+        };
+        // 边界不在像素边缘上，边界像素只被覆盖一半
+        let shapes = [DrawingShape::Mosaic {
+            area: RedactArea::Rectangle {
+                start: (10.5, 10.5),
+                end: (57.5, 45.5),
+            },
+            block_size: 12.0,
+        }];
+
+        let rendered = render_selection(&image, &selection, &shapes).unwrap();
+        assert_redacted(&image, &rendered, (10, 10, 58, 46));
+
+        let bytes = export_png(&image, &selection, &shapes).unwrap();
+        let decoded = SkiaImage::from_encoded(Data::new_copy(&bytes)).unwrap();
+        assert_redacted(&image, &decoded, (10, 10, 58, 46));
+    }
+
+    #[test]
+    fn blur_is_burned_into_export() {
+        let image = checkerboard(80, 60);
+        let selection = Selection {
+            start: (0.0, 0.0),
+            end: (80.0, 60.0),
+        };
+        let shapes = [DrawingShape::Blur {
+            area: RedactArea::Rectangle {
+                start: (10.5, 10.5),
+                end: (57.5, 45.5),
+            },
+            sigma: 12.0,
+        }];
+
+        let rendered = render_selection(&image, &selection, &shapes).unwrap();
+        assert_redacted(&image, &rendered, (10, 10, 58, 46));
+
+        let bytes = export_png(&image, &selection, &shapes).unwrap();
+        let decoded = SkiaImage::from_encoded(Data::new_copy(&bytes)).unwrap();
+        assert_redacted(&image, &decoded, (10, 10, 58, 46));
+    }
+
+    #[test]
+    fn tiny_blur_uses_minimum_sigma() {
+        let image = checkerboard(40, 30);
+        let selection = Selection {
+            start: (0.0, 0.0),
+            end: (40.0, 30.0),
+        };
+        // 只有 2x2 的区域和几乎为零的半径，仍按 MIN_BLUR_SIGMA 模糊成均匀的灰色
+        let shapes = [DrawingShape::Blur {
+            area: RedactArea::Rectangle {
+                start: (20.5, 10.5),
+                end: (22.5, 12.5),
+            },
+            sigma: 0.1,
+        }];
+
+        let rendered = render_selection(&image, &selection, &shapes).unwrap();
+        assert_redacted(&image, &rendered, (20, 10, 23, 13));
+    }
+
+    #[test]
+    fn civil_date_handles_leap_years() {
+        let cases = [
+            (0, (1970, 1, 1)),
+            (59, (1970, 3, 1)),
+            (365, (1971, 1, 1)),
+            (789, (1972, 2, 29)),
+            (11_016, (2000, 2, 29)),
+            (20_088, (2024, 12, 31)),
+            // 2100 年不是闰年
+            (47_541, (2100, 3, 1)),
+        ];
+        for (days, expected) in cases {
+            assert_eq!(civil_date(days), expected, "{days}");
+        }
+    }
+
+    #[test]
+    fn template_fields_are_expanded() {
+        // 2023-11-14 22:13:20 UTC
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let cases = [
+            ("screenshot_{timestamp}", "screenshot_1700000000"),
+            ("{date}_{time}", "2023-11-14_22-13-20"),
+            ("shot {date}{date}", "shot 2023-11-142023-11-14"),
+            ("plain", "plain"),
+            ("a}b", "a}b"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(expand_template(template, time).as_deref(), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn output_path_adds_extension_and_dir() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let location = |dir: Option<&str>, template: &str| OutputLocation {
+            dir: dir.map(PathBuf::from),
+            template: template.into(),
+        };
+
+        assert_eq!(
+            location(None, DEFAULT_FILENAME_TEMPLATE).path_at(time),
+            PathBuf::from("screenshot_1700000000.png")
+        );
+        assert_eq!(
+            location(Some("shots"), "{date}").path_at(time),
+            Path::new("shots").join("2023-11-14.png")
+        );
+        // 已经有扩展名时不再添加
+        assert_eq!(
+            location(None, "capture.PNG").path_at(time),
+            PathBuf::from("capture.PNG")
+        );
+        // 无效的模板退回默认模板
+        assert_eq!(
+            location(None, "{oops}").path_at(time),
+            PathBuf::from("screenshot_1700000000.png")
+        );
+    }
+
+    #[test]
+    fn filename_template_validation() {
+        let valid = [
+            DEFAULT_FILENAME_TEMPLATE,
+            "{date} {time}",
+            "capture.png",
+            "a}b",
+        ];
+        for template in valid {
+            assert_eq!(validate_filename_template(template), Ok(()), "{template}");
+        }
+
+        let invalid = [
+            ("", "不能为空"),
+            ("   ", "不能为空"),
+            ("shots/{date}", "路径分隔符"),
+            ("shots\\{date}", "路径分隔符"),
+            ("{date", "右括号"),
+            ("{oops}", "{oops}"),
+            ("{}", "未知的文件名占位符"),
+            ("{Date}", "{Date}"),
+        ];
+        for (template, message) in invalid {
+            let err = validate_filename_template(template).unwrap_err();
+            assert!(err.contains(message), "{template}: {err}");
+        }
+    }
+}
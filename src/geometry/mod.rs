@@ -1,6 +1,7 @@
 
 use crate::{
     constants::constants::HANDLE_DETECT_SIZE,
+    coords::{PhysicalSize, ScaleFactor},
     types::{app_state::ResizeHandle, ui::Selection},
 };
 
@@ -9,51 +10,58 @@ pub fn point_in_rect(x: f32, y: f32, selection: &Selection) -> bool {
     x >= left && x <= right && y >= top && y <= bottom
 }
 
-pub fn get_resize_handle(x: f32, y: f32, selection: &Selection) -> Option<ResizeHandle> {
+// 手柄的检测范围按界面缩放比例放大，与绘制的手柄大小一致
+pub fn get_resize_handle(
+    x: f32,
+    y: f32,
+    selection: &Selection,
+    ui_scale: ScaleFactor,
+) -> Option<ResizeHandle> {
+    let detect = HANDLE_DETECT_SIZE * ui_scale.get();
     let (left, top, right, bottom) = selection.bounds();
     let center_x = (left + right) / 2.0;
     let center_y = (top + bottom) / 2.0;
 
     // 检查角手柄（优先级最高）
-    if (x - left).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE {
+    if (x - left).abs() <= detect && (y - top).abs() <= detect {
         return Some(ResizeHandle::TopLeft);
     }
-    if (x - right).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE {
+    if (x - right).abs() <= detect && (y - top).abs() <= detect {
         return Some(ResizeHandle::TopRight);
     }
-    if (x - right).abs() <= HANDLE_DETECT_SIZE && (y - bottom).abs() <= HANDLE_DETECT_SIZE {
+    if (x - right).abs() <= detect && (y - bottom).abs() <= detect {
         return Some(ResizeHandle::BottomRight);
     }
-    if (x - left).abs() <= HANDLE_DETECT_SIZE && (y - bottom).abs() <= HANDLE_DETECT_SIZE {
+    if (x - left).abs() <= detect && (y - bottom).abs() <= detect {
         return Some(ResizeHandle::BottomLeft);
     }
 
     // 检查边手柄（确保不与角手柄重叠）
     // 上边中间
-    if (x - center_x).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE {
+    if (x - center_x).abs() <= detect && (y - top).abs() <= detect {
         // 确保不在角手柄范围内
-        if (x - left).abs() > HANDLE_DETECT_SIZE && (x - right).abs() > HANDLE_DETECT_SIZE {
+        if (x - left).abs() > detect && (x - right).abs() > detect {
             return Some(ResizeHandle::Top);
         }
     }
     // 右边中间
-    if (x - right).abs() <= HANDLE_DETECT_SIZE && (y - center_y).abs() <= HANDLE_DETECT_SIZE {
+    if (x - right).abs() <= detect && (y - center_y).abs() <= detect {
         // 确保不在角手柄范围内
-        if (y - top).abs() > HANDLE_DETECT_SIZE && (y - bottom).abs() > HANDLE_DETECT_SIZE {
+        if (y - top).abs() > detect && (y - bottom).abs() > detect {
             return Some(ResizeHandle::Right);
         }
     }
     // 下边中间
-    if (x - center_x).abs() <= HANDLE_DETECT_SIZE && (y - bottom).abs() <= HANDLE_DETECT_SIZE {
+    if (x - center_x).abs() <= detect && (y - bottom).abs() <= detect {
         // 确保不在角手柄范围内
-        if (x - left).abs() > HANDLE_DETECT_SIZE && (x - right).abs() > HANDLE_DETECT_SIZE {
+        if (x - left).abs() > detect && (x - right).abs() > detect {
             return Some(ResizeHandle::Bottom);
         }
     }
     // 左边中间
-    if (x - left).abs() <= HANDLE_DETECT_SIZE && (y - center_y).abs() <= HANDLE_DETECT_SIZE {
+    if (x - left).abs() <= detect && (y - center_y).abs() <= detect {
         // 确保不在角手柄范围内
-        if (y - top).abs() > HANDLE_DETECT_SIZE && (y - bottom).abs() > HANDLE_DETECT_SIZE {
+        if (y - top).abs() > detect && (y - bottom).abs() > detect {
             return Some(ResizeHandle::Left);
         }
     }
@@ -78,10 +86,101 @@ pub fn get_resize_anchor(handle: ResizeHandle, selection: &Selection) -> (f32, f
     }
 }
 
-pub fn constrain_to_screen(selection: Selection, screen_size: (u32, u32)) -> Selection {
+// 手柄相对锚点的方向：-1 为左/上，1 为右/下，0 为边手柄上与之垂直的方向
+fn handle_direction(handle: ResizeHandle) -> (f32, f32) {
+    match handle {
+        ResizeHandle::TopLeft => (-1.0, -1.0),
+        ResizeHandle::Top => (0.0, -1.0),
+        ResizeHandle::TopRight => (1.0, -1.0),
+        ResizeHandle::Right => (1.0, 0.0),
+        ResizeHandle::BottomRight => (1.0, 1.0),
+        ResizeHandle::Bottom => (0.0, 1.0),
+        ResizeHandle::BottomLeft => (-1.0, 1.0),
+        ResizeHandle::Left => (-1.0, 0.0),
+    }
+}
+
+// 新建选择框时从起点拖向 pos，相当于拖动对应方向的角手柄
+pub fn corner_toward(anchor: (f32, f32), pos: (f32, f32)) -> ResizeHandle {
+    match (pos.0 >= anchor.0, pos.1 >= anchor.1) {
+        (true, true) => ResizeHandle::BottomRight,
+        (true, false) => ResizeHandle::TopRight,
+        (false, true) => ResizeHandle::BottomLeft,
+        (false, false) => ResizeHandle::TopLeft,
+    }
+}
+
+// 保持宽高比（宽 / 高）拖动手柄，锚点与 get_resize_anchor 相同
+// 角手柄取拖动距离较大的一边，边手柄以锚点为中心向两侧对称扩展
+// 宽高都不小于 min_size，放不下时缩小到屏幕内
+pub fn resize_with_ratio(
+    handle: ResizeHandle,
+    anchor: (f32, f32),
+    pos: (f32, f32),
+    ratio: f32,
+    min_size: f32,
+    screen_size: PhysicalSize,
+) -> Selection {
+    let (dir_x, dir_y) = handle_direction(handle);
+    let drag_width = (dir_x * (pos.0 - anchor.0)).max(0.0);
+    let drag_height = (dir_y * (pos.1 - anchor.1)).max(0.0);
+
+    let (mut width, mut height) = match (dir_x != 0.0, dir_y != 0.0) {
+        (true, true) if drag_width >= drag_height * ratio => (drag_width, drag_width / ratio),
+        (true, false) => (drag_width, drag_width / ratio),
+        _ => (drag_height * ratio, drag_height),
+    };
+    if width < min_size {
+        width = min_size;
+        height = width / ratio;
+    }
+    if height < min_size {
+        height = min_size;
+        width = height * ratio;
+    }
+
+    // 锚点到屏幕边缘的可用空间，边手柄两侧对称，取较近的一侧
+    let room = |dir: f32, anchor: f32, extent: f32| {
+        if dir > 0.0 {
+            extent - anchor
+        } else if dir < 0.0 {
+            anchor
+        } else {
+            2.0 * anchor.min(extent - anchor)
+        }
+    };
+    let max_width = room(dir_x, anchor.0, screen_size.width_f32());
+    let max_height = room(dir_y, anchor.1, screen_size.height_f32());
+    if width > max_width {
+        width = max_width;
+        height = width / ratio;
+    }
+    if height > max_height {
+        height = max_height;
+        width = height * ratio;
+    }
+
+    let start = |dir: f32, anchor: f32, length: f32| {
+        if dir > 0.0 {
+            anchor
+        } else if dir < 0.0 {
+            anchor - length
+        } else {
+            anchor - length / 2.0
+        }
+    };
+    let left = start(dir_x, anchor.0, width);
+    let top = start(dir_y, anchor.1, height);
+    Selection {
+        start: (left, top),
+        end: (left + width, top + height),
+    }
+}
+
+pub fn constrain_to_screen(selection: Selection, screen_size: PhysicalSize) -> Selection {
     let (width, height) = selection.size();
-    let screen_w = screen_size.0 as f32;
-    let screen_h = screen_size.1 as f32;
+    let screen_w = screen_size.width_f32();
+    let screen_h = screen_size.height_f32();
 
     let left = selection
         .start
@@ -101,3 +200,119 @@ pub fn constrain_to_screen(selection: Selection, screen_size: (u32, u32)) -> Sel
         end: (left + width, top + height),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATIO: f32 = 16.0 / 9.0;
+    const MIN_SIZE: f32 = 10.0;
+
+    fn assert_bounds(selection: Selection, expected: (f32, f32, f32, f32)) {
+        let actual = selection.bounds();
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(
+            close(actual.0, expected.0)
+                && close(actual.1, expected.1)
+                && close(actual.2, expected.2)
+                && close(actual.3, expected.3),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn every_handle_keeps_ratio() {
+        // 每个手柄拖到不同位置，都应得到同一个 16:9 的选择框
+        let screen = PhysicalSize::new(800, 600);
+        let expected = Selection {
+            start: (200.0, 150.0),
+            end: (520.0, 330.0),
+        };
+        let cases = [
+            // 角手柄取拖动距离较大的一边
+            (ResizeHandle::BottomRight, (520.0, 200.0)),
+            (ResizeHandle::TopLeft, (200.0, 300.0)),
+            (ResizeHandle::TopRight, (360.0, 150.0)),
+            (ResizeHandle::BottomLeft, (400.0, 330.0)),
+            // 边手柄只看拖动方向，另一边以锚点为中心对称
+            (ResizeHandle::Right, (520.0, 999.0)),
+            (ResizeHandle::Left, (200.0, 0.0)),
+            (ResizeHandle::Top, (0.0, 150.0)),
+            (ResizeHandle::Bottom, (360.0, 330.0)),
+        ];
+        for (handle, pos) in cases {
+            let anchor = get_resize_anchor(handle, &expected);
+            let resized = resize_with_ratio(handle, anchor, pos, RATIO, MIN_SIZE, screen);
+            assert_bounds(resized, expected.bounds());
+        }
+    }
+
+    #[test]
+    fn ratio_respects_min_size() {
+        // 宽的比例由高度决定下限，窄的比例由宽度决定下限
+        let cases = [
+            ((102.0, 101.0), RATIO, (MIN_SIZE * RATIO, MIN_SIZE)),
+            ((50.0, 50.0), RATIO, (MIN_SIZE * RATIO, MIN_SIZE)),
+            ((101.0, 102.0), 0.5, (MIN_SIZE, MIN_SIZE * 2.0)),
+        ];
+        for (pos, ratio, (width, height)) in cases {
+            let resized = resize_with_ratio(
+                ResizeHandle::BottomRight,
+                (100.0, 100.0),
+                pos,
+                ratio,
+                MIN_SIZE,
+                PhysicalSize::new(800, 600),
+            );
+            assert_bounds(resized, (100.0, 100.0, 100.0 + width, 100.0 + height));
+        }
+    }
+
+    #[test]
+    fn corner_handles_stop_at_screen_edge() {
+        let resized = resize_with_ratio(
+            ResizeHandle::BottomRight,
+            (600.0, 400.0),
+            (900.0, 420.0),
+            RATIO,
+            MIN_SIZE,
+            PhysicalSize::new(800, 600),
+        );
+        assert_bounds(resized, (600.0, 400.0, 800.0, 512.5));
+
+        let resized = resize_with_ratio(
+            ResizeHandle::TopLeft,
+            (100.0, 100.0),
+            (-300.0, 0.0),
+            RATIO,
+            MIN_SIZE,
+            PhysicalSize::new(800, 600),
+        );
+        assert_bounds(resized, (0.0, 43.75, 100.0, 100.0));
+    }
+
+    #[test]
+    fn edge_handles_stop_at_nearer_screen_edge() {
+        // 锚点离上边只有 50，对称扩展时高度最多 100
+        let resized = resize_with_ratio(
+            ResizeHandle::Right,
+            (100.0, 50.0),
+            (500.0, 50.0),
+            RATIO,
+            MIN_SIZE,
+            PhysicalSize::new(800, 600),
+        );
+        assert_bounds(resized, (100.0, 0.0, 100.0 + 100.0 * RATIO, 100.0));
+
+        // 锚点离右边只有 20，对称扩展时宽度最多 40
+        let resized = resize_with_ratio(
+            ResizeHandle::Bottom,
+            (780.0, 100.0),
+            (780.0, 300.0),
+            RATIO,
+            MIN_SIZE,
+            PhysicalSize::new(800, 600),
+        );
+        assert_bounds(resized, (760.0, 100.0, 800.0, 122.5));
+    }
+}
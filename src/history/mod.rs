@@ -0,0 +1,398 @@
+use crate::types::{drawing::DrawingShape, ui::Selection};
+
+// 可撤销的编辑命令，拖拽类操作在松开鼠标时才记录一次
+#[derive(Debug, Clone)]
+pub enum Command {
+    AddShape {
+        index: usize,
+        shape: DrawingShape,
+    },
+    DeleteShape {
+        index: usize,
+        shape: DrawingShape,
+    },
+    MoveShape {
+        index: usize,
+        before: DrawingShape,
+        after: DrawingShape,
+    },
+    ResizeShape {
+        index: usize,
+        before: DrawingShape,
+        after: DrawingShape,
+    },
+    RestyleShape {
+        index: usize,
+        before: DrawingShape,
+        after: DrawingShape,
+    },
+    EditText {
+        index: usize,
+        before: DrawingShape,
+        after: DrawingShape,
+    },
+    ChangeSelection {
+        before: Option<Selection>,
+        after: Option<Selection>,
+    },
+}
+
+impl Command {
+    fn apply(&self, shapes: &mut Vec<DrawingShape>, selection: &mut Option<Selection>) {
+        match self {
+            Command::AddShape { index, shape } => {
+                shapes.insert((*index).min(shapes.len()), shape.clone());
+            }
+            Command::DeleteShape { index, .. } => {
+                if *index < shapes.len() {
+                    shapes.remove(*index);
+                }
+            }
+            Command::MoveShape { index, after, .. }
+            | Command::ResizeShape { index, after, .. }
+            | Command::RestyleShape { index, after, .. }
+            | Command::EditText { index, after, .. } => {
+                if let Some(shape) = shapes.get_mut(*index) {
+                    *shape = after.clone();
+                }
+            }
+            Command::ChangeSelection { after, .. } => {
+                *selection = *after;
+            }
+        }
+    }
+
+    fn revert(&self, shapes: &mut Vec<DrawingShape>, selection: &mut Option<Selection>) {
+        match self {
+            Command::AddShape { index, .. } => {
+                if *index < shapes.len() {
+                    shapes.remove(*index);
+                }
+            }
+            Command::DeleteShape { index, shape } => {
+                shapes.insert((*index).min(shapes.len()), shape.clone());
+            }
+            Command::MoveShape { index, before, .. }
+            | Command::ResizeShape { index, before, .. }
+            | Command::RestyleShape { index, before, .. }
+            | Command::EditText { index, before, .. } => {
+                if let Some(shape) = shapes.get_mut(*index) {
+                    *shape = before.clone();
+                }
+            }
+            Command::ChangeSelection { before, .. } => {
+                *selection = *before;
+            }
+        }
+    }
+
+    // 前后状态相同的命令没有必要记录
+    fn is_noop(&self) -> bool {
+        match self {
+            Command::MoveShape { before, after, .. }
+            | Command::ResizeShape { before, after, .. }
+            | Command::RestyleShape { before, after, .. }
+            | Command::EditText { before, after, .. } => before == after,
+            Command::ChangeSelection { before, after } => before == after,
+            _ => false,
+        }
+    }
+}
+
+// 编辑历史（会话内不限深度）
+#[derive(Debug, Default)]
+pub struct History {
+    undo_stack: Vec<Command>,
+    redo_stack: Vec<Command>,
+}
+
+impl History {
+    pub fn push(&mut self, command: Command) {
+        if command.is_noop() {
+            return;
+        }
+        self.undo_stack.push(command);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(
+        &mut self,
+        shapes: &mut Vec<DrawingShape>,
+        selection: &mut Option<Selection>,
+    ) -> bool {
+        let Some(command) = self.undo_stack.pop() else {
+            return false;
+        };
+        command.revert(shapes, selection);
+        self.redo_stack.push(command);
+        true
+    }
+
+    pub fn redo(
+        &mut self,
+        shapes: &mut Vec<DrawingShape>,
+        selection: &mut Option<Selection>,
+    ) -> bool {
+        let Some(command) = self.redo_stack.pop() else {
+            return false;
+        };
+        command.apply(shapes, selection);
+        self.undo_stack.push(command);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::Color;
+
+    use super::*;
+
+    fn rectangle(start: (f32, f32), end: (f32, f32), color: Color) -> DrawingShape {
+        DrawingShape::Rectangle {
+            start,
+            end,
+            color,
+            stroke_width: 3.0,
+        }
+    }
+
+    fn text(content: &str) -> DrawingShape {
+        DrawingShape::Text {
+            position: (10.0, 10.0),
+            content: content.into(),
+            font_size: 20.0,
+            color: Color::RED,
+            background: None,
+        }
+    }
+
+    fn selection(start: (f32, f32), end: (f32, f32)) -> Option<Selection> {
+        Some(Selection { start, end })
+    }
+
+    // 命令，以及命令执行前后的图形和选择框
+    struct Case {
+        command: Command,
+        shapes: [Vec<DrawingShape>; 2],
+        selection: [Option<Selection>; 2],
+    }
+
+    // 每种命令各一个
+    fn cases() -> Vec<Case> {
+        let first = rectangle((0.0, 0.0), (10.0, 10.0), Color::RED);
+        let second = rectangle((20.0, 20.0), (40.0, 40.0), Color::BLUE);
+        let moved = rectangle((5.0, 5.0), (15.0, 15.0), Color::RED);
+        let resized = rectangle((0.0, 0.0), (30.0, 20.0), Color::RED);
+        let restyled = rectangle((0.0, 0.0), (10.0, 10.0), Color::GREEN);
+        let area = selection((0.0, 0.0), (100.0, 100.0));
+
+        let mut cases = Vec::new();
+        for (command, before, after) in [
+            (
+                Command::AddShape {
+                    index: 1,
+                    shape: second.clone(),
+                },
+                vec![first.clone()],
+                vec![first.clone(), second.clone()],
+            ),
+            (
+                Command::DeleteShape {
+                    index: 0,
+                    shape: first.clone(),
+                },
+                vec![first.clone(), second.clone()],
+                vec![second.clone()],
+            ),
+            (
+                Command::MoveShape {
+                    index: 0,
+                    before: first.clone(),
+                    after: moved.clone(),
+                },
+                vec![first.clone(), second.clone()],
+                vec![moved, second.clone()],
+            ),
+            (
+                Command::ResizeShape {
+                    index: 0,
+                    before: first.clone(),
+                    after: resized.clone(),
+                },
+                vec![first.clone()],
+                vec![resized],
+            ),
+            (
+                Command::RestyleShape {
+                    index: 0,
+                    before: first.clone(),
+                    after: restyled.clone(),
+                },
+                vec![first.clone()],
+                vec![restyled],
+            ),
+            (
+                Command::EditText {
+                    index: 1,
+                    before: text("ab"),
+                    after: text("abc"),
+                },
+                vec![first.clone(), text("ab")],
+                vec![first.clone(), text("abc")],
+            ),
+        ] {
+            cases.push(Case {
+                command,
+                shapes: [before, after],
+                selection: [area, area],
+            });
+        }
+
+        let moved_area = selection((10.0, 10.0), (60.0, 50.0));
+        cases.push(Case {
+            command: Command::ChangeSelection {
+                before: area,
+                after: moved_area,
+            },
+            shapes: [vec![first.clone()], vec![first]],
+            selection: [area, moved_area],
+        });
+        cases.push(Case {
+            command: Command::ChangeSelection {
+                before: None,
+                after: area,
+            },
+            shapes: [Vec::new(), Vec::new()],
+            selection: [None, area],
+        });
+        cases
+    }
+
+    #[test]
+    fn every_command_round_trips() {
+        for Case {
+            command,
+            shapes: [shapes_before, shapes_after],
+            selection: [selection_before, selection_after],
+        } in cases()
+        {
+            // 从执行后的状态撤销，再重做回来
+            let mut shapes = shapes_after.clone();
+            let mut selection = selection_after;
+            let mut history = History::default();
+            history.push(command.clone());
+
+            assert!(history.undo(&mut shapes, &mut selection), "{command:?}");
+            assert_eq!(shapes, shapes_before, "{command:?}");
+            assert_eq!(selection, selection_before, "{command:?}");
+            assert!(!history.can_undo());
+
+            assert!(history.redo(&mut shapes, &mut selection), "{command:?}");
+            assert_eq!(shapes, shapes_after, "{command:?}");
+            assert_eq!(selection, selection_after, "{command:?}");
+            assert!(!history.can_redo());
+        }
+    }
+
+    #[test]
+    fn noop_commands_are_not_recorded() {
+        let shape = rectangle((0.0, 0.0), (10.0, 10.0), Color::RED);
+        let area = selection((0.0, 0.0), (100.0, 100.0));
+        let noops = [
+            Command::MoveShape {
+                index: 0,
+                before: shape.clone(),
+                after: shape.clone(),
+            },
+            Command::ResizeShape {
+                index: 0,
+                before: shape.clone(),
+                after: shape.clone(),
+            },
+            Command::RestyleShape {
+                index: 0,
+                before: shape.clone(),
+                after: shape.clone(),
+            },
+            Command::EditText {
+                index: 0,
+                before: text("ab"),
+                after: text("ab"),
+            },
+            Command::ChangeSelection {
+                before: area,
+                after: area,
+            },
+        ];
+
+        let mut history = History::default();
+        for command in noops {
+            history.push(command);
+        }
+        assert!(!history.can_undo());
+
+        // 添加和删除图形即使图形相同也要记录
+        history.push(Command::AddShape { index: 0, shape });
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let shape = rectangle((0.0, 0.0), (10.0, 10.0), Color::RED);
+        let mut shapes = vec![shape.clone()];
+        let mut selection = None;
+        let mut history = History::default();
+        history.push(Command::AddShape {
+            index: 0,
+            shape: shape.clone(),
+        });
+        assert!(history.undo(&mut shapes, &mut selection));
+        assert!(history.can_redo());
+
+        // 空操作不会清掉重做
+        history.push(Command::ChangeSelection {
+            before: None,
+            after: None,
+        });
+        assert!(history.can_redo());
+
+        history.push(Command::ChangeSelection {
+            before: None,
+            after: selection((0.0, 0.0), (100.0, 100.0)),
+        });
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut shapes, &mut selection));
+        assert!(shapes.is_empty());
+    }
+
+    #[test]
+    fn undo_and_redo_on_empty_history() {
+        let mut shapes = Vec::new();
+        let mut selection = None;
+        let mut history = History::default();
+        assert!(!history.undo(&mut shapes, &mut selection));
+        assert!(!history.redo(&mut shapes, &mut selection));
+
+        history.push(Command::ChangeSelection {
+            before: None,
+            after: selection((0.0, 0.0), (100.0, 100.0)),
+        });
+        history.clear();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+    }
+}
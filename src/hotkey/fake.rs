@@ -0,0 +1,71 @@
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use super::{Hotkey, HotkeyBackend, HotkeyError};
+
+/// 不依赖桌面环境的热键后端，按键由 FakeKeyboard 模拟
+pub struct FakeHotkeys {
+    registered: Vec<Hotkey>,
+    // 模拟已被其他程序占用的热键
+    taken: Vec<Hotkey>,
+    presses: Receiver<Hotkey>,
+}
+
+// 向 FakeHotkeys 发送按键，丢弃后 wait 返回错误
+#[derive(Clone)]
+pub struct FakeKeyboard {
+    sender: Sender<Hotkey>,
+}
+
+impl FakeKeyboard {
+    pub fn press(&self, hotkey: Hotkey) {
+        let _ = self.sender.send(hotkey);
+    }
+}
+
+impl FakeHotkeys {
+    pub fn new() -> (Self, FakeKeyboard) {
+        let (sender, presses) = channel();
+        let backend = Self {
+            registered: Vec::new(),
+            taken: Vec::new(),
+            presses,
+        };
+        (backend, FakeKeyboard { sender })
+    }
+
+    pub fn with_taken(mut self, hotkey: Hotkey) -> Self {
+        self.taken.push(hotkey);
+        self
+    }
+
+    pub fn registered(&self) -> &[Hotkey] {
+        &self.registered
+    }
+}
+
+impl HotkeyBackend for FakeHotkeys {
+    fn register(&mut self, hotkey: &Hotkey) -> Result<(), HotkeyError> {
+        if self.taken.contains(hotkey) {
+            return Err(HotkeyError::Taken(*hotkey));
+        }
+        self.registered.push(*hotkey);
+        Ok(())
+    }
+
+    fn unregister(&mut self, hotkey: &Hotkey) {
+        self.registered.retain(|registered| registered != hotkey);
+    }
+
+    // 和真实的键盘抓取一样，没有注册的按键不会送达
+    fn wait(&mut self) -> Result<Hotkey, HotkeyError> {
+        loop {
+            let hotkey = self
+                .presses
+                .recv()
+                .map_err(|_| HotkeyError::Backend("模拟键盘已断开".into()))?;
+            if self.registered.contains(&hotkey) {
+                return Ok(hotkey);
+            }
+        }
+    }
+}
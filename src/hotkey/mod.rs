@@ -0,0 +1,558 @@
+pub mod fake;
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+use std::{fmt, str::FromStr, thread::JoinHandle};
+
+use crate::cli::CaptureMode;
+
+// 全局热键触发的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyAction {
+    CaptureRegion,
+    CaptureFullscreen,
+    RepeatLastRegion,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 3] = [
+        HotkeyAction::CaptureRegion,
+        HotkeyAction::CaptureFullscreen,
+        HotkeyAction::RepeatLastRegion,
+    ];
+
+    // 与命令行模式同名
+    pub fn name(&self) -> &'static str {
+        match self {
+            HotkeyAction::CaptureRegion => "region",
+            HotkeyAction::CaptureFullscreen => "fullscreen",
+            HotkeyAction::RepeatLastRegion => "last-region",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn capture_mode(&self) -> CaptureMode {
+        match self {
+            HotkeyAction::CaptureRegion => CaptureMode::Region,
+            HotkeyAction::CaptureFullscreen => CaptureMode::Fullscreen,
+            HotkeyAction::RepeatLastRegion => CaptureMode::LastRegion,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HotkeyModifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    // Windows 键 / Command 键
+    pub super_key: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyKey {
+    // 小写 ASCII 字母或数字
+    Char(char),
+    // 功能键 F1-F24
+    Function(u8),
+    PrintScreen,
+    Pause,
+    ScrollLock,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Space,
+}
+
+impl HotkeyKey {
+    fn parse(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return c
+                .is_ascii_alphanumeric()
+                .then(|| HotkeyKey::Char(c.to_ascii_lowercase()));
+        }
+
+        let key = match name.to_ascii_lowercase().as_str() {
+            "print" | "printscreen" | "prtsc" => HotkeyKey::PrintScreen,
+            "pause" => HotkeyKey::Pause,
+            "scrolllock" => HotkeyKey::ScrollLock,
+            "insert" => HotkeyKey::Insert,
+            "home" => HotkeyKey::Home,
+            "end" => HotkeyKey::End,
+            "pageup" => HotkeyKey::PageUp,
+            "pagedown" => HotkeyKey::PageDown,
+            "space" => HotkeyKey::Space,
+            name => {
+                let number = name.strip_prefix('f')?.parse::<u8>().ok()?;
+                return (1..=24)
+                    .contains(&number)
+                    .then_some(HotkeyKey::Function(number));
+            }
+        };
+        Some(key)
+    }
+}
+
+impl fmt::Display for HotkeyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyKey::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            HotkeyKey::Function(number) => write!(f, "F{number}"),
+            HotkeyKey::PrintScreen => write!(f, "PrintScreen"),
+            HotkeyKey::Pause => write!(f, "Pause"),
+            HotkeyKey::ScrollLock => write!(f, "ScrollLock"),
+            HotkeyKey::Insert => write!(f, "Insert"),
+            HotkeyKey::Home => write!(f, "Home"),
+            HotkeyKey::End => write!(f, "End"),
+            HotkeyKey::PageUp => write!(f, "PageUp"),
+            HotkeyKey::PageDown => write!(f, "PageDown"),
+            HotkeyKey::Space => write!(f, "Space"),
+        }
+    }
+}
+
+/// 全局热键，例如 Ctrl+Shift+A 或 PrintScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub modifiers: HotkeyModifiers,
+    pub key: HotkeyKey,
+}
+
+impl Hotkey {
+    pub const fn new(modifiers: HotkeyModifiers, key: HotkeyKey) -> Self {
+        Self { modifiers, key }
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = HotkeyError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || HotkeyError::Parse(text.to_string());
+        let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+        let key = parts.pop().and_then(HotkeyKey::parse).ok_or_else(invalid)?;
+
+        let mut modifiers = HotkeyModifiers::default();
+        for part in parts {
+            let flag = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut modifiers.ctrl,
+                "shift" => &mut modifiers.shift,
+                "alt" => &mut modifiers.alt,
+                "super" | "win" | "meta" | "cmd" => &mut modifiers.super_key,
+                _ => return Err(invalid()),
+            };
+            if *flag {
+                return Err(invalid());
+            }
+            *flag = true;
+        }
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let HotkeyModifiers {
+            ctrl,
+            shift,
+            alt,
+            super_key,
+        } = self.modifiers;
+        for (enabled, name) in [
+            (ctrl, "Ctrl"),
+            (shift, "Shift"),
+            (alt, "Alt"),
+            (super_key, "Super"),
+        ] {
+            if enabled {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    pub hotkey: Hotkey,
+    pub action: HotkeyAction,
+}
+
+impl FromStr for HotkeyBinding {
+    type Err = HotkeyError;
+
+    // 动作=热键，例如 region=Ctrl+Shift+A
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (action, hotkey) = text
+            .split_once('=')
+            .ok_or_else(|| HotkeyError::Parse(text.to_string()))?;
+        let action = HotkeyAction::from_name(action.trim())
+            .ok_or_else(|| HotkeyError::UnknownAction(action.trim().to_string()))?;
+        Ok(Self {
+            hotkey: hotkey.parse()?,
+            action,
+        })
+    }
+}
+
+// 默认热键：PrintScreen 框选，Shift+PrintScreen 全屏，Ctrl+PrintScreen 重复上一次区域
+pub fn default_bindings() -> Vec<HotkeyBinding> {
+    let shift = HotkeyModifiers {
+        shift: true,
+        ..Default::default()
+    };
+    let ctrl = HotkeyModifiers {
+        ctrl: true,
+        ..Default::default()
+    };
+    vec![
+        HotkeyBinding {
+            hotkey: Hotkey::new(HotkeyModifiers::default(), HotkeyKey::PrintScreen),
+            action: HotkeyAction::CaptureRegion,
+        },
+        HotkeyBinding {
+            hotkey: Hotkey::new(shift, HotkeyKey::PrintScreen),
+            action: HotkeyAction::CaptureFullscreen,
+        },
+        HotkeyBinding {
+            hotkey: Hotkey::new(ctrl, HotkeyKey::PrintScreen),
+            action: HotkeyAction::RepeatLastRegion,
+        },
+    ]
+}
+
+// 用户指定的绑定替换同一动作的默认绑定
+pub fn with_defaults(overrides: &[HotkeyBinding]) -> Vec<HotkeyBinding> {
+    override_bindings(default_bindings(), overrides)
+}
+
+// overrides 中的绑定替换 base 中同一动作的绑定（命令行覆盖配置文件）
+pub fn override_bindings(
+    mut base: Vec<HotkeyBinding>,
+    overrides: &[HotkeyBinding],
+) -> Vec<HotkeyBinding> {
+    base.retain(|binding| !overrides.iter().any(|o| o.action == binding.action));
+    base.extend_from_slice(overrides);
+    base
+}
+
+#[derive(Debug)]
+pub enum HotkeyError {
+    // 热键或绑定格式错误
+    Parse(String),
+    UnknownAction(String),
+    // 当前键盘布局上没有这个键
+    UnknownKey(Hotkey),
+    // 同一个热键绑定了多个动作
+    Duplicate(Hotkey),
+    // 热键已被其他程序占用
+    Taken(Hotkey),
+    // 当前平台或会话不支持全局热键（例如 Wayland）
+    Unsupported,
+    Backend(String),
+}
+
+impl fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyError::Parse(text) => {
+                write!(
+                    f,
+                    "无效的热键: {text:?}，例如 Ctrl+Shift+A 或 region=PrintScreen"
+                )
+            }
+            HotkeyError::UnknownAction(name) => write!(
+                f,
+                "未知的热键动作: {name:?}，可用动作为 region、fullscreen、last-region"
+            ),
+            HotkeyError::UnknownKey(hotkey) => {
+                write!(f, "无法注册热键 {hotkey}: 当前键盘上没有这个键")
+            }
+            HotkeyError::Duplicate(hotkey) => write!(f, "热键 {hotkey} 被绑定到了多个动作"),
+            HotkeyError::Taken(hotkey) => {
+                write!(f, "无法注册热键 {hotkey}: 已被其他程序占用")
+            }
+            HotkeyError::Unsupported => write!(f, "当前平台或会话不支持全局热键"),
+            HotkeyError::Backend(message) => write!(f, "全局热键失败: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyError {}
+
+/// 全局热键后端
+pub trait HotkeyBackend: Send {
+    fn register(&mut self, hotkey: &Hotkey) -> Result<(), HotkeyError>;
+
+    fn unregister(&mut self, hotkey: &Hotkey);
+
+    // 阻塞直到某个已注册的热键被按下
+    fn wait(&mut self) -> Result<Hotkey, HotkeyError>;
+}
+
+// 当前平台的热键后端
+pub fn default_backend() -> Result<Box<dyn HotkeyBackend>, HotkeyError> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(x11::X11Hotkeys::connect()?))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(HotkeyError::Unsupported)
+    }
+}
+
+/// 热键与动作的绑定，注册到后端并把按键转为动作
+pub struct HotkeyManager {
+    backend: Box<dyn HotkeyBackend>,
+    bindings: Vec<HotkeyBinding>,
+}
+
+impl HotkeyManager {
+    pub fn new(backend: Box<dyn HotkeyBackend>) -> Self {
+        Self {
+            backend,
+            bindings: Vec::new(),
+        }
+    }
+
+    // 注册所有绑定，返回无法注册的绑定的错误；其余绑定照常生效
+    pub fn register(&mut self, bindings: &[HotkeyBinding]) -> Vec<HotkeyError> {
+        let mut errors = Vec::new();
+        for binding in bindings {
+            if self.bindings.iter().any(|b| b.hotkey == binding.hotkey) {
+                errors.push(HotkeyError::Duplicate(binding.hotkey));
+                continue;
+            }
+            match self.backend.register(&binding.hotkey) {
+                Ok(()) => self.bindings.push(*binding),
+                Err(err) => errors.push(err),
+            }
+        }
+        errors
+    }
+
+    pub fn unregister_all(&mut self) {
+        for binding in self.bindings.drain(..) {
+            self.backend.unregister(&binding.hotkey);
+        }
+    }
+
+    pub fn bindings(&self) -> &[HotkeyBinding] {
+        &self.bindings
+    }
+
+    // 阻塞直到某个绑定的热键被按下
+    pub fn wait(&mut self) -> Result<HotkeyAction, HotkeyError> {
+        loop {
+            let hotkey = self.backend.wait()?;
+            if let Some(binding) = self.bindings.iter().find(|b| b.hotkey == hotkey) {
+                return Ok(binding.action);
+            }
+        }
+    }
+
+    // 在后台线程中等待热键，后端出错时线程结束
+    pub fn spawn(mut self, on_action: impl Fn(HotkeyAction) + Send + 'static) -> JoinHandle<()> {
+        std::thread::spawn(move || {
+            loop {
+                match self.wait() {
+                    Ok(action) => on_action(action),
+                    Err(err) => {
+                        eprintln!("{err}");
+                        break;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Drop for HotkeyManager {
+    fn drop(&mut self) {
+        self.unregister_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fake::FakeHotkeys, *};
+
+    fn hotkey(text: &str) -> Hotkey {
+        text.parse().unwrap()
+    }
+
+    fn binding(text: &str) -> HotkeyBinding {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn hotkey_display_round_trip() {
+        let cases = [
+            ("PrintScreen", "PrintScreen"),
+            ("prtsc", "PrintScreen"),
+            ("ctrl+shift+a", "Ctrl+Shift+A"),
+            ("Super+Alt+F12", "Alt+Super+F12"),
+            ("Win + Print", "Super+PrintScreen"),
+            ("Control+7", "Ctrl+7"),
+            ("Shift+space", "Shift+Space"),
+            ("Ctrl+PageDown", "Ctrl+PageDown"),
+        ];
+        for (text, expected) in cases {
+            let parsed = hotkey(text);
+            assert_eq!(parsed.to_string(), expected, "{text}");
+            assert_eq!(hotkey(expected), parsed, "{text}");
+        }
+    }
+
+    #[test]
+    fn invalid_hotkeys_are_rejected() {
+        let cases = [
+            "",
+            "Ctrl+",
+            "Hyper+A",
+            "Ctrl+Ctrl+A",
+            "A+Ctrl",
+            "Ctrl+AB",
+            "F0",
+            "F25",
+            "Ctrl+é",
+            "Ctrl+-",
+        ];
+        for text in cases {
+            let err = text.parse::<Hotkey>().unwrap_err();
+            assert!(
+                matches!(&err, HotkeyError::Parse(parsed) if parsed == text),
+                "{text}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn binding_parsing() {
+        assert_eq!(
+            binding(" last-region = Ctrl+Shift+R "),
+            HotkeyBinding {
+                hotkey: hotkey("Ctrl+Shift+R"),
+                action: HotkeyAction::RepeatLastRegion,
+            }
+        );
+        assert!(matches!(
+            "zoom=F1".parse::<HotkeyBinding>(),
+            Err(HotkeyError::UnknownAction(name)) if name == "zoom"
+        ));
+        assert!(matches!(
+            "region".parse::<HotkeyBinding>(),
+            Err(HotkeyError::Parse(_))
+        ));
+        assert!(matches!(
+            "region=Ctrl+".parse::<HotkeyBinding>(),
+            Err(HotkeyError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn overrides_replace_defaults_of_same_action() {
+        // 默认值与配置文件注释中写的一致
+        assert_eq!(
+            default_bindings(),
+            [
+                binding("region=PrintScreen"),
+                binding("fullscreen=Shift+PrintScreen"),
+                binding("last-region=Ctrl+PrintScreen"),
+            ]
+        );
+        assert_eq!(with_defaults(&[]), default_bindings());
+
+        let bindings = with_defaults(&[binding("region=F1")]);
+        assert_eq!(
+            bindings,
+            [
+                binding("fullscreen=Shift+PrintScreen"),
+                binding("last-region=Ctrl+PrintScreen"),
+                binding("region=F1"),
+            ]
+        );
+
+        // 命令行覆盖配置文件
+        let config = with_defaults(&[binding("fullscreen=F2")]);
+        let bindings = override_bindings(config, &[binding("fullscreen=F3")]);
+        assert_eq!(bindings.len(), 3);
+        assert!(bindings.contains(&binding("fullscreen=F3")));
+        assert!(!bindings.contains(&binding("fullscreen=F2")));
+    }
+
+    #[test]
+    fn register_reports_taken_and_duplicate_hotkeys() {
+        let (backend, _keyboard) = FakeHotkeys::new();
+        let backend = backend.with_taken(hotkey("Shift+PrintScreen"));
+        let mut manager = HotkeyManager::new(Box::new(backend));
+
+        let mut bindings = default_bindings();
+        bindings.push(binding("fullscreen=PrintScreen"));
+        let errors = manager.register(&bindings);
+
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(
+            matches!(errors[0], HotkeyError::Taken(taken) if taken == hotkey("Shift+PrintScreen"))
+        );
+        assert!(
+            matches!(errors[1], HotkeyError::Duplicate(duplicate) if duplicate == hotkey("PrintScreen"))
+        );
+        // 其余绑定照常生效
+        assert_eq!(
+            manager.bindings(),
+            [
+                binding("region=PrintScreen"),
+                binding("last-region=Ctrl+PrintScreen"),
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_maps_hotkeys_to_actions() {
+        let (backend, keyboard) = FakeHotkeys::new();
+        let mut manager = HotkeyManager::new(Box::new(backend));
+        assert!(manager.register(&default_bindings()).is_empty());
+
+        // 没有注册的热键被忽略
+        keyboard.press(hotkey("F1"));
+        keyboard.press(hotkey("Ctrl+PrintScreen"));
+        keyboard.press(hotkey("PrintScreen"));
+        keyboard.press(hotkey("Shift+PrintScreen"));
+        assert_eq!(manager.wait().unwrap(), HotkeyAction::RepeatLastRegion);
+        assert_eq!(manager.wait().unwrap(), HotkeyAction::CaptureRegion);
+        assert_eq!(manager.wait().unwrap(), HotkeyAction::CaptureFullscreen);
+
+        drop(keyboard);
+        assert!(matches!(manager.wait(), Err(HotkeyError::Backend(_))));
+    }
+
+    #[test]
+    fn unregistered_hotkeys_are_not_delivered() {
+        let (backend, keyboard) = FakeHotkeys::new();
+        let mut manager = HotkeyManager::new(Box::new(backend));
+        manager.register(&default_bindings());
+        manager.unregister_all();
+        assert!(manager.bindings().is_empty());
+
+        keyboard.press(hotkey("PrintScreen"));
+        drop(keyboard);
+        assert!(matches!(manager.wait(), Err(HotkeyError::Backend(_))));
+    }
+
+    #[test]
+    fn fake_backend_tracks_registrations() {
+        let (mut backend, _keyboard) = FakeHotkeys::new();
+        backend.register(&hotkey("F1")).unwrap();
+        backend.register(&hotkey("F2")).unwrap();
+        backend.unregister(&hotkey("F1"));
+        assert_eq!(backend.registered(), [hotkey("F2")]);
+    }
+}
@@ -0,0 +1,177 @@
+use x11rb::{
+    connection::Connection,
+    errors::ReplyError,
+    protocol::{
+        ErrorKind, Event,
+        xproto::{ConnectionExt as _, GrabMode, Keycode, ModMask, Window},
+    },
+    rust_connection::RustConnection,
+};
+
+use super::{Hotkey, HotkeyBackend, HotkeyError, HotkeyKey};
+
+// 参与匹配的修饰键，其余（NumLock、CapsLock 等）在抓取和匹配时忽略
+fn modifier_mask() -> u16 {
+    u16::from(ModMask::CONTROL | ModMask::SHIFT | ModMask::M1 | ModMask::M4)
+}
+
+// CapsLock 和 NumLock 的所有组合，都要分别抓取一次
+fn lock_masks() -> [u16; 4] {
+    let (caps_lock, num_lock) = (u16::from(ModMask::LOCK), u16::from(ModMask::M2));
+    [0, caps_lock, num_lock, caps_lock | num_lock]
+}
+
+/// 通过 XGrabKey 在根窗口上抓取热键
+pub struct X11Hotkeys {
+    connection: RustConnection,
+    root: Window,
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<u32>,
+    grabs: Vec<(Hotkey, Keycode, u16)>,
+}
+
+impl X11Hotkeys {
+    pub fn connect() -> Result<Self, HotkeyError> {
+        // 纯 Wayland 会话没有 X 服务器，无法抓取全局按键
+        if std::env::var_os("DISPLAY").is_none() {
+            return Err(HotkeyError::Unsupported);
+        }
+        let (connection, screen) =
+            x11rb::connect(None).map_err(|err| HotkeyError::Backend(err.to_string()))?;
+        let setup = connection.setup();
+        let root = setup.roots[screen].root;
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+
+        let mapping = connection
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|err| HotkeyError::Backend(err.to_string()))?
+            .reply()
+            .map_err(|err| HotkeyError::Backend(err.to_string()))?;
+
+        Ok(Self {
+            connection,
+            root,
+            min_keycode,
+            keysyms_per_keycode: mapping.keysyms_per_keycode.max(1) as usize,
+            keysyms: mapping.keysyms,
+            grabs: Vec::new(),
+        })
+    }
+
+    fn keycode(&self, keysym: u32) -> Option<Keycode> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode)
+            .position(|keysyms| keysyms.contains(&keysym))
+            .map(|index| self.min_keycode + index as u8)
+    }
+
+    fn ungrab(&self, keycode: Keycode, modifiers: u16) {
+        for lock in lock_masks() {
+            let _ = self
+                .connection
+                .ungrab_key(keycode, self.root, ModMask::from(modifiers | lock));
+        }
+        let _ = self.connection.flush();
+    }
+}
+
+fn keysym(key: HotkeyKey) -> u32 {
+    match key {
+        // 小写字母和数字的 keysym 与 ASCII 相同
+        HotkeyKey::Char(c) => c as u32,
+        HotkeyKey::Function(number) => 0xffbe + u32::from(number) - 1,
+        HotkeyKey::PrintScreen => 0xff61,
+        HotkeyKey::Pause => 0xff13,
+        HotkeyKey::ScrollLock => 0xff14,
+        HotkeyKey::Insert => 0xff63,
+        HotkeyKey::Home => 0xff50,
+        HotkeyKey::End => 0xff57,
+        HotkeyKey::PageUp => 0xff55,
+        HotkeyKey::PageDown => 0xff56,
+        HotkeyKey::Space => 0x20,
+    }
+}
+
+fn modifier_bits(hotkey: &Hotkey) -> u16 {
+    let modifiers = hotkey.modifiers;
+    [
+        (modifiers.ctrl, ModMask::CONTROL),
+        (modifiers.shift, ModMask::SHIFT),
+        (modifiers.alt, ModMask::M1),
+        (modifiers.super_key, ModMask::M4),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .fold(0, |bits, (_, mask)| bits | u16::from(mask))
+}
+
+impl HotkeyBackend for X11Hotkeys {
+    fn register(&mut self, hotkey: &Hotkey) -> Result<(), HotkeyError> {
+        let keycode = self
+            .keycode(keysym(hotkey.key))
+            .ok_or(HotkeyError::UnknownKey(*hotkey))?;
+        let modifiers = modifier_bits(hotkey);
+
+        for lock in lock_masks() {
+            let result = self
+                .connection
+                .grab_key(
+                    false,
+                    self.root,
+                    ModMask::from(modifiers | lock),
+                    keycode,
+                    GrabMode::ASYNC,
+                    GrabMode::ASYNC,
+                )
+                .map_err(ReplyError::from)
+                .and_then(|cookie| cookie.check());
+
+            if let Err(err) = result {
+                // 撤销这个热键已经抓取的组合
+                self.ungrab(keycode, modifiers);
+                return Err(match err {
+                    ReplyError::X11Error(err) if err.error_kind == ErrorKind::Access => {
+                        HotkeyError::Taken(*hotkey)
+                    }
+                    err => HotkeyError::Backend(err.to_string()),
+                });
+            }
+        }
+
+        self.grabs.push((*hotkey, keycode, modifiers));
+        Ok(())
+    }
+
+    fn unregister(&mut self, hotkey: &Hotkey) {
+        let Some(index) = self
+            .grabs
+            .iter()
+            .position(|(grabbed, ..)| grabbed == hotkey)
+        else {
+            return;
+        };
+        let (_, keycode, modifiers) = self.grabs.remove(index);
+        self.ungrab(keycode, modifiers);
+    }
+
+    fn wait(&mut self) -> Result<Hotkey, HotkeyError> {
+        loop {
+            let event = self
+                .connection
+                .wait_for_event()
+                .map_err(|err| HotkeyError::Backend(err.to_string()))?;
+            let Event::KeyPress(event) = event else {
+                continue;
+            };
+            let state = u16::from(event.state) & modifier_mask();
+            let grabbed = self
+                .grabs
+                .iter()
+                .find(|(_, keycode, modifiers)| *keycode == event.detail && *modifiers == state);
+            if let Some((hotkey, ..)) = grabbed {
+                return Ok(*hotkey);
+            }
+        }
+    }
+}
@@ -1,6 +1,22 @@
 pub mod constants;
+pub mod coords;
 pub mod utils;
 pub mod types;
 pub mod geometry;
 pub mod rendering;
-pub mod shapes;
\ No newline at end of file
+pub mod shapes;
+pub mod export;
+pub mod clipboard;
+pub mod history;
+pub mod capture;
+pub mod cli;
+pub mod editor;
+pub mod tray;
+pub mod hotkey;
+pub mod config;
+pub mod windows;
+pub mod snapping;
+pub mod picker;
+
+#[cfg(test)]
+mod test_support;
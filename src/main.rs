@@ -3,74 +3,260 @@
     windows_subsystem = "windows"
 )]
 
-use display_info::DisplayInfo;
 use freya::prelude::*;
 
 use freya_test::{
-    constants::constants::MIN_SELECTION_SIZE,
-    geometry::{constrain_to_screen, get_resize_anchor, get_resize_handle, point_in_rect},
-    rendering::{
-        selection::{draw_handles, draw_selection_area, draw_selection_border},
-        shapes::{draw_drawing_shape, draw_shape},
-        toolbar::draw_toolbar,
+    capture::{
+        CaptureError, DesktopCapture, FileCapture, VirtualDesktop, capture_desktop, default_source,
+        desktop_layout, load_image,
     },
-    types::{
-        app_state::{AppState, ResizeHandle},
-        drawing::{DrawingShape, DrawingTool},
-        ui::{Selection, Toolbar},
+    cli::{
+        CaptureMode, CliCommand, OutputTargets, USAGE, fixed_selection,
+        last_region::save_last_region, parse_args, write_outputs,
     },
-    utils::cursor::CursorManager,
-};
-use screenshots::Screen;
-use skia_safe::{
-    AlphaType, Color, ColorType, Data, Image as SkiaImage, ImageInfo, Paint, Rect, images,
+    clipboard::{SystemClipboard, copy_picked_color, copy_selection},
+    config::{
+        self, CaptureAction, Config, ConfigError, config_path, open_in_editor, watch::ConfigWatcher,
+    },
+    coords::{LogicalPoint, PhysicalSize, scale_to_physical},
+    editor::{Editor, EditorAction, EditorKey, EditorResponse, KeyModifiers, PointerButton},
+    hotkey::{
+        HotkeyAction, HotkeyBinding, HotkeyManager, default_backend, override_bindings,
+        with_defaults,
+    },
+    tray::{Tray, TrayCommand},
+    types::ui::Selection,
+    windows::detect_windows,
 };
+use skia_safe::{Color, Image as SkiaImage};
 
-use winit::window::WindowLevel;
+use winit::{
+    dpi::{LogicalPosition, LogicalSize as WindowLogicalSize},
+    window::{Window, WindowLevel},
+};
 
 fn main() {
-    let display_infos = DisplayInfo::all().unwrap();
-    let dpi_scale = display_infos.first().unwrap().scale_factor;
+    // 复制到剪贴板时启动的子进程，只负责持有剪贴板内容
+    #[cfg(target_os = "linux")]
+    if std::env::var_os(freya_test::clipboard::HANDOVER_ENV).is_some() {
+        freya_test::clipboard::serve_handover();
+        return;
+    }
+
+    let mut options = match parse_args(std::env::args_os().skip(1)) {
+        Ok(CliCommand::Run(options)) => options,
+        Ok(CliCommand::Help) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    // 配置文件有错误时直接退出，而不是悄悄使用默认值
+    let config = config::load().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(2);
+    });
+    options.outputs.location = config.output.clone();
+
+    if !options.delay.is_zero() {
+        std::thread::sleep(options.delay);
+    }
+
+    // 命令行传入图片路径时标注该图片，否则在显示窗口之前截图，避免截到遮罩本身
+    // 托盘模式启动时不截图，只需要虚拟桌面的布局
+    let capture = match &options.image {
+        Some(path) => FileCapture::open(path).and_then(|source| capture_desktop(&source)),
+        None if options.tray => default_source()
+            .and_then(|source| desktop_layout(source.as_ref()))
+            .and_then(|desktop| DesktopCapture::blank(desktop).ok_or(CaptureError::Image)),
+        None => default_source().and_then(|source| capture_desktop(source.as_ref())),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    // 非交互模式不显示遮罩，直接按固定区域合成并输出
+    match fixed_selection(&options.mode, &capture) {
+        Ok(None) => {}
+        Ok(Some(selection)) => {
+            match write_outputs(&options.outputs, &capture.image, &selection, &[]) {
+                Ok(path) => {
+                    if let Some(path) = path {
+                        eprintln!("截图已保存到 {}", path.display());
+                    }
+                    if let Err(err) = save_last_region(&selection) {
+                        eprintln!("无法记录截图区域: {err}");
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+    // 遮罩显示之前记录各窗口的位置，供单击选中窗口（打开图片和托盘模式不需要）
+    let windows = if options.image.is_none() && !options.tray {
+        detect_windows(&capture.desktop)
+    } else {
+        Vec::new()
+    };
+    let single_display = capture.desktop.displays.len() == 1;
+    let (origin, logical_size) = (capture.desktop.origin, capture.desktop.size);
+    let resident = options.tray;
 
     launch_cfg(
         app,
-        LaunchConfig::<f32>::new()
+        LaunchConfig::<LaunchState>::new()
             .with_decorations(false)
-            .with_state(dpi_scale)
+            .with_state(LaunchState {
+                capture,
+                open_image: options.image.is_some(),
+                outputs: options.outputs,
+                resident,
+                hotkeys: options.hotkeys,
+                config,
+                windows,
+            })
             .with_transparency(false)
-            .with_window_attributes(|x| {
-                x.with_fullscreen(Some(Fullscreen::Borderless(None)))
-                    .with_resizable(false)
+            .with_window_attributes(move |x| {
+                if resident {
+                    // 托盘模式下窗口先隐藏，截图时再铺满桌面并显示
+                    x.with_visible(false).with_resizable(false)
+                } else if single_display {
+                    x.with_fullscreen(Some(Fullscreen::Borderless(None)))
+                        .with_resizable(false)
+                } else {
+                    // 多显示器时窗口铺满整个虚拟桌面
+                    x.with_position(LogicalPosition::new(origin.x, origin.y))
+                        .with_inner_size(WindowLogicalSize::new(
+                            logical_size.width,
+                            logical_size.height,
+                        ))
+                        .with_resizable(false)
+                }
                 // .with_window_level(WindowLevel::AlwaysOnTop)
             }),
     );
 }
 
-// 在 app 函数中添加光标状态管理
+// 启动时的截图，open_image 表示标注的是打开的图片而不是屏幕截图
+// resident 表示常驻托盘，截图完成后隐藏窗口而不退出，hotkeys 为命令行覆盖的热键
+#[derive(Clone)]
+struct LaunchState {
+    capture: DesktopCapture,
+    open_image: bool,
+    outputs: OutputTargets,
+    resident: bool,
+    hotkeys: Vec<HotkeyBinding>,
+    config: Config,
+    // 截图时顶层窗口在截图上的区域
+    windows: Vec<Selection>,
+}
+
+// 托盘模式下从其他线程发到界面任务的命令
+#[derive(Debug)]
+enum BackgroundCommand {
+    Tray(TrayCommand),
+    Hotkey(HotkeyAction),
+    // 配置文件有变化，重新读取的结果
+    ConfigChanged(Result<Config, ConfigError>),
+}
+
+// 将遮罩窗口铺满虚拟桌面并显示
+fn show_window(window: &Window, desktop: &VirtualDesktop) {
+    if desktop.displays.len() == 1 {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    } else {
+        window.set_fullscreen(None);
+        window.set_outer_position(LogicalPosition::new(desktop.origin.x, desktop.origin.y));
+        let _ = window.request_inner_size(WindowLogicalSize::new(
+            desktop.size.width,
+            desktop.size.height,
+        ));
+    }
+    window.set_visible(true);
+    window.focus_window();
+}
+
+// freya 的按键转为编辑器按键
+fn editor_key(key: &Key) -> EditorKey {
+    match key {
+        Key::Escape => EditorKey::Escape,
+        Key::Enter => EditorKey::Enter,
+        Key::Backspace => EditorKey::Backspace,
+        Key::Delete => EditorKey::Delete,
+        Key::ArrowLeft => EditorKey::ArrowLeft,
+        Key::ArrowRight => EditorKey::ArrowRight,
+        Key::ArrowUp => EditorKey::ArrowUp,
+        Key::ArrowDown => EditorKey::ArrowDown,
+        Key::Home => EditorKey::Home,
+        Key::End => EditorKey::End,
+        Key::Character(text) => EditorKey::Character(text.clone()),
+        _ => EditorKey::Other,
+    }
+}
+
+// 按键事件之后的修饰键状态；修饰键自身的按下和松开可能还没有反映在 modifiers 中
+fn key_modifiers(e: &KeyboardEvent, pressed: bool) -> KeyModifiers {
+    let mut modifiers = KeyModifiers {
+        ctrl: e.modifiers.ctrl(),
+        shift: e.modifiers.shift(),
+        alt: e.modifiers.alt(),
+    };
+    match e.key {
+        Key::Control => modifiers.ctrl = pressed,
+        Key::Shift => modifiers.shift = pressed,
+        Key::Alt => modifiers.alt = pressed,
+        _ => {}
+    }
+    modifiers
+}
+
 fn app() -> Element {
     let platform = use_platform();
-    let dpi_scale = consume_context::<f32>();
-
-    // 状态管理（保持原有的）
-    let mut screenshot_image = use_signal::<Option<SkiaImage>>(|| None);
-    let mut screen_size = use_signal(|| (0u32, 0u32));
-    let mut mouse_pos = use_signal(|| (0.0f32, 0.0f32));
-    let mut app_state = use_signal(|| AppState::Idle);
-    let mut current_selection = use_signal::<Option<Selection>>(|| None);
-    let mut drag_offset = use_signal::<Option<(f32, f32)>>(|| None);
-    let mut resize_handle = use_signal::<Option<ResizeHandle>>(|| None);
-    let mut resize_anchor = use_signal::<Option<(f32, f32)>>(|| None);
-    let mut temp_selection = use_signal::<Option<Selection>>(|| None);
-    let mut last_cursor = use_signal(|| CursorIcon::Default);
-
-    // 新增绘图状态
-    let mut current_tool = use_signal(|| DrawingTool::None);
-    let mut drawing_shapes = use_signal::<Vec<DrawingShape>>(|| Vec::new());
-    let mut current_drawing = use_signal::<Option<DrawingShape>>(|| None);
-    let mut selected_shape_index = use_signal::<Option<usize>>(|| None);
-    let mut shape_drag_offset = use_signal::<Option<(f32, f32)>>(|| None);
-    let mut shape_resize_handle = use_signal::<Option<ResizeHandle>>(|| None);
-    let mut shape_resize_anchor = use_signal::<Option<(f32, f32)>>(|| None);
+    let LaunchState {
+        capture,
+        open_image,
+        outputs,
+        resident,
+        hotkeys,
+        config,
+        windows,
+    } = consume_context::<LaunchState>();
+    // 保存时写到命令行指定的输出目标
+    let mut outputs = use_signal(|| outputs);
+    // 托盘模式下配置文件修改后替换，下一次截图生效
+    let mut config = use_signal(|| config);
+    // 画布坐标为虚拟桌面的物理像素，缩放比例取所有显示器中的最大值
+    let mut dpi_scale = use_signal(|| capture.desktop.scale);
+    // 当前标注的是打开的图片而不是屏幕截图
+    let mut opened_image = use_signal(|| open_image);
+    let mut overlay_visible = use_signal(|| !resident);
+    let mut tray = use_hook(|| CopyValue::new(None::<Tray>));
+
+    // 编辑器状态不直接触发重绘，由事件处理结果决定是否重绘和切换光标
+    let mut editor = use_hook(|| {
+        // 打开图片时选择框一开始就覆盖整张图片
+        let selection =
+            open_image.then(|| Selection::covering(PhysicalSize::of_image(&capture.image)));
+        let mut editor = Editor::new(capture.image.clone(), selection, &config.peek().editor);
+        editor.set_windows(windows);
+        editor.set_ui_scale(capture.desktop.scale);
+        CopyValue::new(editor)
+    });
+    let mut frame = use_signal(|| 0u64);
+    let mut cursor = use_signal(|| CursorIcon::Default);
 
     let (reference, size) = use_node_signal();
 
@@ -80,589 +266,381 @@ fn app() -> Element {
             w.set_cursor_visible(true);
             w.focus_window();
         });
+    });
 
-        spawn(async move {
-            if let Ok(screens) = Screen::all() {
-                if let Some(screen) = screens.first() {
-                    if let Ok(image) = screen.capture() {
-                        let width = image.width();
-                        let height = image.height();
-                        let data = image.into_raw();
-
-                        screen_size.set((width, height));
-
-                        let image_info = ImageInfo::new(
-                            (width as i32, height as i32),
-                            ColorType::RGBA8888,
-                            AlphaType::Unpremul,
-                            None,
-                        );
-
-                        if let Some(skia_img) = images::raster_from_data(
-                            &image_info,
-                            Data::new_copy(&data),
-                            (width * 4) as usize,
-                        ) {
-                            screenshot_image.set(Some(skia_img));
-                        }
+    // 记录本次截图区域，供 last-region 模式使用（打开的图片不记录）
+    let remember_region = move |selection: Selection| {
+        if *opened_image.peek() {
+            return;
+        }
+        if let Err(err) = save_last_region(&selection) {
+            eprintln!("无法记录截图区域: {err}");
+        }
+    };
+
+    // 结束本次截图：托盘模式下隐藏窗口等待下一次截图，否则退出
+    let mut dismiss = move || {
+        if resident {
+            platform.with_window(|w| w.set_visible(false));
+            overlay_visible.set(false);
+        } else {
+            platform.exit();
+        }
+    };
+
+    // 每次截图都新建编辑器，上一次的选择框、标注和历史不会残留
+    // 截取屏幕时在窗口显示之前记录各窗口的位置
+    let mut show_overlay = move |desktop: VirtualDesktop,
+                                 image: SkiaImage,
+                                 selection: Option<Selection>,
+                                 opened: bool| {
+        let mut new_editor = Editor::new(image, selection, &config.peek().editor);
+        new_editor.set_ui_scale(desktop.scale);
+        if !opened {
+            new_editor.set_windows(detect_windows(&desktop));
+        }
+        *editor.write() = new_editor;
+        dpi_scale.set(desktop.scale);
+        opened_image.set(opened);
+        overlay_visible.set(true);
+        cursor.set(CursorIcon::Default);
+        frame += 1;
+        platform.with_window(move |w| show_window(w, &desktop));
+    };
+
+    // 将选择区域连同标注按原始分辨率保存
+    let save_capture = move |selection: &Selection| -> bool {
+        let editor = editor.read();
+        match write_outputs(
+            &outputs.read(),
+            editor.screenshot(),
+            selection,
+            editor.shapes(),
+        ) {
+            Ok(path) => {
+                // 标准输出可能是 PNG 数据，状态信息写到标准错误
+                if let Some(path) = path {
+                    eprintln!("截图已保存到 {}", path.display());
+                    if let Some(tray) = &*tray.read() {
+                        tray.add_history(&path);
                     }
                 }
+                true
             }
+            Err(err) => {
+                eprintln!("保存截图失败: {err}");
+                false
+            }
+        }
+    };
+
+    // 将合成后的选择区域复制到系统剪贴板
+    let copy_capture = move |selection: &Selection| -> bool {
+        let editor = editor.read();
+        let result = SystemClipboard::new().and_then(|mut clipboard| {
+            copy_selection(
+                &mut clipboard,
+                editor.screenshot(),
+                selection,
+                editor.shapes(),
+            )
         });
-    });
+        match result {
+            Ok(()) => {
+                eprintln!("截图已复制到剪贴板");
+                true
+            }
+            Err(err) => {
+                eprintln!("复制截图失败: {err}");
+                false
+            }
+        }
+    };
 
-    // 同时修改鼠标按下事件处理，确保图形调整手柄优先级正确
-    let handle_mouse_down = move |e: MouseEvent| {
-        if e.trigger_button == Some(MouseButton::Right) {
-            platform.exit();
+    // 将取色器取到的颜色按当前格式复制到系统剪贴板
+    let copy_color = move || {
+        let result = SystemClipboard::new()
+            .and_then(|mut clipboard| copy_picked_color(&mut clipboard, &editor.read()));
+        match result {
+            Ok(Some(text)) => eprintln!("已复制颜色 {text}"),
+            Ok(None) => {}
+            Err(err) => eprintln!("复制颜色失败: {err}"),
+        }
+    };
+
+    // 依次执行保存、复制等动作，全部成功后按配置结束本次截图
+    let mut finish_capture = move |actions: &[CaptureAction]| {
+        let Some(selection) = editor.read().selection() else {
+            return;
+        };
+        let succeeded = actions.iter().all(|action| match action {
+            CaptureAction::Save => save_capture(&selection),
+            CaptureAction::Copy => copy_capture(&selection),
+        });
+        if !succeeded {
             return;
         }
+        remember_region(selection);
+        if config.peek().after_capture.close {
+            dismiss();
+        }
+    };
+
+    // 按编辑器的处理结果重绘、切换光标并完成保存、复制或退出
+    let mut handle_response = move |response: EditorResponse| {
+        if response.redraw {
+            frame += 1;
+        }
+        if *cursor.peek() != response.cursor {
+            cursor.set(response.cursor);
+        }
+        match response.action {
+            Some(EditorAction::Save) => finish_capture(&[CaptureAction::Save]),
+            Some(EditorAction::Copy) => finish_capture(&[CaptureAction::Copy]),
+            Some(EditorAction::Confirm) => {
+                // 命令行指定了输出目标时 Enter 写到这些目标
+                let actions = outputs
+                    .peek()
+                    .confirm_actions(&config.peek().after_capture.confirm);
+                finish_capture(&actions);
+            }
+            Some(EditorAction::CopyColor) => copy_color(),
+            Some(EditorAction::Exit) => dismiss(),
+            None => {}
+        }
+    };
 
+    let pointer_position = move |e: &MouseEvent| -> (f32, f32) {
         let coords = e.get_element_coordinates();
-        let pos = (coords.x as f32 * dpi_scale, coords.y as f32 * dpi_scale);
+        LogicalPoint::new(coords.x as f32, coords.y as f32)
+            .to_physical(*dpi_scale.peek())
+            .into()
+    };
 
-        let current_sel = *current_selection.read();
+    // 拖入图片：替换当前截图并清空所有标注，选择框覆盖整张图片
+    let handle_file_drop = move |e: FileEvent| {
+        let Some(path) = e.file_path.clone() else {
+            return;
+        };
+        match load_image(&path) {
+            Ok(image) => {
+                editor.write().open_image(image);
+                opened_image.set(true);
+                frame += 1;
+                eprintln!("已打开图片 {}", path.display());
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+    };
 
-        if let Some(selection) = current_sel {
-            let toolbar = Toolbar::calculate(&selection, *screen_size.read());
+    let handle_mouse_down = move |e: MouseEvent| {
+        let button = match e.trigger_button {
+            Some(MouseButton::Right) => PointerButton::Secondary,
+            _ => PointerButton::Primary,
+        };
+        let response = editor.write().pointer_down(pointer_position(&e), button);
+        handle_response(response);
+    };
 
-            // 1. 检查工具栏按钮点击
-            if let Some(button_index) = toolbar.get_button_index(pos.0, pos.1) {
-                match button_index {
-                    0 => {
-                        println!("画框工具");
-                        current_tool.set(DrawingTool::Rectangle);
-                        selected_shape_index.set(None);
-                    }
-                    1 => {
-                        println!("画圆工具");
-                        current_tool.set(DrawingTool::Circle);
-                        selected_shape_index.set(None);
-                    }
-                    2 => {
-                        println!("画箭头工具");
-                        current_tool.set(DrawingTool::Arrow);
-                        selected_shape_index.set(None);
-                    }
-                    3 => {
-                        println!("自由画笔工具");
-                        current_tool.set(DrawingTool::Brush);
-                        selected_shape_index.set(None);
-                    }
-                    4 => {
-                        platform.exit();
-                        app_state.set(AppState::Idle);
-                    }
-                    _ => {}
-                }
-                return;
-            }
+    let handle_mouse_move = move |e: MouseEvent| {
+        let response = editor.write().pointer_move(pointer_position(&e));
+        handle_response(response);
+    };
 
-            // 🔧 修复：检查是否有绘图工具选中
-            let tool = *current_tool.read();
+    let handle_mouse_up = move |e: MouseEvent| {
+        let response = editor.write().pointer_up(pointer_position(&e));
+        handle_response(response);
+    };
 
-            // 2. 只有在没有绘图工具时才检查选择框的调整大小手柄
-            if tool == DrawingTool::None {
-                if let Some(handle) = get_resize_handle(pos.0, pos.1, &selection) {
-                    app_state.set(AppState::Resizing);
-                    resize_handle.set(Some(handle));
-                    resize_anchor.set(Some(get_resize_anchor(handle, &selection)));
-                    return;
-                }
-            }
+    let handle_key_down = move |e: KeyboardEvent| {
+        let modifiers = key_modifiers(&e, true);
+        let response = editor.write().set_modifiers(modifiers);
+        handle_response(response);
+        let response = editor.write().key(&editor_key(&e.key), modifiers);
+        handle_response(response);
+    };
 
-            // 3. 检查是否点击了选择框内部
-            if point_in_rect(pos.0, pos.1, &selection) {
-                // 绝对优先检查选中图形的调整手柄
-                if let Some(selected_idx) = *selected_shape_index.read() {
-                    let shapes = drawing_shapes.read();
-                    if let Some(shape) = shapes.get(selected_idx) {
-                        if let Some(handle) = shape.get_resize_handle(pos.0, pos.1) {
-                            app_state.set(AppState::ResizingShape);
-                            shape_resize_handle.set(Some(handle));
-                            shape_resize_anchor.set(Some(shape.get_resize_anchor(handle)));
-                            return;
-                        }
-                    }
-                    drop(shapes);
-                }
+    let handle_key_up = move |e: KeyboardEvent| {
+        let response = editor.write().set_modifiers(key_modifiers(&e, false));
+        handle_response(response);
+    };
 
-                // 然后检查是否点击了任何图形的调整手柄（不管是否选中）
-                let shapes = drawing_shapes.read();
-                for (i, shape) in shapes.iter().enumerate().rev() {
-                    if let Some(handle) = shape.get_resize_handle(pos.0, pos.1) {
-                        selected_shape_index.set(Some(i));
-                        app_state.set(AppState::ResizingShape);
-                        shape_resize_handle.set(Some(handle));
-                        shape_resize_anchor.set(Some(shape.get_resize_anchor(handle)));
-                        return;
+    // 不显示遮罩的截图：按模式确定区域后直接输出并加入历史记录
+    let write_fixed = move |mode: &CaptureMode, capture: &DesktopCapture| {
+        let selection = match fixed_selection(mode, capture) {
+            Ok(Some(selection)) => selection,
+            Ok(None) => return,
+            Err(err) => {
+                eprintln!("{err}");
+                return;
+            }
+        };
+        match write_outputs(&outputs.read(), &capture.image, &selection, &[]) {
+            Ok(path) => {
+                if let Some(path) = path {
+                    eprintln!("截图已保存到 {}", path.display());
+                    if let Some(tray) = &*tray.read() {
+                        tray.add_history(&path);
                     }
                 }
-                drop(shapes);
-
-                // 如果有绘图工具选中
-                if tool != DrawingTool::None {
-                    // 检查是否点击了已有的图形本身（不是调整手柄）
-                    let shapes = drawing_shapes.read();
-                    for (i, shape) in shapes.iter().enumerate().rev() {
-                        if shape.contains_point(pos.0, pos.1) {
-                            drop(shapes);
-                            // 点击了图形本身，进入编辑模式
-                            selected_shape_index.set(Some(i));
-                            app_state.set(AppState::EditingShape);
-                            let shapes = drawing_shapes.read();
-                            if let Some(shape) = shapes.get(i) {
-                                let (left, top, _, _) = shape.bounds();
-                                shape_drag_offset.set(Some((pos.0 - left, pos.1 - top)));
-                            }
-                            return;
-                        }
-                    }
-                    drop(shapes);
-
-                    // 如果没有点击到图形，开始新的绘制
-                    app_state.set(AppState::Drawing);
-                    let default_color = Color::from_rgb(255, 0, 0);
-                    let default_stroke = 1.0;
-
-                    let new_shape = match tool {
-                        DrawingTool::Rectangle => DrawingShape::Rectangle {
-                            start: pos,
-                            end: pos,
-                            color: default_color,
-                            stroke_width: default_stroke,
-                        },
-                        DrawingTool::Circle => DrawingShape::Circle {
-                            center: pos,
-                            radius: 0.0,
-                            color: default_color,
-                            stroke_width: default_stroke,
-                        },
-                        DrawingTool::Arrow => DrawingShape::Arrow {
-                            start: pos,
-                            end: pos,
-                            color: default_color,
-                            stroke_width: default_stroke,
-                        },
-                        DrawingTool::Brush => DrawingShape::BrushStroke {
-                            points: vec![pos],
-                            color: default_color,
-                            stroke_width: default_stroke,
-                        },
-                        DrawingTool::None => return,
-                    };
-
-                    current_drawing.set(Some(new_shape));
-                    return;
-                } else {
-                    // 没有绘图工具选中，检查是否点击了图形本身
-                    let shapes = drawing_shapes.read();
-                    for (i, shape) in shapes.iter().enumerate().rev() {
-                        if shape.contains_point(pos.0, pos.1) {
-                            drop(shapes);
-                            // 点击了图形本身，进入编辑模式
-                            selected_shape_index.set(Some(i));
-                            app_state.set(AppState::EditingShape);
-                            let shapes = drawing_shapes.read();
-                            if let Some(shape) = shapes.get(i) {
-                                let (left, top, _, _) = shape.bounds();
-                                shape_drag_offset.set(Some((pos.0 - left, pos.1 - top)));
-                            }
-                            return;
-                        }
-                    }
-                    drop(shapes);
-
-                    // 没有点击到图形，开始拖拽选择框
-                    app_state.set(AppState::Dragging);
-                    let (left, top, _, _) = selection.bounds();
-                    drag_offset.set(Some((pos.0 - left, pos.1 - top)));
-                    return;
+                if let Err(err) = save_last_region(&selection) {
+                    eprintln!("无法记录截图区域: {err}");
                 }
-            } else {
-                // 点击在选择框外部 - 直接忽略，不做任何操作
-                return;
             }
-        } else {
-            // 没有选择框时，允许新建选择
-            app_state.set(AppState::Selecting);
-            temp_selection.set(Some(Selection {
-                start: pos,
-                end: pos,
-            }));
-            current_selection.set(None);
-            current_tool.set(DrawingTool::None);
-            selected_shape_index.set(None);
+            Err(err) => eprintln!("保存截图失败: {err}"),
         }
     };
 
-    // 鼠标移动和释放事件处理（保持你重构后的代码）
+    // 打开配置文件，不存在时先写入带注释的默认配置
+    let open_settings = || {
+        let result = config::ensure_config_file()
+            .and_then(|path| open_in_editor(&path).map_err(|err| ConfigError::Io(path, err)));
+        if let Err(err) = result {
+            eprintln!("{err}");
+        }
+    };
 
-    let handle_mouse_move = move |e: MouseEvent| {
-        let coords = e.get_element_coordinates();
-        let pos = (coords.x as f32 * dpi_scale, coords.y as f32 * dpi_scale);
-        mouse_pos.set(pos);
-        let current_state = *app_state.read();
-
-        match current_state {
-            AppState::Drawing => {
-                if let Some(selection) = *current_selection.read() {
-                    let selection_bounds = selection.bounds();
-
-                    // 限制绘制位置在选择区域内
-                    let constrained_pos = (
-                        pos.0.max(selection_bounds.0).min(selection_bounds.2),
-                        pos.1.max(selection_bounds.1).min(selection_bounds.3),
-                    );
-
-                    // 🔧 优化1：避免频繁克隆，使用 with_mut
-                    current_drawing.with_mut(|current_shape_opt| {
-                        if let Some(shape) = current_shape_opt {
-                            match shape {
-                                DrawingShape::Rectangle { end, .. }
-                                | DrawingShape::Arrow { end, .. } => {
-                                    *end = constrained_pos;
-                                }
-                                DrawingShape::Circle { center, radius, .. } => {
-                                    let dx = constrained_pos.0 - center.0;
-                                    let dy = constrained_pos.1 - center.1;
-                                    let new_radius = (dx * dx + dy * dy).sqrt();
-
-                                    // 限制圆不超出选择区域
-                                    let max_radius = (center.0 - selection_bounds.0)
-                                        .min(selection_bounds.2 - center.0)
-                                        .min(center.1 - selection_bounds.1)
-                                        .min(selection_bounds.3 - center.1);
-
-                                    *radius = new_radius.min(max_radius);
-                                }
-                                DrawingShape::BrushStroke { points, .. } => {
-                                    // 🔧 优化2：画笔优化 - 减少点的数量和频率
-                                    if point_in_rect(pos.0, pos.1, &selection) {
-                                        // 只有在距离上一个点足够远时才添加新点
-                                        let should_add_point =
-                                            if let Some(last_point) = points.last() {
-                                                let dx = pos.0 - last_point.0;
-                                                let dy = pos.1 - last_point.1;
-                                                let distance = (dx * dx + dy * dy).sqrt();
-                                                distance > 2.0 // 最小距离阈值
-                                            } else {
-                                                true
-                                            };
-
-                                        if should_add_point {
-                                            points.push(pos);
-
-                                            // 🔧 优化3：限制点的总数，防止内存无限增长
-                                            const MAX_BRUSH_POINTS: usize = 1000;
-                                            if points.len() > MAX_BRUSH_POINTS {
-                                                // 移除最早的一些点，保持流畅度
-                                                points.drain(0..100);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    });
-                }
+    // 配置文件修改后替换配置，下一次截图生效；有错误时继续使用之前的配置
+    let mut reload_config = move |result: Result<Config, ConfigError>| match result {
+        Ok(new_config) => {
+            if new_config.hotkeys != config.peek().hotkeys {
+                eprintln!("热键的修改在重新启动后生效");
             }
-            AppState::ResizingShape => {
-                if let (Some(index), Some(handle), Some(anchor), Some(selection)) = (
-                    *selected_shape_index.read(),
-                    *shape_resize_handle.read(),
-                    *shape_resize_anchor.read(),
-                    *current_selection.read(),
-                ) {
-                    let mut shapes = drawing_shapes.read().clone();
-                    if let Some(shape) = shapes.get_mut(index) {
-                        let selection_bounds = selection.bounds();
-
-                        // 限制鼠标位置在选择区域内
-                        let constrained_pos = (
-                            pos.0.max(selection_bounds.0).min(selection_bounds.2),
-                            pos.1.max(selection_bounds.1).min(selection_bounds.3),
-                        );
-
-                        let (left, top, right, bottom) = shape.bounds();
-
-                        // 根据不同的手柄计算新的边界
-                        let new_bounds = match handle {
-                            ResizeHandle::TopLeft => {
-                                (constrained_pos.0, constrained_pos.1, anchor.0, anchor.1)
-                            }
-                            ResizeHandle::TopRight => {
-                                (anchor.0, constrained_pos.1, constrained_pos.0, anchor.1)
-                            }
-                            ResizeHandle::BottomRight => {
-                                (anchor.0, anchor.1, constrained_pos.0, constrained_pos.1)
-                            }
-                            ResizeHandle::BottomLeft => {
-                                (constrained_pos.0, anchor.1, anchor.0, constrained_pos.1)
-                            }
-                            ResizeHandle::Top => (left, constrained_pos.1, right, anchor.1),
-                            ResizeHandle::Bottom => (left, anchor.1, right, constrained_pos.1),
-                            ResizeHandle::Left => (constrained_pos.0, top, anchor.0, bottom),
-                            ResizeHandle::Right => (anchor.0, top, constrained_pos.0, bottom),
-                        };
-
-                        // 确保新边界是有效的（左小于右，上小于下）
-                        let (mut new_left, mut new_top, mut new_right, mut new_bottom) = new_bounds;
-
-                        if new_left > new_right {
-                            std::mem::swap(&mut new_left, &mut new_right);
-                        }
-                        if new_top > new_bottom {
-                            std::mem::swap(&mut new_top, &mut new_bottom);
-                        }
-
-                        // 确保最小尺寸
-                        let min_size = 10.0;
-                        if new_right - new_left < min_size {
-                            if handle == ResizeHandle::Left
-                                || handle == ResizeHandle::TopLeft
-                                || handle == ResizeHandle::BottomLeft
-                            {
-                                new_left = new_right - min_size;
-                            } else {
-                                new_right = new_left + min_size;
-                            }
-                        }
-                        if new_bottom - new_top < min_size {
-                            if handle == ResizeHandle::Top
-                                || handle == ResizeHandle::TopLeft
-                                || handle == ResizeHandle::TopRight
-                            {
-                                new_top = new_bottom - min_size;
-                            } else {
-                                new_bottom = new_top + min_size;
-                            }
-                        }
-
-                        shape.resize_constrained(
-                            (new_left, new_top, new_right, new_bottom),
-                            selection_bounds,
-                        );
-                        drawing_shapes.set(shapes);
-                    }
-                }
+            outputs.write().location = new_config.output.clone();
+            config.set(new_config);
+            eprintln!("已重新加载配置");
+        }
+        Err(err) => eprintln!("{err}，继续使用之前的配置"),
+    };
+
+    // 托盘菜单、全局热键和配置文件变化的命令；遮罩显示期间忽略新的截图请求
+    let mut handle_command = move |command: BackgroundCommand| {
+        let command = match command {
+            BackgroundCommand::Tray(TrayCommand::Quit) => {
+                platform.exit();
+                return;
             }
-            AppState::EditingShape => {
-                let selected_idx = *selected_shape_index.read();
-                let offset = *shape_drag_offset.read();
-
-                if let (Some(index), Some(offset), Some(selection)) =
-                    (selected_idx, offset, *current_selection.read())
-                {
-                    drawing_shapes.with_mut(|shapes| {
-                        if let Some(shape) = shapes.get_mut(index) {
-                            let new_x = pos.0 - offset.0;
-                            let new_y = pos.1 - offset.1;
-                            let (old_left, old_top, _, _) = shape.bounds();
-                            let dx = new_x - old_left;
-                            let dy = new_y - old_top;
-
-                            // 🔧 优化2：只有当移动距离足够大时才更新
-                            if dx.abs() > 0.5 || dy.abs() > 0.5 {
-                                shape.translate(dx, dy);
-                                // 限制在选择区域内
-                                shape.constrain_to_selection(selection.bounds());
-                            }
-                        }
-                    });
-                }
+            BackgroundCommand::Tray(TrayCommand::OpenSettings) => {
+                open_settings();
+                return;
             }
-            AppState::Selecting => {
-                let temp_sel = *temp_selection.read();
-                if let Some(mut selection) = temp_sel {
-                    selection.end = pos;
-                    temp_selection.set(Some(selection));
-                }
+            BackgroundCommand::ConfigChanged(result) => {
+                reload_config(result);
+                return;
             }
-            AppState::Dragging => {
-                let selection_opt = *current_selection.read();
-                let offset_opt = *drag_offset.read();
-                let screen_sz = *screen_size.read();
-
-                if let (Some(selection), Some(offset)) = (selection_opt, offset_opt) {
-                    let new_selection = Selection {
-                        start: (pos.0 - offset.0, pos.1 - offset.1),
-                        end: (
-                            pos.0 - offset.0 + selection.size().0,
-                            pos.1 - offset.1 + selection.size().1,
-                        ),
-                    };
-
-                    current_selection.set(Some(constrain_to_screen(new_selection, screen_sz)));
-                }
+            command => command,
+        };
+        if *overlay_visible.peek() {
+            return;
+        }
+
+        let mode = match &command {
+            BackgroundCommand::Tray(TrayCommand::CaptureFullscreen) => CaptureMode::Fullscreen,
+            BackgroundCommand::Hotkey(action) => action.capture_mode(),
+            _ => CaptureMode::Region,
+        };
+        let result = default_source().and_then(|source| match &command {
+            BackgroundCommand::Tray(TrayCommand::OpenImage(Some(path))) => {
+                let desktop = desktop_layout(source.as_ref())?;
+                Ok(DesktopCapture {
+                    desktop,
+                    image: load_image(path)?,
+                })
             }
-            AppState::Resizing => {
-                let handle_opt = *resize_handle.read();
-                let anchor_opt = *resize_anchor.read();
-                let selection_opt = *current_selection.read();
-                let screen_sz = *screen_size.read();
-
-                if let (Some(handle), Some(anchor), Some(selection)) =
-                    (handle_opt, anchor_opt, selection_opt)
-                {
-                    let screen_width = screen_sz.0 as f32;
-                    let screen_height = screen_sz.1 as f32;
-
-                    let constrained_x = pos.0.max(0.0).min(screen_width);
-                    let constrained_y = pos.1.max(0.0).min(screen_height);
-
-                    let (left, top, right, bottom) = selection.bounds();
-
-                    let new_selection = match handle {
-                        ResizeHandle::TopLeft => Selection {
-                            start: (
-                                constrained_x.min(anchor.0 - MIN_SELECTION_SIZE),
-                                constrained_y.min(anchor.1 - MIN_SELECTION_SIZE),
-                            ),
-                            end: anchor,
-                        },
-                        ResizeHandle::TopRight => Selection {
-                            start: (anchor.0, constrained_y.min(anchor.1 - MIN_SELECTION_SIZE)),
-                            end: (constrained_x.max(anchor.0 + MIN_SELECTION_SIZE), anchor.1),
-                        },
-                        ResizeHandle::BottomRight => Selection {
-                            start: anchor,
-                            end: (
-                                constrained_x.max(anchor.0 + MIN_SELECTION_SIZE),
-                                constrained_y.max(anchor.1 + MIN_SELECTION_SIZE),
-                            ),
-                        },
-                        ResizeHandle::BottomLeft => Selection {
-                            start: (constrained_x.min(anchor.0 - MIN_SELECTION_SIZE), anchor.1),
-                            end: (anchor.0, constrained_y.max(anchor.1 + MIN_SELECTION_SIZE)),
-                        },
-                        ResizeHandle::Top => Selection {
-                            start: (left, constrained_y.min(bottom - MIN_SELECTION_SIZE)),
-                            end: (right, bottom),
-                        },
-                        ResizeHandle::Bottom => Selection {
-                            start: (left, top),
-                            end: (right, constrained_y.max(top + MIN_SELECTION_SIZE)),
-                        },
-                        ResizeHandle::Left => Selection {
-                            start: (constrained_x.min(right - MIN_SELECTION_SIZE), top),
-                            end: (right, bottom),
-                        },
-                        ResizeHandle::Right => Selection {
-                            start: (left, top),
-                            end: (constrained_x.max(left + MIN_SELECTION_SIZE), bottom),
-                        },
-                    };
-
-                    current_selection.set(Some(constrain_to_screen(new_selection, screen_sz)));
+            _ => capture_desktop(source.as_ref()),
+        });
+        let capture = match result {
+            Ok(capture) => capture,
+            Err(err) => {
+                eprintln!("{err}");
+                return;
+            }
+        };
+
+        match command {
+            BackgroundCommand::Tray(TrayCommand::OpenImage(path)) => {
+                // 没有文件对话框时显示当前屏幕，把图片拖进遮罩即可打开
+                let opened = path.is_some();
+                let selection =
+                    opened.then(|| Selection::covering(PhysicalSize::of_image(&capture.image)));
+                if !opened {
+                    eprintln!("将图片拖入窗口进行标注");
                 }
+                show_overlay(capture.desktop, capture.image, selection, opened);
             }
-            _ => {}
+            _ if mode.is_interactive() => show_overlay(capture.desktop, capture.image, None, false),
+            _ => write_fixed(&mode, &capture),
         }
     };
 
-    // 修改鼠标释放处理
-    let handle_mouse_up = move |_: MouseEvent| {
-        let current_state = *app_state.read();
-
-        match current_state {
-            AppState::Drawing => {
-                let current_shape = current_drawing.read().clone();
-                if let Some(shape) = current_shape {
-                    let mut shapes = drawing_shapes.read().clone();
-                    shapes.push(shape);
-                    drawing_shapes.set(shapes);
-                    current_drawing.set(None);
-                }
-                app_state.set(AppState::Idle);
-            }
-            AppState::ResizingShape => {
-                shape_resize_handle.set(None);
-                shape_resize_anchor.set(None);
-                app_state.set(AppState::Idle);
+    // 托盘模式：菜单事件在托盘线程上、热键和配置文件变化在各自的线程上发出，经通道转到界面任务中处理
+    use_hook(move || {
+        if !resident {
+            return;
+        }
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let tray_sender = sender.clone();
+        match Tray::new(move |command| {
+            let _ = tray_sender.send(BackgroundCommand::Tray(command));
+        }) {
+            Ok(handle) => tray.set(Some(handle)),
+            Err(err) => {
+                eprintln!("{err}");
+                platform.exit();
+                return;
             }
+        }
 
-            AppState::EditingShape => {
-                shape_drag_offset.set(None);
-                app_state.set(AppState::Idle);
-            }
-            AppState::Selecting => {
-                let temp_sel = *temp_selection.read();
-                if let Some(selection) = temp_sel {
-                    current_selection.set(Some(selection));
+        if let Some(path) = config_path() {
+            let config_sender = sender.clone();
+            ConfigWatcher::new(path).spawn(move |result| {
+                let _ = config_sender.send(BackgroundCommand::ConfigChanged(result));
+            });
+        }
+
+        // 热键不可用或被占用时只提示，托盘菜单仍然可以截图
+        // 命令行的热键优先于配置文件，两者都没有指定的动作使用默认热键
+        match default_backend() {
+            Ok(backend) => {
+                let bindings = override_bindings(config.peek().hotkeys.clone(), &hotkeys);
+                let mut manager = HotkeyManager::new(backend);
+                for err in manager.register(&with_defaults(&bindings)) {
+                    eprintln!("{err}");
                 }
-                temp_selection.set(None);
-                app_state.set(AppState::Idle);
-            }
-            AppState::Dragging => {
-                drag_offset.set(None);
-                app_state.set(AppState::Idle);
+                manager.spawn(move |action| {
+                    let _ = sender.send(BackgroundCommand::Hotkey(action));
+                });
             }
-            AppState::Resizing => {
-                resize_handle.set(None);
-                resize_anchor.set(None);
-                app_state.set(AppState::Idle);
-            }
-            _ => {}
+            Err(err) => eprintln!("全局热键不可用: {err}"),
         }
-    };
 
-    // 修改 canvas 部分
+        spawn(async move {
+            while let Some(command) = receiver.recv().await {
+                handle_command(command);
+            }
+        });
+    });
+
     let canvas = use_canvas(move || {
         platform.invalidate_drawing_area(size.peek().area);
 
-        let screenshot = screenshot_image.read().clone();
-        let state = *app_state.read();
-        let current_sel = *current_selection.read();
-        let temp_sel = *temp_selection.read();
-        let screen_sz = *screen_size.read();
-        let mouse_position = *mouse_pos.read();
-        let shapes = drawing_shapes.read().clone();
-
-        let current_draw = current_drawing.read().clone();
-        let selected_idx = *selected_shape_index.read();
-        let tool = *current_tool.read();
-
-        let selection = current_sel.or(temp_sel);
+        // 订阅重绘计数，编辑器画面变化时重新生成快照
+        let _ = frame.read();
+        let view = editor.read().view();
+        let dpi_scale = *dpi_scale.read();
 
         move |ctx| {
             ctx.canvas.clear(Color::TRANSPARENT);
 
-            if let Some(img) = &screenshot {
-                let canvas_rect = Rect::from_xywh(0.0, 0.0, ctx.area.width(), ctx.area.height());
-                ctx.canvas
-                    .draw_image_rect(img, None, canvas_rect, &Paint::default());
-
-                let mut mask_paint = Paint::default();
-                mask_paint.set_color(Color::from_argb(160, 0, 0, 0));
-                ctx.canvas.draw_rect(canvas_rect, &mask_paint);
-
-                if let Some(sel) = selection {
-                    draw_selection_area(ctx, img, &sel);
-                    draw_selection_border(ctx, &sel, state);
-
-                    if state == AppState::Idle {
-                        // 只有在没有选择绘图工具时才显示选择框的调整手柄
-                        if tool == DrawingTool::None {
-                            draw_handles(ctx, &sel);
-                        }
-
-                        let toolbar = Toolbar::calculate(&sel, screen_sz);
-                        draw_toolbar(ctx, &toolbar, &sel, mouse_position);
-                    }
-                }
-
-                // 绘制所有已完成的图形
-                for (i, shape) in shapes.iter().enumerate() {
-                    let is_selected = selected_idx == Some(i);
-                    draw_shape(ctx, shape, is_selected);
-                }
-
-                // 绘制正在绘制的图形（使用特殊的绘制函数）
-                if let Some(shape) = &current_draw {
-                    draw_drawing_shape(ctx, shape);
-                }
-            }
+            // 之后的绘制都使用物理像素，截图按原始分辨率 1:1 绘制而不拉伸到画布
+            ctx.canvas.save();
+            scale_to_physical(
+                ctx.canvas,
+                LogicalPoint::new(ctx.area.min_x(), ctx.area.min_y()),
+                dpi_scale,
+            );
+            view.draw(ctx.canvas);
+            ctx.canvas.restore();
         }
     });
 
@@ -673,24 +651,11 @@ fn app() -> Element {
             onmousedown: handle_mouse_down,
             onmousemove: handle_mouse_move,
             onmouseup: handle_mouse_up,
-            onglobalkeydown: move |e: KeyboardEvent| {
-                if e.key == Key::Escape {
-                    platform.exit();
-                }
-            },
+            onglobalkeydown: handle_key_down,
+            onglobalkeyup: handle_key_up,
+            onfiledrop: handle_file_drop,
             CursorArea {
-                icon:CursorManager:: get_cursor_icon_with_cache(
-                    *mouse_pos.read(),
-                    *app_state.read(),
-                    current_selection.read().clone(),
-                    *current_tool.read(),
-                    &drawing_shapes.read(),
-                    *selected_shape_index.read(),
-                    resize_handle.read().clone(),
-                    shape_resize_handle.read().clone(),
-                    *screen_size.read(),
-                    &mut last_cursor,
-                ),
+                icon: *cursor.read(),
                 rect {
                     canvas_reference: canvas.attribute(),
                     reference,
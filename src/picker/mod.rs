@@ -0,0 +1,219 @@
+use skia_safe::{AlphaType, Color, ColorType, Image as SkiaImage, ImageInfo, image::CachingHint};
+
+// 取色范围（边长，像素），按快捷键依次切换
+pub const SAMPLE_SIZES: [u32; 4] = [1, 3, 5, 9];
+
+// 复制到剪贴板的颜色格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorFormat {
+    #[default]
+    Hex,
+    Rgb,
+    Hsl,
+    Rust,
+    Css,
+}
+
+impl ColorFormat {
+    pub const ALL: [ColorFormat; 5] = [
+        ColorFormat::Hex,
+        ColorFormat::Rgb,
+        ColorFormat::Hsl,
+        ColorFormat::Rust,
+        ColorFormat::Css,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|format| *format == self);
+        Self::ALL[index.map_or(0, |index| (index + 1) % Self::ALL.len())]
+    }
+
+    pub fn format(&self, color: Color) -> String {
+        let (r, g, b) = (color.r(), color.g(), color.b());
+        match self {
+            ColorFormat::Hex => format!("#{r:02X}{g:02X}{b:02X}"),
+            ColorFormat::Rgb => format!("rgb({r}, {g}, {b})"),
+            ColorFormat::Hsl => {
+                let (h, s, l) = to_hsl(color);
+                format!("hsl({h:.0}, {s:.0}%, {l:.0}%)")
+            }
+            ColorFormat::Rust => format!("Color::from_rgb({r}, {g}, {b})"),
+            ColorFormat::Css => format!("color: #{r:02x}{g:02x}{b:02x};"),
+        }
+    }
+}
+
+// 色相（0-360）、饱和度和亮度（0-100）
+fn to_hsl(color: Color) -> (f32, f32, f32) {
+    let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| c as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness * 100.0);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation * 100.0, lightness * 100.0)
+}
+
+// 以 center 为中心、边长为 size 的区域的平均颜色，区域裁剪到截图范围内
+pub fn sample_color(image: &SkiaImage, center: (i32, i32), size: u32) -> Option<Color> {
+    let half = (size.max(1) / 2) as i32;
+    let left = (center.0 - half).max(0);
+    let top = (center.1 - half).max(0);
+    let right = (center.0 + half + 1).min(image.width());
+    let bottom = (center.1 + half + 1).min(image.height());
+    if left >= right || top >= bottom {
+        return None;
+    }
+
+    let (width, height) = (right - left, bottom - top);
+    let info = ImageInfo::new(
+        (width, height),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    );
+    let row_bytes = width as usize * 4;
+    let mut pixels = vec![0u8; row_bytes * height as usize];
+    if !image.read_pixels(
+        &info,
+        &mut pixels,
+        row_bytes,
+        (left, top),
+        CachingHint::Allow,
+    ) {
+        return None;
+    }
+
+    let count = (width * height) as u32;
+    let mut sums = [0u32; 3];
+    for pixel in pixels.chunks_exact(4) {
+        for (sum, value) in sums.iter_mut().zip(pixel) {
+            *sum += u32::from(*value);
+        }
+    }
+    let [r, g, b] = sums.map(|sum| ((sum + count / 2) / count) as u8);
+    Some(Color::from_rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::{Paint, Rect, surfaces};
+
+    use super::*;
+
+    // 按 (矩形, 颜色) 依次填充的图片，底色为黑色
+    fn image(width: i32, height: i32, fills: &[(Rect, Color)]) -> SkiaImage {
+        let mut surface = surfaces::raster_n32_premul((width, height)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(Color::BLACK);
+        for (rect, color) in fills {
+            let mut paint = Paint::default();
+            paint.set_color(*color);
+            canvas.draw_rect(*rect, &paint);
+        }
+        surface.image_snapshot()
+    }
+
+    #[test]
+    fn hsl_of_primaries_and_secondaries() {
+        let cases = [
+            (Color::from_rgb(255, 0, 0), "hsl(0, 100%, 50%)"),
+            (Color::from_rgb(0, 255, 0), "hsl(120, 100%, 50%)"),
+            (Color::from_rgb(0, 0, 255), "hsl(240, 100%, 50%)"),
+            (Color::from_rgb(255, 255, 0), "hsl(60, 100%, 50%)"),
+            (Color::from_rgb(0, 255, 255), "hsl(180, 100%, 50%)"),
+            (Color::from_rgb(255, 0, 255), "hsl(300, 100%, 50%)"),
+        ];
+        for (color, expected) in cases {
+            assert_eq!(ColorFormat::Hsl.format(color), expected);
+        }
+    }
+
+    #[test]
+    fn hsl_of_greys_has_no_hue_or_saturation() {
+        let cases = [
+            (Color::from_rgb(0, 0, 0), "hsl(0, 0%, 0%)"),
+            (Color::from_rgb(128, 128, 128), "hsl(0, 0%, 50%)"),
+            (Color::from_rgb(255, 255, 255), "hsl(0, 0%, 100%)"),
+        ];
+        for (color, expected) in cases {
+            assert_eq!(ColorFormat::Hsl.format(color), expected);
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let color = Color::from_rgb(255, 128, 10);
+        let cases = [
+            (ColorFormat::Hex, "#FF800A"),
+            (ColorFormat::Rgb, "rgb(255, 128, 10)"),
+            (ColorFormat::Rust, "Color::from_rgb(255, 128, 10)"),
+            (ColorFormat::Css, "color: #ff800a;"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(format.format(color), expected);
+        }
+    }
+
+    #[test]
+    fn next_format_wraps_around() {
+        let mut format = ColorFormat::default();
+        for expected in ColorFormat::ALL
+            .iter()
+            .skip(1)
+            .chain(&ColorFormat::ALL[..1])
+        {
+            format = format.next();
+            assert_eq!(format, *expected);
+        }
+    }
+
+    #[test]
+    fn sample_averages_window() {
+        // 左半边红色，右半边蓝色
+        let image = image(
+            4,
+            4,
+            &[
+                (Rect::from_ltrb(0.0, 0.0, 2.0, 4.0), Color::RED),
+                (Rect::from_ltrb(2.0, 0.0, 4.0, 4.0), Color::BLUE),
+            ],
+        );
+        assert_eq!(sample_color(&image, (1, 1), 1), Some(Color::RED));
+        assert_eq!(sample_color(&image, (2, 1), 1), Some(Color::BLUE));
+        // 3×3 中一列红色、两列蓝色
+        assert_eq!(
+            sample_color(&image, (2, 2), 3),
+            Some(Color::from_rgb(85, 0, 170))
+        );
+    }
+
+    #[test]
+    fn sample_is_clipped_to_image() {
+        // 只有左上角一个白色像素
+        let image = image(4, 4, &[(Rect::from_ltrb(0.0, 0.0, 1.0, 1.0), Color::WHITE)]);
+        // 只平均图片内的 2×2 个像素
+        assert_eq!(
+            sample_color(&image, (0, 0), 3),
+            Some(Color::from_rgb(64, 64, 64))
+        );
+        // 范围超过整张图片时取整张图片的平均值
+        assert_eq!(
+            sample_color(&image, (2, 2), 9),
+            Some(Color::from_rgb(16, 16, 16))
+        );
+        assert_eq!(sample_color(&image, (-5, -5), 3), None);
+        assert_eq!(sample_color(&image, (4, 0), 1), None);
+    }
+}
@@ -0,0 +1,74 @@
+use skia_safe::{Canvas, Color, Paint, Rect};
+
+use crate::{coords::LogicalSize, shapes::text::text_font};
+
+const HELP_FONT_SIZE: f32 = 15.0;
+const HELP_LINE_HEIGHT: f32 = 24.0;
+const HELP_PADDING: f32 = 20.0;
+const HELP_COLUMN_GAP: f32 = 24.0;
+
+const HELP_TITLE: &str = "快捷键";
+const HELP_FOOTER: &str = "按任意键关闭";
+
+// 屏幕中央的快捷键列表：左列为快捷键，右列为说明
+pub fn draw_help(canvas: &Canvas, entries: &[(String, &str)], screen_size: LogicalSize) {
+    let font = text_font(HELP_FONT_SIZE);
+    let (_, metrics) = font.metrics();
+    let key_width = entries
+        .iter()
+        .map(|(keys, _)| font.measure_str(keys, None).0)
+        .fold(0.0, f32::max);
+    let description_width = entries
+        .iter()
+        .map(|(_, description)| font.measure_str(description, None).0)
+        .chain([font.measure_str(HELP_FOOTER, None).0])
+        .fold(0.0, f32::max);
+
+    // 标题、列表和底部提示各占若干行
+    let rows = entries.len() as f32 + 3.0;
+    let width = key_width + HELP_COLUMN_GAP + description_width + HELP_PADDING * 2.0;
+    let height = rows * HELP_LINE_HEIGHT + HELP_PADDING * 2.0;
+    let left = ((screen_size.width - width) / 2.0).max(0.0);
+    let top = ((screen_size.height - height) / 2.0).max(0.0);
+
+    let mut background = Paint::default();
+    background.set_color(Color::from_argb(230, 30, 30, 30));
+    background.set_anti_alias(true);
+    canvas.draw_round_rect(
+        Rect::from_xywh(left, top, width, height),
+        8.0,
+        8.0,
+        &background,
+    );
+
+    let mut title_paint = Paint::default();
+    title_paint.set_color(Color::WHITE);
+    title_paint.set_anti_alias(true);
+    let mut key_paint = Paint::default();
+    key_paint.set_color(Color::from_rgb(255, 214, 10));
+    key_paint.set_anti_alias(true);
+    let mut text_paint = Paint::default();
+    text_paint.set_color(Color::from_rgb(220, 220, 220));
+    text_paint.set_anti_alias(true);
+
+    // 文字在行内垂直居中
+    let baseline = |row: f32| {
+        top + HELP_PADDING + row * HELP_LINE_HEIGHT + HELP_LINE_HEIGHT / 2.0
+            - (metrics.ascent + metrics.descent) / 2.0
+    };
+    let key_x = left + HELP_PADDING;
+    let description_x = key_x + key_width + HELP_COLUMN_GAP;
+
+    canvas.draw_str(HELP_TITLE, (key_x, baseline(0.0)), &font, &title_paint);
+    for (i, (keys, description)) in entries.iter().enumerate() {
+        let y = baseline(i as f32 + 1.5);
+        canvas.draw_str(keys, (key_x, y), &font, &key_paint);
+        canvas.draw_str(description, (description_x, y), &font, &text_paint);
+    }
+    canvas.draw_str(
+        HELP_FOOTER,
+        (key_x, baseline(rows - 1.0)),
+        &font,
+        &text_paint,
+    );
+}
@@ -0,0 +1,195 @@
+use skia_safe::{
+    Canvas, Color, FilterMode, Image as SkiaImage, MipmapMode, Paint, PaintStyle, Rect,
+    SamplingOptions, canvas::SrcRectConstraint,
+};
+
+use crate::{
+    constants::constants::{MAGNIFIER_PIXELS, MAGNIFIER_ZOOM},
+    picker::{ColorFormat, sample_color},
+    shapes::text::text_font,
+    types::ui::Magnifier,
+};
+
+const INFO_FONT_SIZE: f32 = 12.0;
+const INFO_LINE_HEIGHT: f32 = 16.0;
+const INFO_PADDING: f32 = 6.0;
+const SWATCH_SIZE: f32 = 10.0;
+
+// 放大镜：鼠标所在像素周围的放大网格、十字准线，以及坐标、选区大小和颜色
+// 取色模式下颜色为取色范围内的平均值，最后一行按复制的格式显示
+pub fn draw_magnifier(
+    canvas: &Canvas,
+    magnifier: &Magnifier,
+    screenshot: &SkiaImage,
+    pixel: (i32, i32),
+    picker: Option<(ColorFormat, u32)>,
+    selection_size: Option<(f32, f32)>,
+) {
+    let mut background = Paint::default();
+    background.set_color(Color::from_argb(230, 30, 30, 30));
+    background.set_anti_alias(true);
+    canvas.draw_round_rect(
+        Rect::from_xywh(magnifier.x, magnifier.y, magnifier.width, magnifier.height),
+        6.0,
+        6.0,
+        &background,
+    );
+
+    let (format, sample_size) = picker.unwrap_or((ColorFormat::Rgb, 1));
+    let color =
+        sample_color(screenshot, pixel, sample_size).map(|color| (color, format.format(color)));
+    draw_grid(canvas, magnifier, screenshot, pixel, sample_size);
+    draw_info(canvas, magnifier, pixel, color, selection_size);
+}
+
+fn draw_grid(
+    canvas: &Canvas,
+    magnifier: &Magnifier,
+    screenshot: &SkiaImage,
+    pixel: (i32, i32),
+    sample_size: u32,
+) {
+    let (grid_x, grid_y, grid_size, _) = magnifier.grid_rect();
+    let grid = Rect::from_xywh(grid_x, grid_y, grid_size, grid_size);
+    let half = (MAGNIFIER_PIXELS as i32 - 1) / 2;
+    let src_left = (pixel.0 - half) as f32;
+    let src_top = (pixel.1 - half) as f32;
+
+    // 截图外的部分保持黑色，只放大与截图相交的像素
+    let mut black = Paint::default();
+    black.set_color(Color::BLACK);
+    canvas.draw_rect(grid, &black);
+
+    let mut visible = Rect::from_xywh(src_left, src_top, MAGNIFIER_PIXELS, MAGNIFIER_PIXELS);
+    let bounds = Rect::from_wh(screenshot.width() as f32, screenshot.height() as f32);
+    if visible.intersect(bounds) {
+        let dst = Rect::from_ltrb(
+            grid_x + (visible.left - src_left) * MAGNIFIER_ZOOM,
+            grid_y + (visible.top - src_top) * MAGNIFIER_ZOOM,
+            grid_x + (visible.right - src_left) * MAGNIFIER_ZOOM,
+            grid_y + (visible.bottom - src_top) * MAGNIFIER_ZOOM,
+        );
+        canvas.draw_image_rect_with_sampling_options(
+            screenshot,
+            Some((&visible, SrcRectConstraint::Strict)),
+            dst,
+            SamplingOptions::new(FilterMode::Nearest, MipmapMode::None),
+            &Paint::default(),
+        );
+    }
+
+    // 像素之间的网格线
+    let mut grid_paint = Paint::default();
+    grid_paint.set_color(Color::from_argb(50, 255, 255, 255));
+    grid_paint.set_stroke_width(1.0);
+    for i in 1..MAGNIFIER_PIXELS as i32 {
+        let offset = i as f32 * MAGNIFIER_ZOOM;
+        canvas.draw_line(
+            (grid_x + offset, grid_y),
+            (grid_x + offset, grid_y + grid_size),
+            &grid_paint,
+        );
+        canvas.draw_line(
+            (grid_x, grid_y + offset),
+            (grid_x + grid_size, grid_y + offset),
+            &grid_paint,
+        );
+    }
+
+    // 十字准线：高亮中心像素所在的行和列，并给中心像素加边框
+    let center = half as f32 * MAGNIFIER_ZOOM;
+    let mut crosshair = Paint::default();
+    crosshair.set_color(Color::from_argb(70, 0, 170, 255));
+    canvas.draw_rect(
+        Rect::from_xywh(grid_x + center, grid_y, MAGNIFIER_ZOOM, grid_size),
+        &crosshair,
+    );
+    canvas.draw_rect(
+        Rect::from_xywh(grid_x, grid_y + center, grid_size, MAGNIFIER_ZOOM),
+        &crosshair,
+    );
+
+    // 中心像素的边框，取色范围大于一个像素时框出整个范围
+    let mut center_paint = Paint::default();
+    center_paint.set_color(Color::WHITE);
+    center_paint.set_style(PaintStyle::Stroke);
+    center_paint.set_stroke_width(1.0);
+    let sample_offset = (sample_size / 2) as f32 * MAGNIFIER_ZOOM;
+    let sample_extent = sample_size as f32 * MAGNIFIER_ZOOM;
+    canvas.draw_rect(
+        Rect::from_xywh(
+            grid_x + center - sample_offset,
+            grid_y + center - sample_offset,
+            sample_extent,
+            sample_extent,
+        ),
+        &center_paint,
+    );
+
+    let mut border = Paint::default();
+    border.set_color(Color::from_rgb(180, 180, 180));
+    border.set_style(PaintStyle::Stroke);
+    border.set_stroke_width(1.0);
+    canvas.draw_rect(grid, &border);
+}
+
+fn draw_info(
+    canvas: &Canvas,
+    magnifier: &Magnifier,
+    pixel: (i32, i32),
+    color: Option<(Color, String)>,
+    selection_size: Option<(f32, f32)>,
+) {
+    let font = text_font(INFO_FONT_SIZE);
+    let (_, metrics) = font.metrics();
+    let mut text_paint = Paint::default();
+    text_paint.set_color(Color::from_rgb(220, 220, 220));
+    text_paint.set_anti_alias(true);
+
+    let (_, grid_y, _, grid_size) = magnifier.grid_rect();
+    let left = magnifier.x + INFO_PADDING;
+    // 文字在行内垂直居中
+    let baseline = |row: f32| {
+        grid_y + grid_size + INFO_PADDING + row * INFO_LINE_HEIGHT + INFO_LINE_HEIGHT / 2.0
+            - (metrics.ascent + metrics.descent) / 2.0
+    };
+
+    let position = format!("{}, {}", pixel.0, pixel.1);
+    canvas.draw_str(position, (left, baseline(0.0)), &font, &text_paint);
+
+    if let Some((width, height)) = selection_size {
+        let size = format!("{} × {}", width.round(), height.round());
+        canvas.draw_str(size, (left, baseline(1.0)), &font, &text_paint);
+    }
+
+    let Some((color, color_text)) = color else {
+        return;
+    };
+    let swatch_top = grid_y
+        + grid_size
+        + INFO_PADDING
+        + 2.0 * INFO_LINE_HEIGHT
+        + (INFO_LINE_HEIGHT - SWATCH_SIZE) / 2.0;
+    let mut swatch = Paint::default();
+    swatch.set_color(color);
+    canvas.draw_rect(
+        Rect::from_xywh(left, swatch_top, SWATCH_SIZE, SWATCH_SIZE),
+        &swatch,
+    );
+    let mut swatch_border = Paint::default();
+    swatch_border.set_color(Color::WHITE);
+    swatch_border.set_style(PaintStyle::Stroke);
+    swatch_border.set_stroke_width(1.0);
+    canvas.draw_rect(
+        Rect::from_xywh(left, swatch_top, SWATCH_SIZE, SWATCH_SIZE),
+        &swatch_border,
+    );
+
+    canvas.draw_str(
+        ColorFormat::Hex.format(color),
+        (left + SWATCH_SIZE + INFO_PADDING, baseline(2.0)),
+        &font,
+        &text_paint,
+    );
+    canvas.draw_str(color_text, (left, baseline(3.0)), &font, &text_paint);
+}
@@ -1,5 +1,11 @@
 
 
+pub mod help;
+pub mod magnifier;
+pub mod overlay;
+pub mod property_bar;
+pub mod redact;
 pub mod shapes;
 pub mod selection;
+pub mod selection_input;
 pub mod toolbar;
\ No newline at end of file
@@ -0,0 +1,173 @@
+use skia_safe::{Canvas, Color, Image as SkiaImage, Paint, Rect, surfaces};
+
+use crate::{
+    coords::ScaleFactor,
+    rendering::{
+        selection::{draw_handles, draw_selection_area, draw_selection_border},
+        shapes::{draw_drawing_shape, draw_shape, draw_text_caret},
+    },
+    types::{app_state::AppState, drawing::DrawingShape, ui::Selection},
+};
+
+pub const DEFAULT_MASK_COLOR: Color = Color::from_argb(160, 0, 0, 0);
+
+// 编辑器画面（不含工具栏和属性栏），所有坐标都是截图的物理像素
+pub struct OverlayScene<'a> {
+    pub screenshot: &'a SkiaImage,
+    pub selection: Option<Selection>,
+    pub state: AppState,
+    pub mask_color: Color,
+    // 是否显示选择框的调整手柄
+    pub selection_handles: bool,
+    pub shapes: &'a [DrawingShape],
+    pub selected_shape: Option<usize>,
+    // 正在绘制的图形
+    pub drawing: Option<&'a DrawingShape>,
+    // 正在编辑的文字：(图形索引, 光标位置)
+    pub text_caret: Option<(usize, usize)>,
+    // 调整手柄按界面缩放比例放大
+    pub ui_scale: ScaleFactor,
+}
+
+impl<'a> OverlayScene<'a> {
+    pub fn new(
+        screenshot: &'a SkiaImage,
+        selection: Option<Selection>,
+        shapes: &'a [DrawingShape],
+    ) -> Self {
+        Self {
+            screenshot,
+            selection,
+            state: AppState::Idle,
+            mask_color: DEFAULT_MASK_COLOR,
+            selection_handles: false,
+            shapes,
+            selected_shape: None,
+            drawing: None,
+            text_caret: None,
+            ui_scale: ScaleFactor::IDENTITY,
+        }
+    }
+}
+
+// 在任意 skia 画布上绘制编辑器画面，截图按 1:1 绘制在原点
+pub fn draw_overlay(canvas: &Canvas, scene: &OverlayScene) {
+    let screenshot = scene.screenshot;
+    let image_rect = Rect::from_wh(screenshot.width() as f32, screenshot.height() as f32);
+    canvas.draw_image(screenshot, (0.0, 0.0), None);
+
+    let mut mask_paint = Paint::default();
+    mask_paint.set_color(scene.mask_color);
+    canvas.draw_rect(image_rect, &mask_paint);
+
+    if let Some(selection) = &scene.selection {
+        draw_selection_area(canvas, screenshot, selection);
+        draw_selection_border(canvas, selection, scene.state);
+        if scene.selection_handles {
+            draw_handles(canvas, selection, scene.ui_scale);
+        }
+    }
+
+    for (i, shape) in scene.shapes.iter().enumerate() {
+        draw_shape(
+            canvas,
+            screenshot,
+            shape,
+            scene.selected_shape == Some(i),
+            scene.ui_scale,
+        );
+    }
+
+    if let Some(shape) = scene.drawing {
+        draw_drawing_shape(canvas, screenshot, shape);
+    }
+
+    if let Some((index, caret)) = scene.text_caret {
+        if let Some(shape) = scene.shapes.get(index) {
+            draw_text_caret(canvas, shape, caret);
+        }
+    }
+}
+
+// 在 CPU 光栅表面上绘制编辑器画面，不需要窗口或 GPU
+pub fn render_overlay(scene: &OverlayScene) -> Option<SkiaImage> {
+    let mut surface =
+        surfaces::raster_n32_premul((scene.screenshot.width(), scene.screenshot.height()))?;
+    surface.canvas().clear(Color::TRANSPARENT);
+    draw_overlay(surface.canvas(), scene);
+    Some(surface.image_snapshot())
+}
+
+// 在 CPU 光栅表面上绘制图片、选择框和标注
+pub fn render_annotations(
+    image: &SkiaImage,
+    selection: Option<Selection>,
+    shapes: &[DrawingShape],
+) -> Option<SkiaImage> {
+    render_overlay(&OverlayScene::new(image, selection, shapes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pixel, solid_image};
+
+    fn white_image() -> SkiaImage {
+        solid_image(100, 80, Color::WHITE)
+    }
+
+    const SELECTION: Selection = Selection {
+        start: (20.0, 20.0),
+        end: (80.0, 60.0),
+    };
+
+    #[test]
+    fn mask_covers_only_outside_selection() {
+        let image = white_image();
+        let mut scene = OverlayScene::new(&image, Some(SELECTION), &[]);
+        scene.mask_color = Color::BLUE;
+        let rendered = render_overlay(&scene).unwrap();
+
+        assert_eq!(rendered.dimensions(), image.dimensions());
+        assert_eq!(pixel(&rendered, 5, 5), [0, 0, 255, 255]);
+        assert_eq!(pixel(&rendered, 90, 70), [0, 0, 255, 255]);
+        assert_eq!(pixel(&rendered, 50, 40), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn default_mask_darkens_screenshot() {
+        let image = white_image();
+        let rendered = render_annotations(&image, Some(SELECTION), &[]).unwrap();
+
+        let [r, g, b, a] = pixel(&rendered, 5, 5);
+        assert!(r < 128 && r == g && g == b, "{r}, {g}, {b}");
+        assert_eq!(a, 255);
+        assert_eq!(pixel(&rendered, 50, 40), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn annotations_are_drawn_over_selection() {
+        let image = white_image();
+        let shapes = [DrawingShape::Rectangle {
+            start: (30.0, 30.0),
+            end: (50.0, 50.0),
+            color: Color::RED,
+            stroke_width: 4.0,
+        }];
+        let rendered = render_annotations(&image, Some(SELECTION), &shapes).unwrap();
+
+        assert_eq!(pixel(&rendered, 30, 40), [255, 0, 0, 255]);
+        assert_eq!(pixel(&rendered, 40, 40), [255, 255, 255, 255]);
+        assert_eq!(pixel(&rendered, 70, 40), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn whole_screenshot_is_masked_without_selection() {
+        let image = white_image();
+        let mut scene = OverlayScene::new(&image, None, &[]);
+        scene.mask_color = Color::BLUE;
+        let rendered = render_overlay(&scene).unwrap();
+
+        assert_eq!(pixel(&rendered, 50, 40), [0, 0, 255, 255]);
+    }
+}
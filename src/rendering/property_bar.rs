@@ -0,0 +1,191 @@
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Rect};
+
+use crate::{
+    constants::constants::PROPERTY_BUTTON_SIZE,
+    coords::LogicalSize,
+    shapes::{
+        style::{PRESET_COLORS, parse_hex_color},
+        text::text_font,
+    },
+    types::{
+        drawing::ShapeStyle,
+        ui::{PropertyBar, PropertyButton},
+    },
+};
+
+const COLOR_INPUT_FONT_SIZE: f32 = 14.0;
+
+pub fn draw_property_bar(
+    canvas: &Canvas,
+    property_bar: &PropertyBar,
+    style: ShapeStyle,
+    color_input: Option<&str>,
+    mouse_pos: (f32, f32),
+    screen_size: LogicalSize,
+) {
+    for index in 0..PropertyButton::COUNT {
+        let Some(button) = PropertyButton::from_index(index) else {
+            continue;
+        };
+        let button_x = property_bar.button_x(index);
+        let button_rect = Rect::from_xywh(
+            button_x,
+            property_bar.y,
+            PROPERTY_BUTTON_SIZE,
+            PROPERTY_BUTTON_SIZE,
+        );
+        let is_hovered = mouse_pos.0 >= button_rect.left
+            && mouse_pos.0 <= button_rect.right
+            && mouse_pos.1 >= button_rect.top
+            && mouse_pos.1 <= button_rect.bottom;
+
+        // 按钮背景与工具栏保持一致
+        let mut button_paint = Paint::default();
+        if is_hovered {
+            button_paint.set_color(Color::from_argb(240, 80, 80, 80));
+        } else {
+            button_paint.set_color(Color::from_argb(220, 45, 45, 45));
+        }
+        button_paint.set_anti_alias(true);
+        canvas.draw_round_rect(button_rect, 4.0, 4.0, &button_paint);
+
+        let is_active = match button {
+            PropertyButton::Color(color) => style.color == color,
+            PropertyButton::CustomColor => {
+                color_input.is_some() || !PRESET_COLORS.contains(&style.color)
+            }
+            PropertyButton::StrokeWidth(width) => style.stroke_width == width,
+        };
+
+        let mut border_paint = Paint::default();
+        border_paint.set_style(PaintStyle::Stroke);
+        border_paint.set_anti_alias(true);
+        if is_active {
+            // 当前样式：高亮边框
+            border_paint.set_color(Color::from_rgb(255, 255, 255));
+            border_paint.set_stroke_width(2.0);
+        } else if is_hovered {
+            border_paint.set_color(Color::from_rgb(220, 220, 220));
+            border_paint.set_stroke_width(1.0);
+        } else {
+            border_paint.set_color(Color::from_rgb(180, 180, 180));
+            border_paint.set_stroke_width(1.0);
+        }
+        canvas.draw_round_rect(button_rect, 4.0, 4.0, &border_paint);
+
+        let swatch_rect = button_rect.with_inset((6.0, 6.0));
+        match button {
+            PropertyButton::Color(color) => {
+                draw_swatch(canvas, swatch_rect, color);
+            }
+            PropertyButton::CustomColor => {
+                // 自定义颜色：非预设颜色时显示当前颜色，并标注 #
+                let glyph_color = if PRESET_COLORS.contains(&style.color) {
+                    Color::from_rgb(200, 200, 200)
+                } else {
+                    draw_swatch(canvas, swatch_rect, style.color);
+                    contrast_color(style.color)
+                };
+                let mut text_paint = Paint::default();
+                text_paint.set_color(glyph_color);
+                text_paint.set_anti_alias(true);
+                let font = text_font(COLOR_INPUT_FONT_SIZE);
+                let (glyph_width, _) = font.measure_str("#", Some(&text_paint));
+                let (_, metrics) = font.metrics();
+                canvas.draw_str(
+                    "#",
+                    (
+                        swatch_rect.center_x() - glyph_width / 2.0,
+                        swatch_rect.center_y() - (metrics.ascent + metrics.descent) / 2.0,
+                    ),
+                    &font,
+                    &text_paint,
+                );
+            }
+            PropertyButton::StrokeWidth(width) => {
+                // 线宽：用对应直径的圆点示意
+                let mut dot_paint = Paint::default();
+                dot_paint.set_color(Color::from_rgb(200, 200, 200));
+                dot_paint.set_anti_alias(true);
+                let radius = (width / 2.0).min(swatch_rect.width() / 2.0);
+                canvas.draw_circle(
+                    (swatch_rect.center_x(), swatch_rect.center_y()),
+                    radius,
+                    &dot_paint,
+                );
+            }
+        }
+    }
+
+    if let Some(input) = color_input {
+        draw_color_input(canvas, property_bar.color_input_rect(screen_size), input);
+    }
+}
+
+fn draw_swatch(canvas: &Canvas, rect: Rect, color: Color) {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 2.0, 2.0, &paint);
+
+    // 细边框，避免深色和白色色块融进背景
+    let mut outline = Paint::default();
+    outline.set_color(Color::from_argb(160, 128, 128, 128));
+    outline.set_style(PaintStyle::Stroke);
+    outline.set_stroke_width(1.0);
+    outline.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 2.0, 2.0, &outline);
+}
+
+fn contrast_color(color: Color) -> Color {
+    let luma = 0.299 * color.r() as f32 + 0.587 * color.g() as f32 + 0.114 * color.b() as f32;
+    if luma > 150.0 {
+        Color::from_rgb(0, 0, 0)
+    } else {
+        Color::from_rgb(255, 255, 255)
+    }
+}
+
+fn draw_color_input(canvas: &Canvas, (x, y, width, height): (f32, f32, f32, f32), input: &str) {
+    let rect = Rect::from_xywh(x, y, width, height);
+
+    let mut background = Paint::default();
+    background.set_color(Color::from_argb(240, 30, 30, 30));
+    background.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 4.0, 4.0, &background);
+
+    let mut border = Paint::default();
+    border.set_color(Color::from_rgb(255, 255, 255));
+    border.set_style(PaintStyle::Stroke);
+    border.set_stroke_width(1.0);
+    border.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 4.0, 4.0, &border);
+
+    // 输入合法时在右侧预览颜色
+    let preview_size = rect.height() - 10.0;
+    if let Some(color) = parse_hex_color(input) {
+        let preview = Rect::from_xywh(
+            rect.right - preview_size - 5.0,
+            rect.top + 5.0,
+            preview_size,
+            preview_size,
+        );
+        draw_swatch(canvas, preview, color);
+    }
+
+    let font = text_font(COLOR_INPUT_FONT_SIZE);
+    let (_, metrics) = font.metrics();
+    let mut text_paint = Paint::default();
+    text_paint.set_color(Color::from_rgb(230, 230, 230));
+    text_paint.set_anti_alias(true);
+    let text = format!("#{input}|");
+    canvas.draw_str(
+        &text,
+        (
+            rect.left + 8.0,
+            rect.center_y() - (metrics.ascent + metrics.descent) / 2.0,
+        ),
+        &font,
+        &text_paint,
+    );
+}
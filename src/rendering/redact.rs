@@ -0,0 +1,136 @@
+use skia_safe::{
+    Canvas, FilterMode, Image as SkiaImage, Matrix, MipmapMode, Paint, PaintCap, PaintJoin,
+    PaintStyle, Path, Rect, SamplingOptions, Shader, TileMode, canvas::SrcRectConstraint,
+    image_filters, surfaces,
+};
+
+use crate::{
+    shapes::redact::{MIN_BLUR_SIGMA, MIN_MOSAIC_BLOCK_SIZE},
+    types::drawing::RedactArea,
+};
+
+// 区域向外取整到像素后与截图范围的交集
+fn clipped_bounds(area: &RedactArea, screenshot: &SkiaImage) -> Option<Rect> {
+    let (left, top, right, bottom) = area.bounds();
+    let rect = Rect::from_ltrb(
+        left.floor().max(0.0),
+        top.floor().max(0.0),
+        right.ceil().min(screenshot.width() as f32),
+        bottom.ceil().min(screenshot.height() as f32),
+    );
+    (rect.width() >= 1.0 && rect.height() >= 1.0).then_some(rect)
+}
+
+// 用着色器填充打码区域（矩形、椭圆或笔刷轨迹）
+// 不开抗锯齿：边缘像素按覆盖率与原图混合会留下可辨认的原始内容
+fn fill_area(canvas: &Canvas, area: &RedactArea, shader: Shader) {
+    let mut paint = Paint::default();
+    paint.set_shader(shader);
+    paint.set_anti_alias(false);
+
+    match area {
+        RedactArea::Rectangle { .. } => {
+            // 矩形向外取整，边界上只覆盖了一部分的像素也整个打码
+            let (left, top, right, bottom) = area.bounds();
+            let rect = Rect::from_ltrb(left.floor(), top.floor(), right.ceil(), bottom.ceil());
+            canvas.draw_rect(rect, &paint);
+        }
+        RedactArea::Ellipse { .. } => {
+            let (left, top, right, bottom) = area.bounds();
+            canvas.draw_oval(Rect::from_ltrb(left, top, right, bottom), &paint);
+        }
+        RedactArea::Brush { points, width } => {
+            if let [point] = points.as_slice() {
+                canvas.draw_circle(*point, width / 2.0, &paint);
+                return;
+            }
+
+            let mut path = Path::new();
+            for (i, point) in points.iter().enumerate() {
+                if i == 0 {
+                    path.move_to(*point);
+                } else {
+                    path.line_to(*point);
+                }
+            }
+
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_width(*width);
+            paint.set_stroke_cap(PaintCap::Round);
+            paint.set_stroke_join(PaintJoin::Round);
+            canvas.draw_path(&path, &paint);
+        }
+    }
+}
+
+// 马赛克：先把区域缩小到每块一个像素，再用最近邻采样放大回去
+pub fn paint_mosaic(canvas: &Canvas, screenshot: &SkiaImage, area: &RedactArea, block_size: f32) {
+    let Some(src) = clipped_bounds(area, screenshot) else {
+        return;
+    };
+
+    let block_size = block_size.max(MIN_MOSAIC_BLOCK_SIZE);
+    let columns = (src.width() / block_size).ceil().max(1.0) as i32;
+    let rows = (src.height() / block_size).ceil().max(1.0) as i32;
+
+    let Some(mut surface) = surfaces::raster_n32_premul((columns, rows)) else {
+        return;
+    };
+    surface.canvas().draw_image_rect_with_sampling_options(
+        screenshot,
+        Some((&src, SrcRectConstraint::Strict)),
+        Rect::from_wh(columns as f32, rows as f32),
+        SamplingOptions::new(FilterMode::Linear, MipmapMode::None),
+        &Paint::default(),
+    );
+    let blocks = surface.image_snapshot();
+
+    let mut matrix = Matrix::scale((src.width() / columns as f32, src.height() / rows as f32));
+    matrix.post_translate((src.left, src.top));
+
+    if let Some(shader) = blocks.to_shader(
+        (TileMode::Clamp, TileMode::Clamp),
+        SamplingOptions::new(FilterMode::Nearest, MipmapMode::None),
+        &matrix,
+    ) {
+        fill_area(canvas, area, shader);
+    }
+}
+
+// 高斯模糊：在离屏表面上模糊区域内的截图像素，再填充回区域
+// 导出时同样走这里，输出文件中只保留模糊后的像素
+pub fn paint_blur(canvas: &Canvas, screenshot: &SkiaImage, area: &RedactArea, sigma: f32) {
+    let Some(src) = clipped_bounds(area, screenshot) else {
+        return;
+    };
+
+    let width = src.width().ceil() as i32;
+    let height = src.height().ceil() as i32;
+    let Some(mut surface) = surfaces::raster_n32_premul((width, height)) else {
+        return;
+    };
+
+    let sigma = sigma.max(MIN_BLUR_SIGMA);
+    let mut blur_paint = Paint::default();
+    blur_paint.set_image_filter(image_filters::blur(
+        (sigma, sigma),
+        TileMode::Clamp,
+        None,
+        None,
+    ));
+
+    // 区域外的相邻像素也参与模糊，避免边缘变暗
+    let surface_canvas = surface.canvas();
+    surface_canvas.translate((-src.left, -src.top));
+    surface_canvas.draw_image(screenshot, (0.0, 0.0), Some(&blur_paint));
+    let blurred = surface.image_snapshot();
+
+    let matrix = Matrix::translate((src.left, src.top));
+    if let Some(shader) = blurred.to_shader(
+        (TileMode::Clamp, TileMode::Clamp),
+        SamplingOptions::new(FilterMode::Linear, MipmapMode::None),
+        &matrix,
+    ) {
+        fill_area(canvas, area, shader);
+    }
+}
@@ -1,16 +1,16 @@
-use freya::core::custom_attributes::CanvasRunnerContext;
 use skia_safe::{
-    AlphaType, Color, ColorType, Data, Image as SkiaImage, ImageInfo, Paint, PaintStyle,
+    AlphaType, Canvas, Color, ColorType, Data, Image as SkiaImage, ImageInfo, Paint, PaintStyle,
     PathEffect, Rect, canvas::SrcRectConstraint, images,
 };
 
-use crate::{constants::constants::HANDLE_SIZE, types::{app_state::AppState, drawing::DrawingShape, ui::Selection}};
+use crate::{constants::constants::HANDLE_SIZE, coords::ScaleFactor, types::{app_state::AppState, drawing::DrawingShape, ui::Selection}};
 
 
-pub fn draw_selection_area(ctx: &mut CanvasRunnerContext, img: &SkiaImage, selection: &Selection) {
+pub fn draw_selection_area(canvas: &Canvas, img: &SkiaImage, selection: &Selection) {
     let (left, top, right, bottom) = selection.bounds();
-    let canvas_width = ctx.area.width();
-    let canvas_height = ctx.area.height();
+    // 画布已切换到物理像素，按截图范围裁剪
+    let canvas_width = img.width() as f32;
+    let canvas_height = img.height() as f32;
 
     let clipped_left = left.max(0.0);
     let clipped_top = top.max(0.0);
@@ -31,7 +31,7 @@ pub fn draw_selection_area(ctx: &mut CanvasRunnerContext, img: &SkiaImage, selec
             clipped_bottom - clipped_top,
         );
 
-        ctx.canvas.draw_image_rect(
+        canvas.draw_image_rect(
             img,
             Some((&src_rect, SrcRectConstraint::Fast)),
             selection_rect,
@@ -40,11 +40,7 @@ pub fn draw_selection_area(ctx: &mut CanvasRunnerContext, img: &SkiaImage, selec
     }
 }
 
-pub fn draw_selection_border(
-    ctx: &mut CanvasRunnerContext,
-    selection: &Selection,
-    state: AppState,
-) {
+pub fn draw_selection_border(canvas: &Canvas, selection: &Selection, state: AppState) {
     let (left, top, right, bottom) = selection.bounds();
     let mut paint = Paint::default();
     paint.set_style(PaintStyle::Stroke);
@@ -66,13 +62,15 @@ pub fn draw_selection_border(
     }
 
     let rect = Rect::from_xywh(left, top, right - left, bottom - top);
-    ctx.canvas.draw_rect(rect, &paint);
+    canvas.draw_rect(rect, &paint);
 }
-pub fn draw_handles(ctx: &mut CanvasRunnerContext, selection: &Selection) {
+// 手柄是界面控件，按界面缩放比例放大，在高 DPI 显示器上保持同样的大小
+pub fn draw_handles(canvas: &Canvas, selection: &Selection, ui_scale: ScaleFactor) {
     let (left, top, right, bottom) = selection.bounds();
     let center_x = (left + right) / 2.0;
     let center_y = (top + bottom) / 2.0;
 
+    let handle_size = HANDLE_SIZE * ui_scale.get();
     let mut handle_paint = Paint::default();
     handle_paint.set_color(Color::from_rgb(255, 255, 255));
     handle_paint.set_anti_alias(true);
@@ -80,7 +78,7 @@ pub fn draw_handles(ctx: &mut CanvasRunnerContext, selection: &Selection) {
     let mut border_paint = Paint::default();
     border_paint.set_color(Color::from_rgb(0, 0, 0));
     border_paint.set_style(PaintStyle::Stroke);
-    border_paint.set_stroke_width(1.0);
+    border_paint.set_stroke_width(ui_scale.get());
     border_paint.set_anti_alias(true);
 
     let handles = [
@@ -96,22 +94,23 @@ pub fn draw_handles(ctx: &mut CanvasRunnerContext, selection: &Selection) {
 
     for (x, y) in handles {
         let rect = Rect::from_xywh(
-            x - HANDLE_SIZE / 2.0,
-            y - HANDLE_SIZE / 2.0,
-            HANDLE_SIZE,
-            HANDLE_SIZE,
+            x - handle_size / 2.0,
+            y - handle_size / 2.0,
+            handle_size,
+            handle_size,
         );
-        ctx.canvas.draw_rect(rect, &handle_paint);
-        ctx.canvas.draw_rect(rect, &border_paint);
+        canvas.draw_rect(rect, &handle_paint);
+        canvas.draw_rect(rect, &border_paint);
     }
 }
 
 // 新增函数：绘制选择手柄
-pub fn draw_selection_handles(ctx: &mut CanvasRunnerContext, shape: &DrawingShape) {
+pub fn draw_selection_handles(canvas: &Canvas, shape: &DrawingShape, ui_scale: ScaleFactor) {
     let (left, top, right, bottom) = shape.bounds();
     let center_x = (left + right) / 2.0;
     let center_y = (top + bottom) / 2.0;
 
+    let handle_size = HANDLE_SIZE * ui_scale.get();
     let mut handle_paint = Paint::default();
     handle_paint.set_color(Color::from_rgb(255, 255, 255));
     handle_paint.set_anti_alias(true);
@@ -119,12 +118,15 @@ pub fn draw_selection_handles(ctx: &mut CanvasRunnerContext, shape: &DrawingShap
     let mut border_paint = Paint::default();
     border_paint.set_color(Color::from_rgb(128, 128, 128));
     border_paint.set_style(PaintStyle::Stroke);
-    border_paint.set_stroke_width(1.0);
+    border_paint.set_stroke_width(ui_scale.get());
     border_paint.set_anti_alias(true);
 
     match shape {
-        DrawingShape::Rectangle { .. } | DrawingShape::Arrow { .. } => {
-            // 矩形和箭头显示全部8个手柄
+        DrawingShape::Rectangle { .. }
+        | DrawingShape::Arrow { .. }
+        | DrawingShape::Mosaic { .. }
+        | DrawingShape::Blur { .. } => {
+            // 矩形、箭头、马赛克和模糊显示全部8个手柄
             let handles = [
                 (left, top),        // 左上
                 (center_x, top),    // 上中
@@ -138,17 +140,17 @@ pub fn draw_selection_handles(ctx: &mut CanvasRunnerContext, shape: &DrawingShap
 
             for (x, y) in handles {
                 let rect = Rect::from_xywh(
-                    x - HANDLE_SIZE / 2.0,
-                    y - HANDLE_SIZE / 2.0,
-                    HANDLE_SIZE,
-                    HANDLE_SIZE,
+                    x - handle_size / 2.0,
+                    y - handle_size / 2.0,
+                    handle_size,
+                    handle_size,
                 );
-                ctx.canvas.draw_rect(rect, &handle_paint);
-                ctx.canvas.draw_rect(rect, &border_paint);
+                canvas.draw_rect(rect, &handle_paint);
+                canvas.draw_rect(rect, &border_paint);
             }
         }
-        DrawingShape::Circle { .. } => {
-            // 圆形只显示4个角的手柄
+        DrawingShape::Circle { .. } | DrawingShape::Text { .. } => {
+            // 圆形和文字只显示4个角的手柄
             let handles = [
                 (left, top),     // 左上
                 (right, top),    // 右上
@@ -158,13 +160,13 @@ pub fn draw_selection_handles(ctx: &mut CanvasRunnerContext, shape: &DrawingShap
 
             for (x, y) in handles {
                 let rect = Rect::from_xywh(
-                    x - HANDLE_SIZE / 2.0,
-                    y - HANDLE_SIZE / 2.0,
-                    HANDLE_SIZE,
-                    HANDLE_SIZE,
+                    x - handle_size / 2.0,
+                    y - handle_size / 2.0,
+                    handle_size,
+                    handle_size,
                 );
-                ctx.canvas.draw_rect(rect, &handle_paint);
-                ctx.canvas.draw_rect(rect, &border_paint);
+                canvas.draw_rect(rect, &handle_paint);
+                canvas.draw_rect(rect, &border_paint);
             }
         }
         DrawingShape::BrushStroke { .. } => {
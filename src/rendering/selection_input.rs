@@ -0,0 +1,70 @@
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Rect};
+
+use crate::{
+    constants::constants::{
+        PROPERTY_BAR_MARGIN, SCREEN_MARGIN, SELECTION_INPUT_HEIGHT, SELECTION_INPUT_WIDTH,
+    },
+    coords::LogicalSize,
+    shapes::text::text_font,
+    types::ui::Selection,
+};
+
+const SELECTION_INPUT_FONT_SIZE: f32 = 14.0;
+const SELECTION_INPUT_PLACEHOLDER: &str = "x, y, 宽, 高";
+
+// 选择框位置和大小的输入框：放在选择框左上角的上方，放不下时放在内侧；没有选择框时居中
+pub fn draw_selection_input(
+    canvas: &Canvas,
+    input: &str,
+    selection: Option<Selection>,
+    screen_size: LogicalSize,
+) {
+    let (x, y) = match selection {
+        Some(selection) => {
+            let (left, top, _, _) = selection.bounds();
+            let above = top - SELECTION_INPUT_HEIGHT - PROPERTY_BAR_MARGIN;
+            let y = if above >= SCREEN_MARGIN {
+                above
+            } else {
+                top + PROPERTY_BAR_MARGIN
+            };
+            (left, y)
+        }
+        None => (
+            (screen_size.width - SELECTION_INPUT_WIDTH) / 2.0,
+            (screen_size.height - SELECTION_INPUT_HEIGHT) / 2.0,
+        ),
+    };
+    let x = x
+        .min(screen_size.width - SELECTION_INPUT_WIDTH - SCREEN_MARGIN)
+        .max(SCREEN_MARGIN);
+    let rect = Rect::from_xywh(x, y, SELECTION_INPUT_WIDTH, SELECTION_INPUT_HEIGHT);
+
+    let mut background = Paint::default();
+    background.set_color(Color::from_argb(240, 30, 30, 30));
+    background.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 4.0, 4.0, &background);
+
+    let mut border = Paint::default();
+    border.set_color(Color::from_rgb(255, 255, 255));
+    border.set_style(PaintStyle::Stroke);
+    border.set_stroke_width(1.0);
+    border.set_anti_alias(true);
+    canvas.draw_round_rect(rect, 4.0, 4.0, &border);
+
+    let font = text_font(SELECTION_INPUT_FONT_SIZE);
+    let (_, metrics) = font.metrics();
+    let baseline = rect.center_y() - (metrics.ascent + metrics.descent) / 2.0;
+    let mut text_paint = Paint::default();
+    text_paint.set_anti_alias(true);
+
+    // 输入为空时显示灰色的格式提示
+    let text = if input.is_empty() {
+        text_paint.set_color(Color::from_rgb(140, 140, 140));
+        format!("|{SELECTION_INPUT_PLACEHOLDER}")
+    } else {
+        text_paint.set_color(Color::from_rgb(230, 230, 230));
+        format!("{input}|")
+    };
+    canvas.draw_str(&text, (rect.left + 8.0, baseline), &font, &text_paint);
+}
@@ -1,9 +1,53 @@
-use freya::core::custom_attributes::CanvasRunnerContext;
-use skia_safe::{Color, Paint, PaintStyle, PathEffect, Rect};
+use skia_safe::{Canvas, Color, Image as SkiaImage, Paint, PaintStyle, PathEffect, Rect};
 
-use crate::{rendering::selection::draw_selection_handles, types::drawing::DrawingShape};
+use crate::{
+    coords::ScaleFactor,
+    rendering::{
+        redact::{paint_blur, paint_mosaic},
+        selection::draw_selection_handles,
+    },
+    shapes::text::{TEXT_PADDING, caret_position, line_height, text_font},
+    types::drawing::DrawingShape,
+};
 
-pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_selected: bool) {
+pub fn draw_shape(
+    canvas: &Canvas,
+    screenshot: &SkiaImage,
+    shape: &DrawingShape,
+    is_selected: bool,
+    ui_scale: ScaleFactor,
+) {
+    paint_shape(canvas, screenshot, shape);
+
+    if !is_selected {
+        return;
+    }
+
+    match shape {
+        DrawingShape::Rectangle { .. } => {
+            draw_selection_handles(canvas, shape, ui_scale);
+        }
+        DrawingShape::Circle { .. }
+        | DrawingShape::Arrow { .. }
+        | DrawingShape::Text { .. }
+        | DrawingShape::Mosaic { .. }
+        | DrawingShape::Blur { .. } => {
+            // 绘制边界框虚线和调整大小手柄
+            draw_shape_boundary(canvas, shape);
+            draw_selection_handles(canvas, shape, ui_scale);
+        }
+        DrawingShape::BrushStroke { points, .. } => {
+            // 画笔笔迹不支持调整大小，只显示边界框
+            if points.len() >= 2 {
+                draw_shape_boundary(canvas, shape);
+            }
+        }
+    }
+}
+
+// 只绘制图形本身（不含选中状态），屏幕绘制和离屏导出共用
+// 打码类图形需要读取原始截图像素
+pub fn paint_shape(canvas: &Canvas, screenshot: &SkiaImage, shape: &DrawingShape) {
     let mut paint = Paint::default();
     paint.set_anti_alias(true);
 
@@ -24,11 +68,7 @@ pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_select
             let height = (end.1 - start.1).abs();
 
             let rect = Rect::from_xywh(left, top, width, height);
-            ctx.canvas.draw_rect(rect, &paint);
-
-            if is_selected {
-                draw_selection_handles(ctx, shape);
-            }
+            canvas.draw_rect(rect, &paint);
         }
         DrawingShape::Circle {
             center,
@@ -41,34 +81,7 @@ pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_select
             paint.set_stroke_width(*stroke_width);
 
             // 绘制圆形
-            ctx.canvas.draw_circle(*center, *radius, &paint);
-
-            // 在绘制或选中状态时显示边界框虚线
-            if is_selected {
-                // 绘制边界框虚线
-                let mut boundary_paint = Paint::default();
-                boundary_paint.set_color(Color::from_rgb(128, 128, 128));
-                boundary_paint.set_style(PaintStyle::Stroke);
-                boundary_paint.set_stroke_width(1.0);
-                boundary_paint.set_anti_alias(true);
-
-                // 添加虚线效果
-                if let Some(dash_effect) = PathEffect::dash(&[5.0, 5.0], 0.0) {
-                    boundary_paint.set_path_effect(dash_effect);
-                }
-
-                // 绘制圆的边界矩形
-                let boundary_rect = Rect::from_xywh(
-                    center.0 - radius,
-                    center.1 - radius,
-                    radius * 2.0,
-                    radius * 2.0,
-                );
-                ctx.canvas.draw_rect(boundary_rect, &boundary_paint);
-
-                // 绘制调整大小手柄
-                draw_selection_handles(ctx, shape);
-            }
+            canvas.draw_circle(*center, *radius, &paint);
         }
         DrawingShape::Arrow {
             start,
@@ -82,7 +95,7 @@ pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_select
             paint.set_stroke_cap(skia_safe::PaintCap::Round);
 
             // 绘制箭头主体
-            ctx.canvas.draw_line(*start, *end, &paint);
+            canvas.draw_line(*start, *end, &paint);
 
             // 计算箭头头部
             let dx = end.0 - start.0;
@@ -100,28 +113,8 @@ pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_select
                 end.1 - arrow_length * (angle + arrow_angle).sin(),
             );
 
-            ctx.canvas.draw_line(*end, arrow_point1, &paint);
-            ctx.canvas.draw_line(*end, arrow_point2, &paint);
-
-            if is_selected {
-                // 绘制边界框虚线
-                let mut boundary_paint = Paint::default();
-                boundary_paint.set_color(Color::from_rgb(128, 128, 128));
-                boundary_paint.set_style(PaintStyle::Stroke);
-                boundary_paint.set_stroke_width(1.0);
-                boundary_paint.set_anti_alias(true);
-
-                if let Some(dash_effect) = PathEffect::dash(&[5.0, 5.0], 0.0) {
-                    boundary_paint.set_path_effect(dash_effect);
-                }
-
-                let bounds = shape.bounds();
-                let boundary_rect =
-                    Rect::from_xywh(bounds.0, bounds.1, bounds.2 - bounds.0, bounds.3 - bounds.1);
-                ctx.canvas.draw_rect(boundary_rect, &boundary_paint);
-
-                draw_selection_handles(ctx, shape);
-            }
+            canvas.draw_line(*end, arrow_point1, &paint);
+            canvas.draw_line(*end, arrow_point2, &paint);
         }
         DrawingShape::BrushStroke {
             points,
@@ -139,32 +132,90 @@ pub fn draw_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape, is_select
             paint.set_stroke_join(skia_safe::PaintJoin::Round);
 
             for window in points.windows(2) {
-                ctx.canvas.draw_line(window[0], window[1], &paint);
+                canvas.draw_line(window[0], window[1], &paint);
+            }
+        }
+        DrawingShape::Text {
+            position,
+            content,
+            font_size,
+            color,
+            background,
+        } => {
+            // 背景框
+            if let Some(background) = background {
+                let (left, top, right, bottom) = shape.bounds();
+                let mut background_paint = Paint::default();
+                background_paint.set_color(*background);
+                background_paint.set_anti_alias(true);
+                canvas.draw_round_rect(
+                    Rect::from_ltrb(left, top, right, bottom),
+                    3.0,
+                    3.0,
+                    &background_paint,
+                );
             }
 
-            if is_selected {
-                // 绘制边界框虚线
-                let mut boundary_paint = Paint::default();
-                boundary_paint.set_color(Color::from_rgb(128, 128, 128));
-                boundary_paint.set_style(PaintStyle::Stroke);
-                boundary_paint.set_stroke_width(1.0);
-                boundary_paint.set_anti_alias(true);
-
-                if let Some(dash_effect) = PathEffect::dash(&[5.0, 5.0], 0.0) {
-                    boundary_paint.set_path_effect(dash_effect);
-                }
+            paint.set_color(*color);
+            paint.set_style(PaintStyle::Fill);
 
-                let bounds = shape.bounds();
-                let boundary_rect =
-                    Rect::from_xywh(bounds.0, bounds.1, bounds.2 - bounds.0, bounds.3 - bounds.1);
-                ctx.canvas.draw_rect(boundary_rect, &boundary_paint);
+            let font = text_font(*font_size);
+            let (_, metrics) = font.metrics();
+            let line_step = line_height(*font_size);
 
-                // 画笔笔迹不支持调整大小，只显示边界框
+            for (i, line) in content.split('\n').enumerate() {
+                let baseline = position.1 + TEXT_PADDING + i as f32 * line_step - metrics.ascent;
+                canvas.draw_str(line, (position.0 + TEXT_PADDING, baseline), &font, &paint);
             }
         }
+        DrawingShape::Mosaic { area, block_size } => {
+            paint_mosaic(canvas, screenshot, area, *block_size);
+        }
+        DrawingShape::Blur { area, sigma } => {
+            paint_blur(canvas, screenshot, area, *sigma);
+        }
     }
 }
-pub fn draw_drawing_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape) {
+
+// 绘制正在编辑的文字的光标
+pub fn draw_text_caret(canvas: &Canvas, shape: &DrawingShape, caret: usize) {
+    if let DrawingShape::Text {
+        position,
+        content,
+        font_size,
+        color,
+        ..
+    } = shape
+    {
+        let (x, y) = caret_position(*position, content, *font_size, caret);
+
+        let mut paint = Paint::default();
+        paint.set_color(*color);
+        paint.set_stroke_width(1.5);
+        paint.set_anti_alias(true);
+        canvas.draw_line((x, y), (x, y + line_height(*font_size)), &paint);
+    }
+}
+
+// 绘制选中图形的边界框虚线
+fn draw_shape_boundary(canvas: &Canvas, shape: &DrawingShape) {
+    let mut boundary_paint = Paint::default();
+    boundary_paint.set_color(Color::from_rgb(128, 128, 128));
+    boundary_paint.set_style(PaintStyle::Stroke);
+    boundary_paint.set_stroke_width(1.0);
+    boundary_paint.set_anti_alias(true);
+
+    // 添加虚线效果
+    if let Some(dash_effect) = PathEffect::dash(&[5.0, 5.0], 0.0) {
+        boundary_paint.set_path_effect(dash_effect);
+    }
+
+    let bounds = shape.bounds();
+    let boundary_rect =
+        Rect::from_xywh(bounds.0, bounds.1, bounds.2 - bounds.0, bounds.3 - bounds.1);
+    canvas.draw_rect(boundary_rect, &boundary_paint);
+}
+pub fn draw_drawing_shape(canvas: &Canvas, screenshot: &SkiaImage, shape: &DrawingShape) {
     let mut paint = Paint::default();
     paint.set_anti_alias(true);
 
@@ -180,7 +231,7 @@ pub fn draw_drawing_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape) {
             paint.set_stroke_width(*stroke_width);
 
             // 绘制圆形
-            ctx.canvas.draw_circle(*center, *radius, &paint);
+            canvas.draw_circle(*center, *radius, &paint);
 
             // 绘制边界框虚线（绘制中状态）
             let mut boundary_paint = Paint::default();
@@ -201,11 +252,11 @@ pub fn draw_drawing_shape(ctx: &mut CanvasRunnerContext, shape: &DrawingShape) {
                 radius * 2.0,
                 radius * 2.0,
             );
-            ctx.canvas.draw_rect(boundary_rect, &boundary_paint);
+            canvas.draw_rect(boundary_rect, &boundary_paint);
         }
         _ => {
             // 其他图形正常绘制
-            draw_shape(ctx, shape, false);
+            paint_shape(canvas, screenshot, shape);
         }
     }
 }
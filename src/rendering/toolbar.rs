@@ -1,20 +1,21 @@
-use freya::core::custom_attributes::CanvasRunnerContext;
-use skia_safe::{Color, Paint, PaintStyle, Rect};
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Rect};
 
 use crate::{
     constants::constants::{BUTTON_HEIGHT, BUTTON_SPACING, BUTTON_WIDTH},
-    types::ui::{Selection, Toolbar},
+    shapes::text::text_font,
+    types::ui::{Selection, SelectionRatio, Toolbar, ToolbarButton},
 };
 
+const RATIO_LABEL_FONT_SIZE: f32 = 11.0;
+
 pub fn draw_toolbar(
-    ctx: &mut CanvasRunnerContext,
+    canvas: &Canvas,
     toolbar: &Toolbar,
     _selection: &Selection,
     mouse_pos: (f32, f32),
+    ratio: SelectionRatio,
 ) {
-    let buttons = ["rectangle", "circle", "arrow", "brush", "close"];
-
-    for (i, icon_type) in buttons.iter().enumerate() {
+    for (i, button) in ToolbarButton::ALL.iter().enumerate() {
         let button_x = toolbar.x + i as f32 * (BUTTON_WIDTH + BUTTON_SPACING);
         let button_rect = Rect::from_xywh(button_x, toolbar.y, BUTTON_WIDTH, BUTTON_HEIGHT);
 
@@ -47,29 +48,48 @@ pub fn draw_toolbar(
         border_paint.set_anti_alias(true);
 
         // 绘制按钮背景
-        ctx.canvas
-            .draw_round_rect(button_rect, 4.0, 4.0, &button_paint);
-        ctx.canvas
-            .draw_round_rect(button_rect, 4.0, 4.0, &border_paint);
-
-        // 绘制图标
-        draw_icon(
-            ctx,
-            icon_type,
-            button_x + BUTTON_WIDTH / 2.0,
-            toolbar.y + BUTTON_HEIGHT / 2.0,
-            is_hovered,
-        );
+        canvas.draw_round_rect(button_rect, 4.0, 4.0, &button_paint);
+        canvas.draw_round_rect(button_rect, 4.0, 4.0, &border_paint);
+
+        // 比例按钮在限制比例时显示当前比例，否则绘制图标
+        let center_x = button_x + BUTTON_WIDTH / 2.0;
+        let center_y = toolbar.y + BUTTON_HEIGHT / 2.0;
+        match (button, ratio) {
+            (ToolbarButton::Ratio, SelectionRatio::Ratio(..)) => {
+                draw_label(canvas, &ratio.to_string(), center_x, center_y, is_hovered)
+            }
+            (ToolbarButton::Ratio, SelectionRatio::Fixed(..)) => {
+                draw_label(canvas, "固定", center_x, center_y, is_hovered)
+            }
+            _ => draw_icon(canvas, button.icon(), center_x, center_y, is_hovered),
+        }
     }
 }
 
-fn draw_icon(
-    ctx: &mut CanvasRunnerContext,
-    icon_type: &str,
-    center_x: f32,
-    center_y: f32,
-    is_hovered: bool,
-) {
+fn draw_label(canvas: &Canvas, label: &str, center_x: f32, center_y: f32, is_hovered: bool) {
+    let mut paint = Paint::default();
+    if is_hovered {
+        paint.set_color(Color::from_rgb(255, 255, 255));
+    } else {
+        paint.set_color(Color::from_rgb(200, 200, 200));
+    }
+    paint.set_anti_alias(true);
+
+    let font = text_font(RATIO_LABEL_FONT_SIZE);
+    let (_, metrics) = font.metrics();
+    let (width, _) = font.measure_str(label, None);
+    canvas.draw_str(
+        label,
+        (
+            center_x - width / 2.0,
+            center_y - (metrics.ascent + metrics.descent) / 2.0,
+        ),
+        &font,
+        &paint,
+    );
+}
+
+fn draw_icon(canvas: &Canvas, icon_type: &str, center_x: f32, center_y: f32, is_hovered: bool) {
     let mut paint = Paint::default();
     // Hover 时图标颜色更亮
     if is_hovered {
@@ -92,12 +112,12 @@ fn draw_icon(
                 size * 2.0,
                 size * 1.4,
             );
-            ctx.canvas.draw_rect(rect, &paint);
+            canvas.draw_rect(rect, &paint);
         }
         "circle" => {
             // 画圆图标
             paint.set_style(PaintStyle::Stroke);
-            ctx.canvas.draw_circle((center_x, center_y), size, &paint);
+            canvas.draw_circle((center_x, center_y), size, &paint);
         }
         "arrow" => {
             // 箭头图标
@@ -105,19 +125,19 @@ fn draw_icon(
             paint.set_stroke_cap(skia_safe::PaintCap::Round);
 
             // 箭头主体
-            ctx.canvas.draw_line(
+            canvas.draw_line(
                 (center_x - size, center_y + size * 0.5),
                 (center_x + size, center_y - size * 0.5),
                 &paint,
             );
 
             // 箭头头部
-            ctx.canvas.draw_line(
+            canvas.draw_line(
                 (center_x + size, center_y - size * 0.5),
                 (center_x + size * 0.3, center_y - size * 0.8),
                 &paint,
             );
-            ctx.canvas.draw_line(
+            canvas.draw_line(
                 (center_x + size, center_y - size * 0.5),
                 (center_x + size * 0.3, center_y - size * 0.2),
                 &paint,
@@ -134,7 +154,7 @@ fn draw_icon(
                 size * 0.6,
                 size * 1.5,
             );
-            ctx.canvas.draw_rect(brush_rect, &paint);
+            canvas.draw_rect(brush_rect, &paint);
 
             // 画笔头
             if is_hovered {
@@ -148,18 +168,114 @@ fn draw_icon(
                 size * 0.4,
                 size * 0.5,
             );
-            ctx.canvas.draw_rect(brush_tip, &paint);
+            canvas.draw_rect(brush_tip, &paint);
+        }
+        "text" => {
+            // 文字图标（T）
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_cap(skia_safe::PaintCap::Round);
+            canvas.draw_line(
+                (center_x - size * 0.8, center_y - size * 0.8),
+                (center_x + size * 0.8, center_y - size * 0.8),
+                &paint,
+            );
+            canvas.draw_line(
+                (center_x, center_y - size * 0.8),
+                (center_x, center_y + size * 0.9),
+                &paint,
+            );
+        }
+        "mosaic" => {
+            // 马赛克图标（棋盘格）
+            paint.set_style(PaintStyle::Fill);
+            let cell = size * 0.6;
+            for row in 0..3 {
+                for column in 0..3 {
+                    if (row + column) % 2 == 0 {
+                        let rect = Rect::from_xywh(
+                            center_x - cell * 1.5 + column as f32 * cell,
+                            center_y - cell * 1.5 + row as f32 * cell,
+                            cell,
+                            cell,
+                        );
+                        canvas.draw_rect(rect, &paint);
+                    }
+                }
+            }
+        }
+        "blur" => {
+            // 模糊图标（由实到虚的同心圆）
+            paint.set_style(PaintStyle::Stroke);
+            canvas.draw_circle((center_x, center_y), size * 0.35, &paint);
+            paint.set_stroke_width(1.0);
+            paint.set_alpha(paint.alpha() / 2);
+            canvas.draw_circle((center_x, center_y), size * 0.7, &paint);
+            paint.set_alpha(paint.alpha() / 2);
+            canvas.draw_circle((center_x, center_y), size, &paint);
+        }
+        "ratio" => {
+            // 比例图标（四个角标）
+            paint.set_style(PaintStyle::Stroke);
+            let (half_w, half_h, arm) = (size, size * 0.7, size * 0.5);
+            for (x, y, dx, dy) in [
+                (center_x - half_w, center_y - half_h, 1.0, 1.0),
+                (center_x + half_w, center_y - half_h, -1.0, 1.0),
+                (center_x + half_w, center_y + half_h, -1.0, -1.0),
+                (center_x - half_w, center_y + half_h, 1.0, -1.0),
+            ] {
+                canvas.draw_line((x, y), (x + dx * arm, y), &paint);
+                canvas.draw_line((x, y), (x, y + dy * arm), &paint);
+            }
+        }
+        "save" => {
+            // 保存图标（向下箭头 + 托盘）
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_cap(skia_safe::PaintCap::Round);
+
+            // 箭头
+            canvas.draw_line(
+                (center_x, center_y - size),
+                (center_x, center_y + size * 0.3),
+                &paint,
+            );
+            canvas.draw_line(
+                (center_x - size * 0.5, center_y - size * 0.2),
+                (center_x, center_y + size * 0.3),
+                &paint,
+            );
+            canvas.draw_line(
+                (center_x + size * 0.5, center_y - size * 0.2),
+                (center_x, center_y + size * 0.3),
+                &paint,
+            );
+
+            // 托盘
+            canvas.draw_line(
+                (center_x - size, center_y + size * 0.4),
+                (center_x - size, center_y + size),
+                &paint,
+            );
+            canvas.draw_line(
+                (center_x - size, center_y + size),
+                (center_x + size, center_y + size),
+                &paint,
+            );
+            canvas.draw_line(
+                (center_x + size, center_y + size),
+                (center_x + size, center_y + size * 0.4),
+                &paint,
+            );
         }
         "close" => {
             // 关闭图标（X）
             paint.set_style(PaintStyle::Stroke);
             paint.set_stroke_cap(skia_safe::PaintCap::Round);
-            ctx.canvas.draw_line(
+            canvas.draw_line(
                 (center_x - size * 0.7, center_y - size * 0.7),
                 (center_x + size * 0.7, center_y + size * 0.7),
                 &paint,
             );
-            ctx.canvas.draw_line(
+            canvas.draw_line(
                 (center_x + size * 0.7, center_y - size * 0.7),
                 (center_x - size * 0.7, center_y + size * 0.7),
                 &paint,
@@ -1,33 +1,36 @@
+pub mod redact;
+pub mod style;
+pub mod text;
+
 use crate::{
     constants::constants::HANDLE_DETECT_SIZE,
+    coords::ScaleFactor,
     types::{app_state::ResizeHandle, drawing::DrawingShape},
 };
 
+use text::{MIN_FONT_SIZE, TEXT_PADDING, text_size};
+
 impl DrawingShape {
     // 添加调整大小手柄检测
-    pub fn get_resize_handle(&self, x: f32, y: f32) -> Option<ResizeHandle> {
+    pub fn get_resize_handle(&self, x: f32, y: f32, ui_scale: ScaleFactor) -> Option<ResizeHandle> {
+        let detect = HANDLE_DETECT_SIZE * ui_scale.get();
         let (left, top, right, bottom) = self.bounds();
         let center_x = (left + right) / 2.0;
         let center_y = (top + bottom) / 2.0;
 
         match self {
-            DrawingShape::Circle { .. } => {
-                // 圆形只检查四个角手柄
-                if (x - left).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE {
+            DrawingShape::Circle { .. } | DrawingShape::Text { .. } => {
+                // 圆形和文字只检查四个角手柄（等比缩放）
+                if (x - left).abs() <= detect && (y - top).abs() <= detect {
                     return Some(ResizeHandle::TopLeft);
                 }
-                if (x - right).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - right).abs() <= detect && (y - top).abs() <= detect {
                     return Some(ResizeHandle::TopRight);
                 }
-                if (x - right).abs() <= HANDLE_DETECT_SIZE
-                    && (y - bottom).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - right).abs() <= detect && (y - bottom).abs() <= detect {
                     return Some(ResizeHandle::BottomRight);
                 }
-                if (x - left).abs() <= HANDLE_DETECT_SIZE
-                    && (y - bottom).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - left).abs() <= detect && (y - bottom).abs() <= detect {
                     return Some(ResizeHandle::BottomLeft);
                 }
                 None
@@ -35,66 +38,45 @@ impl DrawingShape {
             _ => {
                 // 其他图形保持原有的全部8个手柄检测
                 // 检查角手柄（优先级最高）
-                if (x - left).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE {
+                if (x - left).abs() <= detect && (y - top).abs() <= detect {
                     return Some(ResizeHandle::TopLeft);
                 }
-                if (x - right).abs() <= HANDLE_DETECT_SIZE && (y - top).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - right).abs() <= detect && (y - top).abs() <= detect {
                     return Some(ResizeHandle::TopRight);
                 }
-                if (x - right).abs() <= HANDLE_DETECT_SIZE
-                    && (y - bottom).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - right).abs() <= detect && (y - bottom).abs() <= detect {
                     return Some(ResizeHandle::BottomRight);
                 }
-                if (x - left).abs() <= HANDLE_DETECT_SIZE
-                    && (y - bottom).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - left).abs() <= detect && (y - bottom).abs() <= detect {
                     return Some(ResizeHandle::BottomLeft);
                 }
 
                 // 检查边手柄（确保不与角手柄重叠）
                 // 上边中间
-                if (x - center_x).abs() <= HANDLE_DETECT_SIZE
-                    && (y - top).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - center_x).abs() <= detect && (y - top).abs() <= detect {
                     // 确保不在角手柄范围内
-                    if (x - left).abs() > HANDLE_DETECT_SIZE
-                        && (x - right).abs() > HANDLE_DETECT_SIZE
-                    {
+                    if (x - left).abs() > detect && (x - right).abs() > detect {
                         return Some(ResizeHandle::Top);
                     }
                 }
                 // 右边中间
-                if (x - right).abs() <= HANDLE_DETECT_SIZE
-                    && (y - center_y).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - right).abs() <= detect && (y - center_y).abs() <= detect {
                     // 确保不在角手柄范围内
-                    if (y - top).abs() > HANDLE_DETECT_SIZE
-                        && (y - bottom).abs() > HANDLE_DETECT_SIZE
-                    {
+                    if (y - top).abs() > detect && (y - bottom).abs() > detect {
                         return Some(ResizeHandle::Right);
                     }
                 }
                 // 下边中间
-                if (x - center_x).abs() <= HANDLE_DETECT_SIZE
-                    && (y - bottom).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - center_x).abs() <= detect && (y - bottom).abs() <= detect {
                     // 确保不在角手柄范围内
-                    if (x - left).abs() > HANDLE_DETECT_SIZE
-                        && (x - right).abs() > HANDLE_DETECT_SIZE
-                    {
+                    if (x - left).abs() > detect && (x - right).abs() > detect {
                         return Some(ResizeHandle::Bottom);
                     }
                 }
                 // 左边中间
-                if (x - left).abs() <= HANDLE_DETECT_SIZE
-                    && (y - center_y).abs() <= HANDLE_DETECT_SIZE
-                {
+                if (x - left).abs() <= detect && (y - center_y).abs() <= detect {
                     // 确保不在角手柄范围内
-                    if (y - top).abs() > HANDLE_DETECT_SIZE
-                        && (y - bottom).abs() > HANDLE_DETECT_SIZE
-                    {
+                    if (y - top).abs() > detect && (y - bottom).abs() > detect {
                         return Some(ResizeHandle::Left);
                     }
                 }
@@ -109,8 +91,8 @@ impl DrawingShape {
         let (left, top, right, bottom) = self.bounds();
 
         match self {
-            DrawingShape::Circle { .. } => {
-                // 圆形只有四个角手柄，锚点是对角
+            DrawingShape::Circle { .. } | DrawingShape::Text { .. } => {
+                // 圆形和文字只有四个角手柄，锚点是对角
                 match handle {
                     ResizeHandle::TopLeft => (right, bottom),
                     ResizeHandle::TopRight => (left, bottom),
@@ -143,6 +125,7 @@ impl DrawingShape {
     ) {
         let (new_left, new_top, new_right, new_bottom) = new_bounds;
         let (sel_left, sel_top, sel_right, sel_bottom) = selection_bounds;
+        let old_bounds = self.bounds();
 
         // 限制在选择区域内
         let constrained_left = new_left.max(sel_left).min(sel_right - 10.0);
@@ -210,6 +193,60 @@ impl DrawingShape {
             DrawingShape::BrushStroke { .. } => {
                 // 画笔笔迹不支持调整大小
             }
+            DrawingShape::Text {
+                position,
+                font_size,
+                ..
+            } => {
+                let (left, top, right, bottom) = old_bounds;
+                let old_height = bottom - top;
+                let target_height = constrained_bottom - constrained_top;
+                if old_height <= 0.0 || target_height <= 0.0 {
+                    return;
+                }
+
+                // 按高度等比缩放字号，宽度随文字内容变化
+                let new_font_size = (*font_size * target_height / old_height).max(MIN_FONT_SIZE);
+                let scale = new_font_size / *font_size;
+                let new_width = (right - left) * scale;
+                let new_height = old_height * scale;
+
+                // 保持被拖拽手柄对面的角不动
+                let anchored_right =
+                    (constrained_right - right).abs() < (constrained_left - left).abs();
+                let anchored_bottom =
+                    (constrained_bottom - bottom).abs() < (constrained_top - top).abs();
+                let new_left = if anchored_right {
+                    right - new_width
+                } else {
+                    constrained_left
+                };
+                let new_top = if anchored_bottom {
+                    bottom - new_height
+                } else {
+                    constrained_top
+                };
+
+                // 放大后超出选择区域时放弃本次调整
+                if new_left < sel_left
+                    || new_top < sel_top
+                    || new_left + new_width > sel_right
+                    || new_top + new_height > sel_bottom
+                {
+                    return;
+                }
+
+                *font_size = new_font_size;
+                *position = (new_left, new_top);
+            }
+            DrawingShape::Mosaic { area, .. } | DrawingShape::Blur { area, .. } => {
+                area.set_bounds((
+                    constrained_left,
+                    constrained_top,
+                    constrained_right,
+                    constrained_bottom,
+                ));
+            }
         }
     }
 
@@ -280,6 +317,21 @@ impl DrawingShape {
                 }
                 (min_x, min_y, max_x, max_y)
             }
+            DrawingShape::Text {
+                position,
+                content,
+                font_size,
+                ..
+            } => {
+                let (width, height) = text_size(content, *font_size);
+                (
+                    position.0,
+                    position.1,
+                    position.0 + width + TEXT_PADDING * 2.0,
+                    position.1 + height + TEXT_PADDING * 2.0,
+                )
+            }
+            DrawingShape::Mosaic { area, .. } | DrawingShape::Blur { area, .. } => area.bounds(),
         }
     }
 
@@ -300,10 +352,13 @@ impl DrawingShape {
                 let (left, top, right, bottom) = self.bounds();
                 x >= left && x <= right && y >= top && y <= bottom
             }
-            DrawingShape::BrushStroke { .. } => {
+            DrawingShape::BrushStroke { .. } | DrawingShape::Text { .. } => {
                 let (left, top, right, bottom) = self.bounds();
                 x >= left && x <= right && y >= top && y <= bottom
             }
+            DrawingShape::Mosaic { area, .. } | DrawingShape::Blur { area, .. } => {
+                area.contains_point(x, y)
+            }
         }
     }
 
@@ -331,6 +386,17 @@ impl DrawingShape {
                     point.1 += dy;
                 }
             }
+            DrawingShape::Text { position, .. } => {
+                position.0 += dx;
+                position.1 += dy;
+            }
+            DrawingShape::Mosaic { area, .. } | DrawingShape::Blur { area, .. } => {
+                area.translate(dx, dy)
+            }
         }
     }
+
+    pub fn is_empty_text(&self) -> bool {
+        matches!(self, DrawingShape::Text { content, .. } if content.is_empty())
+    }
 }
@@ -0,0 +1,148 @@
+use crate::types::drawing::{RedactArea, RedactAreaKind};
+
+pub const DEFAULT_MOSAIC_BLOCK_SIZE: f32 = 12.0;
+pub const MIN_MOSAIC_BLOCK_SIZE: f32 = 4.0;
+pub const MAX_MOSAIC_BLOCK_SIZE: f32 = 48.0;
+pub const DEFAULT_REDACT_BRUSH_WIDTH: f32 = 24.0;
+pub const DEFAULT_BLUR_SIGMA: f32 = 12.0;
+// 过小的模糊半径仍可能被还原出原始内容
+pub const MIN_BLUR_SIGMA: f32 = 4.0;
+pub const MAX_BLUR_SIGMA: f32 = 40.0;
+
+fn points_bounds(points: &[(f32, f32)]) -> (f32, f32, f32, f32) {
+    if points.is_empty() {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+    points.iter().fold(
+        (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+        |(left, top, right, bottom), &(x, y)| {
+            (left.min(x), top.min(y), right.max(x), bottom.max(y))
+        },
+    )
+}
+
+impl RedactArea {
+    pub fn new(kind: RedactAreaKind, pos: (f32, f32), brush_width: f32) -> Self {
+        match kind {
+            RedactAreaKind::Rectangle => RedactArea::Rectangle {
+                start: pos,
+                end: pos,
+            },
+            RedactAreaKind::Ellipse => RedactArea::Ellipse {
+                start: pos,
+                end: pos,
+            },
+            RedactAreaKind::Brush => RedactArea::Brush {
+                points: vec![pos],
+                width: brush_width,
+            },
+        }
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match self {
+            RedactArea::Rectangle { start, end } | RedactArea::Ellipse { start, end } => (
+                start.0.min(end.0),
+                start.1.min(end.1),
+                start.0.max(end.0),
+                start.1.max(end.1),
+            ),
+            RedactArea::Brush { points, width } => {
+                // 边界包含笔刷宽度
+                let (left, top, right, bottom) = points_bounds(points);
+                let half = width / 2.0;
+                (left - half, top - half, right + half, bottom + half)
+            }
+        }
+    }
+
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (left, top, right, bottom) = self.bounds();
+        match self {
+            RedactArea::Ellipse { .. } => {
+                let rx = (right - left) / 2.0;
+                let ry = (bottom - top) / 2.0;
+                if rx <= 0.0 || ry <= 0.0 {
+                    return false;
+                }
+                let dx = (x - (left + rx)) / rx;
+                let dy = (y - (top + ry)) / ry;
+                dx * dx + dy * dy <= 1.0
+            }
+            _ => x >= left && x <= right && y >= top && y <= bottom,
+        }
+    }
+
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        match self {
+            RedactArea::Rectangle { start, end } | RedactArea::Ellipse { start, end } => {
+                start.0 += dx;
+                start.1 += dy;
+                end.0 += dx;
+                end.1 += dy;
+            }
+            RedactArea::Brush { points, .. } => {
+                for point in points {
+                    point.0 += dx;
+                    point.1 += dy;
+                }
+            }
+        }
+    }
+
+    // 绘制过程中把区域延伸到鼠标位置
+    pub fn extend_to(&mut self, pos: (f32, f32)) {
+        match self {
+            RedactArea::Rectangle { end, .. } | RedactArea::Ellipse { end, .. } => {
+                *end = pos;
+            }
+            RedactArea::Brush { points, .. } => {
+                let should_add_point = points.last().is_none_or(|last| {
+                    let dx = pos.0 - last.0;
+                    let dy = pos.1 - last.1;
+                    (dx * dx + dy * dy).sqrt() > 2.0
+                });
+                if should_add_point {
+                    points.push(pos);
+                }
+            }
+        }
+    }
+
+    // 调整到新的边界，笔刷区域按比例缩放所有点
+    pub fn set_bounds(&mut self, new_bounds: (f32, f32, f32, f32)) {
+        let (new_left, new_top, new_right, new_bottom) = new_bounds;
+        match self {
+            RedactArea::Rectangle { start, end } | RedactArea::Ellipse { start, end } => {
+                *start = (new_left, new_top);
+                *end = (new_right, new_bottom);
+            }
+            RedactArea::Brush { points, width } => {
+                let half = *width / 2.0;
+                let (left, top, right, bottom) = points_bounds(points);
+                let target = (
+                    new_left + half,
+                    new_top + half,
+                    (new_right - half).max(new_left + half),
+                    (new_bottom - half).max(new_top + half),
+                );
+
+                let scale_x = if right > left {
+                    (target.2 - target.0) / (right - left)
+                } else {
+                    1.0
+                };
+                let scale_y = if bottom > top {
+                    (target.3 - target.1) / (bottom - top)
+                } else {
+                    1.0
+                };
+
+                for point in points {
+                    point.0 = target.0 + (point.0 - left) * scale_x;
+                    point.1 = target.1 + (point.1 - top) * scale_y;
+                }
+            }
+        }
+    }
+}
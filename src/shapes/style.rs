@@ -0,0 +1,175 @@
+use skia_safe::Color;
+
+use crate::types::drawing::{DrawingShape, DrawingTool, ShapeStyle};
+
+// 属性栏中的预设颜色
+pub const PRESET_COLORS: [Color; 8] = [
+    Color::from_rgb(255, 59, 48),
+    Color::from_rgb(255, 149, 0),
+    Color::from_rgb(255, 214, 10),
+    Color::from_rgb(52, 199, 89),
+    Color::from_rgb(0, 122, 255),
+    Color::from_rgb(175, 82, 222),
+    Color::from_rgb(0, 0, 0),
+    Color::from_rgb(255, 255, 255),
+];
+
+// 线宽档位（截图像素），高分屏上 1 像素的线几乎看不清
+pub const STROKE_WIDTHS: [f32; 4] = [2.0, 4.0, 8.0, 12.0];
+pub const DEFAULT_STROKE_WIDTH: f32 = 4.0;
+
+impl Default for ShapeStyle {
+    fn default() -> Self {
+        Self {
+            color: PRESET_COLORS[0],
+            stroke_width: DEFAULT_STROKE_WIDTH,
+        }
+    }
+}
+
+// 解析十六进制颜色：RGB、RRGGBB 或 RRGGBBAA，可带 # 前缀
+pub fn parse_hex_color(input: &str) -> Option<Color> {
+    let input = input.trim();
+    let hex = input.strip_prefix('#').unwrap_or(input);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+
+    match hex.len() {
+        3 => {
+            let value = u16::from_str_radix(hex, 16).ok()?;
+            let expand = |shift: u16| ((value >> shift) & 0xf) as u8 * 17;
+            Some(Color::from_rgb(expand(8), expand(4), expand(0)))
+        }
+        6 => Some(Color::from_rgb(channel(0)?, channel(1)?, channel(2)?)),
+        8 => Some(Color::from_argb(
+            channel(3)?,
+            channel(0)?,
+            channel(1)?,
+            channel(2)?,
+        )),
+        _ => None,
+    }
+}
+
+impl DrawingTool {
+    // 该工具新建的图形是否使用颜色和线宽
+    pub fn uses_style(&self) -> bool {
+        matches!(
+            self,
+            DrawingTool::Rectangle
+                | DrawingTool::Circle
+                | DrawingTool::Arrow
+                | DrawingTool::Brush
+                | DrawingTool::Text
+        )
+    }
+}
+
+// 选中了绘图工具或可着色的图形时显示属性栏
+pub fn shows_property_bar(tool: DrawingTool, selected: Option<&DrawingShape>) -> bool {
+    tool.uses_style() || selected.is_some_and(|shape| shape.color().is_some())
+}
+
+impl DrawingShape {
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            DrawingShape::Rectangle { color, .. }
+            | DrawingShape::Circle { color, .. }
+            | DrawingShape::Arrow { color, .. }
+            | DrawingShape::BrushStroke { color, .. }
+            | DrawingShape::Text { color, .. } => Some(*color),
+            DrawingShape::Mosaic { .. } | DrawingShape::Blur { .. } => None,
+        }
+    }
+
+    pub fn stroke_width(&self) -> Option<f32> {
+        match self {
+            DrawingShape::Rectangle { stroke_width, .. }
+            | DrawingShape::Circle { stroke_width, .. }
+            | DrawingShape::Arrow { stroke_width, .. }
+            | DrawingShape::BrushStroke { stroke_width, .. } => Some(*stroke_width),
+            DrawingShape::Text { .. } | DrawingShape::Mosaic { .. } | DrawingShape::Blur { .. } => {
+                None
+            }
+        }
+    }
+
+    pub fn set_color(&mut self, new_color: Color) {
+        match self {
+            DrawingShape::Rectangle { color, .. }
+            | DrawingShape::Circle { color, .. }
+            | DrawingShape::Arrow { color, .. }
+            | DrawingShape::BrushStroke { color, .. }
+            | DrawingShape::Text { color, .. } => *color = new_color,
+            DrawingShape::Mosaic { .. } | DrawingShape::Blur { .. } => {}
+        }
+    }
+
+    pub fn set_stroke_width(&mut self, width: f32) {
+        match self {
+            DrawingShape::Rectangle { stroke_width, .. }
+            | DrawingShape::Circle { stroke_width, .. }
+            | DrawingShape::Arrow { stroke_width, .. }
+            | DrawingShape::BrushStroke { stroke_width, .. } => *stroke_width = width,
+            DrawingShape::Text { .. } | DrawingShape::Mosaic { .. } | DrawingShape::Blur { .. } => {
+            }
+        }
+    }
+
+    // 属性栏中显示的样式：图形没有的属性沿用默认值
+    pub fn style_or(&self, default: ShapeStyle) -> ShapeStyle {
+        ShapeStyle {
+            color: self.color().unwrap_or(default.color),
+            stroke_width: self.stroke_width().unwrap_or(default.stroke_width),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        let cases = [
+            ("#fff", Color::from_rgb(255, 255, 255)),
+            ("#f80", Color::from_rgb(255, 136, 0)),
+            ("#FF3B30", Color::from_rgb(255, 59, 48)),
+            ("#ff3b30", Color::from_rgb(255, 59, 48)),
+            ("#ff3b3080", Color::from_argb(128, 255, 59, 48)),
+            ("#00000000", Color::from_argb(0, 0, 0, 0)),
+            // 可以不带 #，前后的空白会被忽略
+            ("0a0", Color::from_rgb(0, 170, 0)),
+            ("007aff", Color::from_rgb(0, 122, 255)),
+            (" #007aff\n", Color::from_rgb(0, 122, 255)),
+        ];
+        for (input, color) in cases {
+            assert_eq!(parse_hex_color(input), Some(color), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_hex_colors() {
+        let cases = [
+            "",
+            "#",
+            "##fff",
+            "#ggg",
+            "#12345g",
+            "#ff 000",
+            "#+ff",
+            "#ffé",
+            "#ff",
+            "#ffff",
+            "#fffff",
+            "#fffffff",
+            "#fffffffff",
+            "red",
+        ];
+        for input in cases {
+            assert_eq!(parse_hex_color(input), None, "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::sync::OnceLock;
+
+use skia_safe::{Color, Font, FontMgr, FontStyle, Typeface};
+
+use crate::types::drawing::{DrawingShape, TextEditing};
+
+pub const TEXT_PADDING: f32 = 4.0;
+pub const TEXT_LINE_SPACING: f32 = 1.25;
+pub const MIN_FONT_SIZE: f32 = 8.0;
+pub const DEFAULT_FONT_SIZE: f32 = 20.0;
+pub const TEXT_BACKGROUND_COLOR: Color = Color::from_argb(200, 255, 255, 255);
+
+fn default_typeface() -> Option<Typeface> {
+    static TYPEFACE: OnceLock<Option<Typeface>> = OnceLock::new();
+    TYPEFACE
+        .get_or_init(|| FontMgr::new().legacy_make_typeface(None, FontStyle::default()))
+        .clone()
+}
+
+pub fn text_font(font_size: f32) -> Font {
+    match default_typeface() {
+        Some(typeface) => Font::from_typeface(typeface, font_size),
+        None => {
+            let mut font = Font::default();
+            font.set_size(font_size);
+            font
+        }
+    }
+}
+
+pub fn line_height(font_size: f32) -> f32 {
+    font_size * TEXT_LINE_SPACING
+}
+
+// 文本内容（不含内边距）的宽高，空文本保留一个光标的宽度
+pub fn text_size(content: &str, font_size: f32) -> (f32, f32) {
+    let font = text_font(font_size);
+    let width = content
+        .split('\n')
+        .map(|line| font.measure_str(line, None).0)
+        .fold(font_size * 0.5, f32::max);
+    let lines = content.split('\n').count() as f32;
+    (width, lines * line_height(font_size))
+}
+
+// 光标所在行的顶部坐标
+pub fn caret_position(
+    position: (f32, f32),
+    content: &str,
+    font_size: f32,
+    caret: usize,
+) -> (f32, f32) {
+    let before_caret: String = content.chars().take(caret).collect();
+    let line_index = before_caret.matches('\n').count();
+    let current_line = before_caret.rsplit('\n').next().unwrap_or_default();
+    let x = text_font(font_size).measure_str(current_line, None).0;
+
+    (
+        position.0 + TEXT_PADDING + x,
+        position.1 + TEXT_PADDING + line_index as f32 * line_height(font_size),
+    )
+}
+
+fn byte_index(content: &str, caret: usize) -> usize {
+    content
+        .char_indices()
+        .nth(caret)
+        .map(|(i, _)| i)
+        .unwrap_or(content.len())
+}
+
+impl TextEditing {
+    pub fn insert(&mut self, shape: &mut DrawingShape, text: &str) {
+        if let DrawingShape::Text { content, .. } = shape {
+            content.insert_str(byte_index(content, self.caret), text);
+            self.caret += text.chars().count();
+        }
+    }
+
+    pub fn backspace(&mut self, shape: &mut DrawingShape) {
+        if let DrawingShape::Text { content, .. } = shape {
+            if self.caret > 0 {
+                self.caret -= 1;
+                content.remove(byte_index(content, self.caret));
+            }
+        }
+    }
+
+    pub fn delete(&mut self, shape: &mut DrawingShape) {
+        if let DrawingShape::Text { content, .. } = shape {
+            if self.caret < content.chars().count() {
+                content.remove(byte_index(content, self.caret));
+            }
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.caret = self.caret.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self, shape: &DrawingShape) {
+        if let DrawingShape::Text { content, .. } = shape {
+            self.caret = (self.caret + 1).min(content.chars().count());
+        }
+    }
+
+    // 移动到当前行行首
+    pub fn move_line_start(&mut self, shape: &DrawingShape) {
+        if let DrawingShape::Text { content, .. } = shape {
+            let chars: Vec<char> = content.chars().collect();
+            while self.caret > 0 && chars[self.caret - 1] != '\n' {
+                self.caret -= 1;
+            }
+        }
+    }
+
+    // 移动到当前行行尾
+    pub fn move_line_end(&mut self, shape: &DrawingShape) {
+        if let DrawingShape::Text { content, .. } = shape {
+            let chars: Vec<char> = content.chars().collect();
+            while self.caret < chars.len() && chars[self.caret] != '\n' {
+                self.caret += 1;
+            }
+        }
+    }
+
+    pub fn move_up(&mut self, shape: &DrawingShape) {
+        self.move_line(shape, false);
+    }
+
+    pub fn move_down(&mut self, shape: &DrawingShape) {
+        self.move_line(shape, true);
+    }
+
+    // 移动到上一行或下一行的同一列（按字符计），目标行较短时停在行尾
+    // 已经在第一行或最后一行时不移动
+    fn move_line(&mut self, shape: &DrawingShape, down: bool) {
+        if let DrawingShape::Text { content, .. } = shape {
+            let chars: Vec<char> = content.chars().collect();
+            let caret = self.caret.min(chars.len());
+            let line_start = |end: usize| {
+                chars[..end]
+                    .iter()
+                    .rposition(|c| *c == '\n')
+                    .map_or(0, |i| i + 1)
+            };
+            let current_start = line_start(caret);
+
+            let target_start = if down {
+                match chars[caret..].iter().position(|c| *c == '\n') {
+                    Some(i) => caret + i + 1,
+                    None => return,
+                }
+            } else if current_start == 0 {
+                return;
+            } else {
+                line_start(current_start - 1)
+            };
+            let target_len = chars[target_start..]
+                .iter()
+                .position(|c| *c == '\n')
+                .unwrap_or(chars.len() - target_start);
+            self.caret = target_start + (caret - current_start).min(target_len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(content: &str) -> DrawingShape {
+        DrawingShape::Text {
+            position: (100.0, 100.0),
+            content: content.into(),
+            font_size: DEFAULT_FONT_SIZE,
+            color: Color::RED,
+            background: None,
+        }
+    }
+
+    fn content(shape: &DrawingShape) -> &str {
+        match shape {
+            DrawingShape::Text { content, .. } => content,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    fn editing_at(caret: usize) -> TextEditing {
+        TextEditing {
+            index: 0,
+            caret,
+            before: None,
+        }
+    }
+
+    #[test]
+    fn insert_counts_characters() {
+        let mut shape = text("");
+        let mut editing = editing_at(0);
+        editing.insert(&mut shape, "截图");
+        assert_eq!(editing.caret, 2);
+        editing.insert(&mut shape, "é");
+        editing.move_left();
+        editing.move_left();
+        editing.insert(&mut shape, "ab\n");
+        assert_eq!(content(&shape), "截ab\n图é");
+        assert_eq!(editing.caret, 4);
+    }
+
+    #[test]
+    fn backspace_and_delete_remove_whole_characters() {
+        let mut shape = text("a截图é");
+        let mut editing = editing_at(2);
+        editing.backspace(&mut shape);
+        assert_eq!((content(&shape), editing.caret), ("a图é", 1));
+        editing.delete(&mut shape);
+        assert_eq!((content(&shape), editing.caret), ("aé", 1));
+
+        // 开头退格、末尾删除都不做任何事
+        let mut editing = editing_at(0);
+        editing.backspace(&mut shape);
+        let mut end = editing_at(2);
+        end.delete(&mut shape);
+        assert_eq!(content(&shape), "aé");
+    }
+
+    #[test]
+    fn caret_stays_inside_text() {
+        let shape = text("截图");
+        let mut editing = editing_at(0);
+        editing.move_left();
+        assert_eq!(editing.caret, 0);
+        for _ in 0..3 {
+            editing.move_right(&shape);
+        }
+        assert_eq!(editing.caret, 2);
+    }
+
+    #[test]
+    fn home_and_end_stay_on_line() {
+        let shape = text("ab\n截图中\nc");
+        let mut editing = editing_at(4);
+        editing.move_line_start(&shape);
+        assert_eq!(editing.caret, 3);
+        editing.move_line_end(&shape);
+        assert_eq!(editing.caret, 6);
+    }
+
+    #[test]
+    fn up_and_down_keep_column() {
+        // 第 0 行 "截图中文"、第 1 行 "ab"、第 2 行 "é文字x"
+        let shape = text("截图中文\nab\né文字x");
+        let mut editing = editing_at(3);
+
+        // 下一行较短，停在行尾
+        editing.move_down(&shape);
+        assert_eq!(editing.caret, 7);
+        editing.move_down(&shape);
+        assert_eq!(editing.caret, 10);
+        // 已经在最后一行
+        editing.move_down(&shape);
+        assert_eq!(editing.caret, 10);
+
+        editing.move_up(&shape);
+        assert_eq!(editing.caret, 7);
+        editing.move_up(&shape);
+        assert_eq!(editing.caret, 2);
+        // 已经在第一行
+        editing.move_up(&shape);
+        assert_eq!(editing.caret, 2);
+    }
+
+    #[test]
+    fn caret_position_follows_lines() {
+        let (x, y) = caret_position((100.0, 100.0), "截图\nab", DEFAULT_FONT_SIZE, 0);
+        assert_eq!((x, y), (100.0 + TEXT_PADDING, 100.0 + TEXT_PADDING));
+
+        // 换行后回到下一行行首
+        let (x, y) = caret_position((100.0, 100.0), "截图\nab", DEFAULT_FONT_SIZE, 3);
+        assert_eq!(x, 100.0 + TEXT_PADDING);
+        assert_eq!(y, 100.0 + TEXT_PADDING + line_height(DEFAULT_FONT_SIZE));
+    }
+
+    #[test]
+    fn resizing_scales_font_size() {
+        let selection = (0.0, 0.0, 800.0, 600.0);
+        let mut shape = text("ab\ncd");
+        let (left, top, right, bottom) = shape.bounds();
+
+        // 拖动右下角把高度放大一倍，左上角不动
+        shape.resize_constrained((left, top, right, top + (bottom - top) * 2.0), selection);
+        let DrawingShape::Text {
+            position,
+            font_size,
+            ..
+        } = &shape
+        else {
+            unreachable!();
+        };
+        assert_eq!(*position, (left, top));
+        assert!(
+            (font_size - DEFAULT_FONT_SIZE * 2.0).abs() < 1e-3,
+            "{font_size}"
+        );
+
+        // 缩小时不小于最小字号
+        let (left, top, right, _) = shape.bounds();
+        shape.resize_constrained((left, top, right, top + 1.0), selection);
+        let DrawingShape::Text { font_size, .. } = &shape else {
+            unreachable!();
+        };
+        assert_eq!(*font_size, MIN_FONT_SIZE);
+    }
+
+    #[test]
+    fn resizing_past_selection_is_ignored() {
+        let mut shape = text("ab");
+        let before = shape.clone();
+        let (left, top, right, bottom) = shape.bounds();
+        // 高度放大四倍后超出选择区域右边界
+        shape.resize_constrained(
+            (left, top, right, top + (bottom - top) * 4.0),
+            (0.0, 0.0, right + 1.0, 600.0),
+        );
+        assert_eq!(shape, before);
+    }
+}
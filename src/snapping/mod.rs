@@ -0,0 +1,270 @@
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+
+use skia_safe::Image as SkiaImage;
+
+use crate::export::read_rgba;
+
+// 相邻像素的亮度差（0-255）不小于该值时视为边缘
+const EDGE_CONTRAST: u8 = 24;
+// 边缘至少连续这么多像素才算作一条直线，过滤掉文字和图标的笔画
+const MIN_EDGE_LENGTH: usize = 24;
+// 选择框的边离直线不超过该距离（物理像素）时吸附过去
+pub const SNAP_DISTANCE: f32 = 8.0;
+
+/// 截图中明显的水平和垂直直线，坐标为像素之间的边界
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeMap {
+    // 垂直线的 x 坐标，升序
+    columns: Vec<f32>,
+    // 水平线的 y 坐标，升序
+    rows: Vec<f32>,
+}
+
+impl EdgeMap {
+    // 在紧密排列的 RGBA 像素中查找直线
+    pub fn detect(rgba: &[u8], width: usize, height: usize) -> Self {
+        if width == 0 || height == 0 || rgba.len() < width * height * 4 {
+            return Self::default();
+        }
+        let luma: Vec<u8> = rgba
+            .chunks_exact(4)
+            .take(width * height)
+            .map(|pixel| {
+                let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(u32::from);
+                ((r * 299 + g * 587 + b * 114) / 1000) as u8
+            })
+            .collect();
+        let is_edge = |a: u8, b: u8| a.abs_diff(b) >= EDGE_CONTRAST;
+
+        // 垂直线：逐行扫描，统计每个 x 边界上连续的边缘长度
+        let mut runs = vec![0usize; width];
+        let mut column_found = vec![false; width];
+        for row in luma.chunks_exact(width) {
+            for x in 1..width {
+                if is_edge(row[x - 1], row[x]) {
+                    runs[x] += 1;
+                    column_found[x] |= runs[x] >= MIN_EDGE_LENGTH;
+                } else {
+                    runs[x] = 0;
+                }
+            }
+        }
+
+        // 水平线：比较相邻两行，统计连续的边缘长度
+        let mut rows = Vec::new();
+        for y in 1..height {
+            let above = &luma[(y - 1) * width..y * width];
+            let below = &luma[y * width..(y + 1) * width];
+            let mut run = 0;
+            let found = above.iter().zip(below).any(|(&a, &b)| {
+                run = if is_edge(a, b) { run + 1 } else { 0 };
+                run >= MIN_EDGE_LENGTH
+            });
+            if found {
+                rows.push(y as f32);
+            }
+        }
+
+        let columns = column_found
+            .iter()
+            .enumerate()
+            .filter(|(_, found)| **found)
+            .map(|(x, _)| x as f32)
+            .collect();
+        Self { columns, rows }
+    }
+
+    pub fn snap_x(&self, x: f32) -> Option<f32> {
+        nearest(&self.columns, x)
+    }
+
+    pub fn snap_y(&self, y: f32) -> Option<f32> {
+        nearest(&self.rows, y)
+    }
+
+    // 横纵坐标分别吸附到最近的直线，附近没有直线时保持不变
+    pub fn snap(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.snap_x(x).unwrap_or(x), self.snap_y(y).unwrap_or(y))
+    }
+}
+
+// 有序坐标中距离 value 不超过 SNAP_DISTANCE 的最近值
+fn nearest(lines: &[f32], value: f32) -> Option<f32> {
+    let index = lines.partition_point(|&line| line < value);
+    let before = index.checked_sub(1).and_then(|i| lines.get(i));
+    let after = lines.get(index);
+    [before, after]
+        .into_iter()
+        .flatten()
+        .copied()
+        .filter(|line| (line - value).abs() <= SNAP_DISTANCE)
+        .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+}
+
+/// 在后台线程中分析截图，结果就绪前不吸附
+pub struct EdgeAnalysis {
+    receiver: Option<Receiver<EdgeMap>>,
+    edges: Option<EdgeMap>,
+}
+
+impl EdgeAnalysis {
+    // 在当前线程读出像素，边缘检测在新线程中进行
+    pub fn start(image: &SkiaImage) -> Self {
+        let Some(rgba) = read_rgba(image) else {
+            return Self {
+                receiver: None,
+                edges: None,
+            };
+        };
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(EdgeMap::detect(&rgba, width, height));
+        });
+        Self {
+            receiver: Some(receiver),
+            edges: None,
+        }
+    }
+
+    // 已经完成的分析结果，不会阻塞
+    pub fn edges(&mut self) -> Option<&EdgeMap> {
+        if let Some(receiver) = &self.receiver {
+            match receiver.try_recv() {
+                Ok(edges) => {
+                    self.edges = Some(edges);
+                    self.receiver = None;
+                }
+                Err(TryRecvError::Disconnected) => self.receiver = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        self.edges.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use skia_safe::{Color, Paint, Rect, surfaces};
+
+    use super::*;
+
+    // 黑色背景上画一个白色矩形（左上闭、右下开）
+    fn rgba_with_rect(
+        width: usize,
+        height: usize,
+        (left, top, right, bottom): (usize, usize, usize, usize),
+    ) -> Vec<u8> {
+        let mut rgba = vec![0u8; width * height * 4];
+        for y in top..bottom {
+            for x in left..right {
+                let offset = (y * width + x) * 4;
+                rgba[offset..offset + 4].copy_from_slice(&[255, 255, 255, 255]);
+            }
+        }
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+        rgba
+    }
+
+    #[test]
+    fn detects_long_lines() {
+        let rgba = rgba_with_rect(60, 50, (10, 5, 40, 45));
+        let edges = EdgeMap::detect(&rgba, 60, 50);
+        assert_eq!(
+            edges,
+            EdgeMap {
+                columns: vec![10.0, 40.0],
+                rows: vec![5.0, 45.0],
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_short_strokes() {
+        // 边长比 MIN_EDGE_LENGTH 短一个像素
+        let short = MIN_EDGE_LENGTH - 1;
+        let rgba = rgba_with_rect(60, 50, (10, 10, 10 + short, 10 + short));
+        assert_eq!(EdgeMap::detect(&rgba, 60, 50), EdgeMap::default());
+
+        // 正好 MIN_EDGE_LENGTH 时算作直线
+        let rgba = rgba_with_rect(60, 50, (10, 10, 10 + MIN_EDGE_LENGTH, 10 + MIN_EDGE_LENGTH));
+        let edges = EdgeMap::detect(&rgba, 60, 50);
+        assert_eq!(edges.columns, [10.0, 10.0 + MIN_EDGE_LENGTH as f32]);
+        assert_eq!(edges.rows, [10.0, 10.0 + MIN_EDGE_LENGTH as f32]);
+    }
+
+    #[test]
+    fn ignores_low_contrast() {
+        let mut rgba = rgba_with_rect(60, 50, (10, 5, 40, 45));
+        for pixel in rgba.chunks_exact_mut(4) {
+            let value = if pixel[0] == 255 {
+                EDGE_CONTRAST - 1
+            } else {
+                0
+            };
+            pixel[..3].fill(value);
+        }
+        assert_eq!(EdgeMap::detect(&rgba, 60, 50), EdgeMap::default());
+    }
+
+    #[test]
+    fn rejects_truncated_pixels() {
+        let rgba = rgba_with_rect(60, 50, (10, 5, 40, 45));
+        assert_eq!(EdgeMap::detect(&rgba[..100], 60, 50), EdgeMap::default());
+        assert_eq!(EdgeMap::detect(&[], 0, 0), EdgeMap::default());
+    }
+
+    #[test]
+    fn snap_respects_distance() {
+        let edges = EdgeMap {
+            columns: vec![10.0, 40.0],
+            rows: vec![5.0, 45.0],
+        };
+        assert_eq!(edges.snap_x(15.0), Some(10.0));
+        assert_eq!(edges.snap_x(10.0 + SNAP_DISTANCE), Some(10.0));
+        assert_eq!(edges.snap_x(10.0 + SNAP_DISTANCE + 0.5), None);
+        assert_eq!(edges.snap_x(25.0), None);
+        assert_eq!(edges.snap_x(-SNAP_DISTANCE + 10.0), Some(10.0));
+        assert_eq!(edges.snap_y(50.0), Some(45.0));
+        assert_eq!(edges.snap((12.0, 30.0)), (10.0, 30.0));
+        assert_eq!(edges.snap((38.0, 47.0)), (40.0, 45.0));
+    }
+
+    #[test]
+    fn snap_picks_nearest_line() {
+        let edges = EdgeMap {
+            columns: vec![10.0, 20.0],
+            rows: Vec::new(),
+        };
+        assert_eq!(edges.snap_x(14.0), Some(10.0));
+        assert_eq!(edges.snap_x(16.0), Some(20.0));
+        assert_eq!(edges.snap_y(16.0), None);
+    }
+
+    #[test]
+    fn analysis_runs_in_background() {
+        let mut surface = surfaces::raster_n32_premul((60, 50)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(Color::BLACK);
+        let mut paint = Paint::default();
+        paint.set_color(Color::WHITE);
+        canvas.draw_rect(Rect::from_ltrb(10.0, 5.0, 40.0, 45.0), &paint);
+
+        let mut analysis = EdgeAnalysis::start(&surface.image_snapshot());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while analysis.edges().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            analysis.edges(),
+            Some(&EdgeMap {
+                columns: vec![10.0, 40.0],
+                rows: vec![5.0, 45.0],
+            })
+        );
+    }
+}
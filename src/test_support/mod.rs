@@ -0,0 +1,19 @@
+// 各模块测试共用的图片工具
+
+use skia_safe::{Color, Image as SkiaImage, surfaces};
+
+use crate::export::read_rgba;
+
+// 纯色的光栅图片
+pub fn solid_image(width: i32, height: i32, color: Color) -> SkiaImage {
+    let mut surface = surfaces::raster_n32_premul((width, height)).unwrap();
+    surface.canvas().clear(color);
+    surface.image_snapshot()
+}
+
+// 图片上一个像素的非预乘 RGBA
+pub fn pixel(image: &SkiaImage, x: usize, y: usize) -> [u8; 4] {
+    let rgba = read_rgba(image).unwrap();
+    let offset = (y * image.width() as usize + x) * 4;
+    rgba[offset..offset + 4].try_into().unwrap()
+}
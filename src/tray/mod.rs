@@ -0,0 +1,405 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use skia_safe::{AlphaType, Color, ColorType, ImageInfo, Paint, PaintStyle, RRect, Rect, surfaces};
+use tray_icon::{
+    Icon, TrayIcon, TrayIconBuilder,
+    menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
+};
+
+const CAPTURE_REGION_ID: &str = "capture-region";
+const CAPTURE_FULLSCREEN_ID: &str = "capture-fullscreen";
+const OPEN_IMAGE_ID: &str = "open-image";
+const SETTINGS_ID: &str = "settings";
+const QUIT_ID: &str = "quit";
+// 历史记录菜单项的 id 为该前缀加上记录的序号，路径不一定能无损转成字符串
+const HISTORY_ID_PREFIX: &str = "history:";
+
+// 历史记录菜单中最多保留的截图数量
+pub const MAX_HISTORY_ITEMS: usize = 10;
+
+const ICON_SIZE: i32 = 32;
+
+// 托盘菜单发出的命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayCommand {
+    CaptureRegion,
+    CaptureFullscreen,
+    // 打开图片进行标注；没有文件对话框的平台上为 None，显示遮罩后拖入图片
+    OpenImage(Option<PathBuf>),
+    // 打开配置文件
+    OpenSettings,
+    Quit,
+}
+
+#[derive(Debug)]
+pub enum TrayError {
+    // 托盘图标像素无效
+    Icon(String),
+    Menu(String),
+    Tray(String),
+    // 图形界面工具包初始化失败（Linux 上为 GTK）
+    Toolkit(String),
+}
+
+impl fmt::Display for TrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrayError::Icon(message) => write!(f, "无法创建托盘图标: {message}"),
+            TrayError::Menu(message) => write!(f, "无法创建托盘菜单: {message}"),
+            TrayError::Tray(message) => write!(f, "无法创建托盘: {message}"),
+            TrayError::Toolkit(message) => write!(f, "无法初始化 GTK: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TrayError {}
+
+// 历史记录菜单中的截图路径，按序号查找
+#[derive(Debug, Default)]
+struct HistoryPaths {
+    next_index: usize,
+    // 最新的在前
+    entries: VecDeque<(usize, PathBuf)>,
+}
+
+impl HistoryPaths {
+    // 记录路径并返回其序号，超出上限时丢弃最旧的记录
+    fn push(&mut self, path: PathBuf) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.entries.push_front((index, path));
+        self.entries.truncate(MAX_HISTORY_ITEMS);
+        index
+    }
+
+    fn get(&self, index: usize) -> Option<&PathBuf> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| *entry == index)
+            .map(|(_, path)| path)
+    }
+}
+
+// 托盘回调与历史记录菜单共享的路径表
+type SharedHistory = Arc<Mutex<HistoryPaths>>;
+
+// 菜单项对应的命令，分隔线等不对应命令的菜单项返回 None
+// 只在查找历史记录时加锁，打开图片的对话框会运行嵌套的事件循环
+fn command_for(id: &MenuId, history: &Mutex<HistoryPaths>) -> Option<TrayCommand> {
+    match id.as_ref() {
+        CAPTURE_REGION_ID => Some(TrayCommand::CaptureRegion),
+        CAPTURE_FULLSCREEN_ID => Some(TrayCommand::CaptureFullscreen),
+        OPEN_IMAGE_ID => Some(TrayCommand::OpenImage(pick_image())),
+        SETTINGS_ID => Some(TrayCommand::OpenSettings),
+        QUIT_ID => Some(TrayCommand::Quit),
+        id => id
+            .strip_prefix(HISTORY_ID_PREFIX)
+            .and_then(|index| index.parse().ok())
+            .and_then(|index| history.lock().ok()?.get(index).cloned())
+            .map(|path| TrayCommand::OpenImage(Some(path))),
+    }
+}
+
+// 托盘图标：圆角方块上画出选择框的四个角
+fn tray_icon_image() -> Result<Icon, TrayError> {
+    let mut surface = surfaces::raster_n32_premul((ICON_SIZE, ICON_SIZE))
+        .ok_or_else(|| TrayError::Icon("无法创建绘制表面".into()))?;
+    let canvas = surface.canvas();
+    canvas.clear(Color::TRANSPARENT);
+
+    let size = ICON_SIZE as f32;
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(Color::from_rgb(0, 120, 215));
+    canvas.draw_rrect(
+        RRect::new_rect_xy(Rect::from_wh(size, size), 6.0, 6.0),
+        &paint,
+    );
+
+    paint.set_color(Color::WHITE);
+    paint.set_style(PaintStyle::Stroke);
+    paint.set_stroke_width(3.0);
+    let (near, far, arm) = (8.0, size - 8.0, 7.0);
+    for (x, y, dx, dy) in [
+        (near, near, arm, arm),
+        (far, near, -arm, arm),
+        (far, far, -arm, -arm),
+        (near, far, arm, -arm),
+    ] {
+        canvas.draw_line((x + dx, y), (x, y), &paint);
+        canvas.draw_line((x, y), (x, y + dy), &paint);
+    }
+
+    let info = ImageInfo::new(
+        (ICON_SIZE, ICON_SIZE),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    );
+    let row_bytes = ICON_SIZE as usize * 4;
+    let mut rgba = vec![0u8; row_bytes * ICON_SIZE as usize];
+    if !surface.read_pixels(&info, &mut rgba, row_bytes, (0, 0)) {
+        return Err(TrayError::Icon("无法读取图标像素".into()));
+    }
+    Icon::from_rgba(rgba, ICON_SIZE as u32, ICON_SIZE as u32)
+        .map_err(|err| TrayError::Icon(err.to_string()))
+}
+
+// 最近保存的截图，点击后重新打开标注
+struct HistoryMenu {
+    submenu: Submenu,
+    paths: SharedHistory,
+}
+
+impl HistoryMenu {
+    fn add(&self, path: &Path) {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let index = match self.paths.lock() {
+            Ok(mut paths) => paths.push(path.to_path_buf()),
+            Err(_) => return,
+        };
+        let item = MenuItem::with_id(format!("{HISTORY_ID_PREFIX}{index}"), name, true, None);
+        if let Err(err) = self.submenu.insert(&item, 0) {
+            eprintln!("无法更新历史记录菜单: {err}");
+            return;
+        }
+        if self.submenu.items().len() > MAX_HISTORY_ITEMS {
+            self.submenu.remove_at(MAX_HISTORY_ITEMS);
+        }
+        self.submenu.set_enabled(true);
+    }
+}
+
+// 创建托盘图标和菜单，必须在平台的界面线程上调用
+fn build_tray(paths: SharedHistory) -> Result<(TrayIcon, HistoryMenu), TrayError> {
+    // 没有历史记录时禁用
+    let history = Submenu::new("历史记录", false);
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id(CAPTURE_REGION_ID, "区域截图", true, None),
+        &MenuItem::with_id(CAPTURE_FULLSCREEN_ID, "全屏截图", true, None),
+        &MenuItem::with_id(OPEN_IMAGE_ID, "打开图片…", true, None),
+        &PredefinedMenuItem::separator(),
+        &history,
+        &MenuItem::with_id(SETTINGS_ID, "设置…", true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(QUIT_ID, "退出", true, None),
+    ])
+    .map_err(|err| TrayError::Menu(err.to_string()))?;
+
+    let tray = TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(env!("CARGO_PKG_NAME"))
+        .with_icon(tray_icon_image()?)
+        .build()
+        .map_err(|err| TrayError::Tray(err.to_string()))?;
+
+    Ok((
+        tray,
+        HistoryMenu {
+            submenu: history,
+            paths,
+        },
+    ))
+}
+
+/// 系统托盘：菜单命令通过回调发出，托盘在 Tray 被丢弃前一直存在
+pub struct Tray {
+    // Linux 上托盘运行在单独的 GTK 线程中，历史记录通过通道发过去
+    #[cfg(target_os = "linux")]
+    history: std::sync::mpsc::Sender<PathBuf>,
+    #[cfg(not(target_os = "linux"))]
+    _icon: TrayIcon,
+    #[cfg(not(target_os = "linux"))]
+    history: HistoryMenu,
+}
+
+impl Tray {
+    // 回调在托盘所在的界面线程上调用
+    pub fn new(
+        on_command: impl Fn(TrayCommand) + Send + Sync + 'static,
+    ) -> Result<Self, TrayError> {
+        let paths = SharedHistory::default();
+        let handler_paths = paths.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(command) = command_for(event.id(), &handler_paths) {
+                on_command(command);
+            }
+        }));
+        Self::start(paths)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn start(paths: SharedHistory) -> Result<Self, TrayError> {
+        use std::{sync::mpsc, time::Duration};
+
+        let (history_sender, history_receiver) = mpsc::channel::<PathBuf>();
+        let (ready_sender, ready_receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            if let Err(err) = gtk::init() {
+                let _ = ready_sender.send(Err(TrayError::Toolkit(err.to_string())));
+                return;
+            }
+            let (_icon, history) = match build_tray(paths) {
+                Ok(tray) => tray,
+                Err(err) => {
+                    let _ = ready_sender.send(Err(err));
+                    return;
+                }
+            };
+            let _ = ready_sender.send(Ok(()));
+
+            gtk::glib::timeout_add_local(Duration::from_millis(200), move || {
+                while let Ok(path) = history_receiver.try_recv() {
+                    history.add(&path);
+                }
+                gtk::glib::ControlFlow::Continue
+            });
+            gtk::main();
+        });
+
+        ready_receiver
+            .recv()
+            .map_err(|_| TrayError::Toolkit("托盘线程意外退出".into()))??;
+        Ok(Self {
+            history: history_sender,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn start(paths: SharedHistory) -> Result<Self, TrayError> {
+        let (icon, history) = build_tray(paths)?;
+        Ok(Self {
+            _icon: icon,
+            history,
+        })
+    }
+
+    // 将保存的截图加入历史记录菜单
+    pub fn add_history(&self, path: &Path) {
+        #[cfg(target_os = "linux")]
+        let _ = self.history.send(path.to_path_buf());
+        #[cfg(not(target_os = "linux"))]
+        self.history.add(path);
+    }
+}
+
+// 用 GTK 文件对话框选择图片，菜单回调本身就在 GTK 线程上
+#[cfg(target_os = "linux")]
+fn pick_image() -> Option<PathBuf> {
+    use gtk::prelude::*;
+
+    let dialog = gtk::FileChooserDialog::with_buttons(
+        Some("打开图片"),
+        None::<&gtk::Window>,
+        gtk::FileChooserAction::Open,
+        &[
+            ("取消", gtk::ResponseType::Cancel),
+            ("打开", gtk::ResponseType::Accept),
+        ],
+    );
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some("图片"));
+    filter.add_pixbuf_formats();
+    dialog.add_filter(&filter);
+
+    let path = match dialog.run() {
+        gtk::ResponseType::Accept => dialog.filename(),
+        _ => None,
+    };
+    dialog.close();
+    path
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pick_image() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, history: &Mutex<HistoryPaths>) -> Option<TrayCommand> {
+        command_for(&MenuId::new(id), history)
+    }
+
+    fn history_id(index: usize) -> String {
+        format!("{HISTORY_ID_PREFIX}{index}")
+    }
+
+    #[test]
+    fn menu_ids_map_to_commands() {
+        let history = Mutex::default();
+        assert_eq!(
+            command(CAPTURE_REGION_ID, &history),
+            Some(TrayCommand::CaptureRegion)
+        );
+        assert_eq!(
+            command(CAPTURE_FULLSCREEN_ID, &history),
+            Some(TrayCommand::CaptureFullscreen)
+        );
+        assert_eq!(
+            command(SETTINGS_ID, &history),
+            Some(TrayCommand::OpenSettings)
+        );
+        assert_eq!(command(QUIT_ID, &history), Some(TrayCommand::Quit));
+        assert_eq!(command("separator", &history), None);
+    }
+
+    #[test]
+    fn history_entries_are_found_by_index() {
+        let mut paths = HistoryPaths::default();
+        let first = paths.push(PathBuf::from("/tmp/第一张.png"));
+        let second = paths.push(PathBuf::from("/tmp/a:b.png"));
+        assert_ne!(first, second);
+        let history = Mutex::new(paths);
+
+        assert_eq!(
+            command(&history_id(first), &history),
+            Some(TrayCommand::OpenImage(Some(PathBuf::from(
+                "/tmp/第一张.png"
+            ))))
+        );
+        assert_eq!(
+            command(&history_id(second), &history),
+            Some(TrayCommand::OpenImage(Some(PathBuf::from("/tmp/a:b.png"))))
+        );
+        assert_eq!(command(&history_id(99), &history), None);
+        assert_eq!(command(&format!("{HISTORY_ID_PREFIX}abc"), &history), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn history_keeps_non_utf8_paths() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = PathBuf::from(OsStr::from_bytes(b"/tmp/\xff.png"));
+        let mut paths = HistoryPaths::default();
+        let index = paths.push(path.clone());
+        assert_eq!(
+            command(&history_id(index), &Mutex::new(paths)),
+            Some(TrayCommand::OpenImage(Some(path)))
+        );
+    }
+
+    #[test]
+    fn oldest_history_entries_are_dropped() {
+        let mut paths = HistoryPaths::default();
+        let oldest = paths.push(PathBuf::from("oldest.png"));
+        for n in 0..MAX_HISTORY_ITEMS {
+            paths.push(PathBuf::from(format!("{n}.png")));
+        }
+        assert_eq!(paths.entries.len(), MAX_HISTORY_ITEMS);
+        assert_eq!(command(&history_id(oldest), &Mutex::new(paths)), None);
+    }
+}
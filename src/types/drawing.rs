@@ -7,9 +7,36 @@ pub enum DrawingTool {
     Circle,
     Arrow,
     Brush,
+    Text,
+    Mosaic,
+    Blur,
 }
 
-#[derive(Debug, Clone)]
+// 打码区域的形状
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedactAreaKind {
+    Rectangle,
+    Ellipse,
+    Brush,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedactArea {
+    Rectangle {
+        start: (f32, f32),
+        end: (f32, f32),
+    },
+    Ellipse {
+        start: (f32, f32),
+        end: (f32, f32),
+    },
+    Brush {
+        points: Vec<(f32, f32)>,
+        width: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum DrawingShape {
     Rectangle {
         start: (f32, f32),
@@ -34,4 +61,36 @@ pub enum DrawingShape {
         color: Color,
         stroke_width: f32,
     },
+    Text {
+        position: (f32, f32), // 文本框左上角
+        content: String,
+        font_size: f32,
+        color: Color,
+        background: Option<Color>,
+    },
+    // 马赛克：对区域下的截图像素做块状像素化
+    Mosaic {
+        area: RedactArea,
+        block_size: f32,
+    },
+    // 高斯模糊：对区域下的截图像素做模糊
+    Blur {
+        area: RedactArea,
+        sigma: f32,
+    },
+}
+
+// 图形的颜色与线宽，同时用作新建图形的默认样式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeStyle {
+    pub color: Color,
+    pub stroke_width: f32,
+}
+
+// 正在编辑的文字标注
+#[derive(Debug, Clone)]
+pub struct TextEditing {
+    pub index: usize,
+    pub caret: usize, // 光标位置（按字符计）
+    pub before: Option<DrawingShape>, // 编辑前的图形，新建文字时为 None
 }
\ No newline at end of file
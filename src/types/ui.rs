@@ -1,13 +1,88 @@
-use crate::constants::constants::{
-    BUTTON_HEIGHT, BUTTON_SPACING, BUTTON_WIDTH, SCREEN_MARGIN, TOOLBAR_MARGIN, TOTAL_BUTTONS,
+use std::fmt;
+
+use skia_safe::Color;
+
+use crate::{
+    constants::constants::{
+        BUTTON_HEIGHT, BUTTON_SPACING, BUTTON_WIDTH, COLOR_INPUT_WIDTH, MAGNIFIER_INFO_HEIGHT,
+        MAGNIFIER_MARGIN, MAGNIFIER_PIXELS, MAGNIFIER_ZOOM, PROPERTY_BAR_MARGIN, PROPERTY_BUTTON_SIZE,
+        PROPERTY_BUTTON_SPACING, SCREEN_MARGIN, TOOLBAR_MARGIN, TOTAL_BUTTONS,
+    },
+    coords::{LogicalSize, PhysicalSize, ScaleFactor},
+    shapes::style::{PRESET_COLORS, STROKE_WIDTHS},
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Selection {
     pub start: (f32, f32),
     pub end: (f32, f32),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolbarButton {
+    Rectangle,
+    Circle,
+    Arrow,
+    Brush,
+    Text,
+    Mosaic,
+    Blur,
+    // 切换选择框的比例限制
+    Ratio,
+    Save,
+    Close,
+}
+
+impl ToolbarButton {
+    // 工具栏按钮从左到右的顺序
+    pub const ALL: [ToolbarButton; TOTAL_BUTTONS as usize] = [
+        ToolbarButton::Rectangle,
+        ToolbarButton::Circle,
+        ToolbarButton::Arrow,
+        ToolbarButton::Brush,
+        ToolbarButton::Text,
+        ToolbarButton::Mosaic,
+        ToolbarButton::Blur,
+        ToolbarButton::Ratio,
+        ToolbarButton::Save,
+        ToolbarButton::Close,
+    ];
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ToolbarButton::Rectangle => "rectangle",
+            ToolbarButton::Circle => "circle",
+            ToolbarButton::Arrow => "arrow",
+            ToolbarButton::Brush => "brush",
+            ToolbarButton::Text => "text",
+            ToolbarButton::Mosaic => "mosaic",
+            ToolbarButton::Blur => "blur",
+            ToolbarButton::Ratio => "ratio",
+            ToolbarButton::Save => "save",
+            ToolbarButton::Close => "close",
+        }
+    }
+}
+
+// 新建和调整选择框时的限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionRatio {
+    Free,
+    // 宽高比，例如 16:9
+    Ratio(f32, f32),
+    // 固定大小（物理像素），只能移动位置
+    Fixed(f32, f32),
+}
+
+// 工具栏比例按钮依次切换的预设，配置文件中的自定义比例和固定大小排在后面
+pub const DEFAULT_RATIOS: [SelectionRatio; 4] = [
+    SelectionRatio::Free,
+    SelectionRatio::Ratio(16.0, 9.0),
+    SelectionRatio::Ratio(4.0, 3.0),
+    SelectionRatio::Ratio(1.0, 1.0),
+];
+
+// 工具栏、属性栏和放大镜都按逻辑像素布局，在高 DPI 显示器上与普通显示器一样大
 pub struct Toolbar {
     pub x: f32,
     pub y: f32,
@@ -15,7 +90,65 @@ pub struct Toolbar {
     pub height: f32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyButton {
+    Color(Color),
+    CustomColor,
+    StrokeWidth(f32),
+}
+
+impl PropertyButton {
+    // 预设颜色、自定义颜色、线宽档位
+    pub const COUNT: usize = PRESET_COLORS.len() + 1 + STROKE_WIDTHS.len();
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        let colors = PRESET_COLORS.len();
+        if index < colors {
+            Some(PropertyButton::Color(PRESET_COLORS[index]))
+        } else if index == colors {
+            Some(PropertyButton::CustomColor)
+        } else {
+            STROKE_WIDTHS
+                .get(index - colors - 1)
+                .map(|width| PropertyButton::StrokeWidth(*width))
+        }
+    }
+}
+
+// 工具栏旁边的属性栏（颜色与线宽）
+pub struct PropertyBar {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// 鼠标旁边的放大镜：上部是放大的像素网格，下部是坐标和颜色信息
+pub struct Magnifier {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
 impl Selection {
+    // 覆盖整张图片的选择框
+    pub fn covering(size: PhysicalSize) -> Self {
+        Self {
+            start: (0.0, 0.0),
+            end: (size.width_f32(), size.height_f32()),
+        }
+    }
+
+    // 换算为逻辑像素，工具栏等界面控件按逻辑像素布局
+    pub fn to_logical(self, scale: ScaleFactor) -> Self {
+        let scale = scale.get();
+        Self {
+            start: (self.start.0 / scale, self.start.1 / scale),
+            end: (self.end.0 / scale, self.end.1 / scale),
+        }
+    }
+
     pub fn bounds(&self) -> (f32, f32, f32, f32) {
         let left = self.start.0.min(self.end.0);
         let right = self.start.0.max(self.end.0);
@@ -34,8 +167,50 @@ impl Selection {
         (right - left, bottom - top)
     }
 }
+impl SelectionRatio {
+    // 解析 "21:9" 这样的比例
+    pub fn parse_ratio(text: &str) -> Option<Self> {
+        let (width, height) = parse_pair(text, &[':'])?;
+        Some(SelectionRatio::Ratio(width, height))
+    }
+
+    // 解析 "1280x720" 这样的大小
+    pub fn parse_size(text: &str) -> Option<Self> {
+        let (width, height) = parse_pair(text, &['x', 'X', '×'])?;
+        Some(SelectionRatio::Fixed(width, height))
+    }
+
+    // 宽除以高，不限制比例时为 None
+    pub fn aspect(&self) -> Option<f32> {
+        match self {
+            SelectionRatio::Free => None,
+            SelectionRatio::Ratio(width, height) | SelectionRatio::Fixed(width, height) => {
+                Some(width / height)
+            }
+        }
+    }
+}
+
+fn parse_pair(text: &str, separators: &[char]) -> Option<(f32, f32)> {
+    let (width, height) = text.split_once(separators)?;
+    let width = width.trim().parse::<f32>().ok()?;
+    let height = height.trim().parse::<f32>().ok()?;
+    (width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0)
+        .then_some((width, height))
+}
+
+impl fmt::Display for SelectionRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionRatio::Free => write!(f, "自由"),
+            SelectionRatio::Ratio(width, height) => write!(f, "{width}:{height}"),
+            SelectionRatio::Fixed(width, height) => write!(f, "{width}×{height}"),
+        }
+    }
+}
+
 impl Toolbar {
-  pub  fn calculate(selection: &Selection, screen_size: (u32, u32)) -> Self {
+  pub  fn calculate(selection: &Selection, screen_size: LogicalSize) -> Self {
         let (left, top, right, bottom) = selection.bounds();
         let center_x = (left + right) / 2.0;
 
@@ -47,7 +222,7 @@ impl Toolbar {
         let toolbar_bottom = default_y + height;
 
         // 检查是否需要移动到上方
-        let y = if toolbar_bottom > screen_size.1 as f32 - SCREEN_MARGIN {
+        let y = if toolbar_bottom > screen_size.height - SCREEN_MARGIN {
             top - height - TOOLBAR_MARGIN
         } else {
             default_y
@@ -57,7 +232,7 @@ impl Toolbar {
         // 水平居中，但不超出屏幕边界
         let x = (center_x - width / 2.0)
             .max(SCREEN_MARGIN)
-            .min(screen_size.0 as f32 - width - SCREEN_MARGIN);
+            .min(screen_size.width - width - SCREEN_MARGIN);
 
         Self {
             x,
@@ -77,6 +252,163 @@ impl Toolbar {
         }
         let relative_x = x - self.x;
         let index = (relative_x / (BUTTON_WIDTH + BUTTON_SPACING)).floor() as usize;
-        if index < 5 { Some(index) } else { None }
+        if index < ToolbarButton::ALL.len() { Some(index) } else { None }
+    }
+
+    pub fn get_button(&self, x: f32, y: f32) -> Option<ToolbarButton> {
+        self.get_button_index(x, y).map(|index| ToolbarButton::ALL[index])
+    }
+}
+
+impl PropertyBar {
+    pub fn calculate(toolbar: &Toolbar, selection: &Selection, screen_size: LogicalSize) -> Self {
+        let count = PropertyButton::COUNT as f32;
+        let width = count * PROPERTY_BUTTON_SIZE + (count - 1.0) * PROPERTY_BUTTON_SPACING;
+        let height = PROPERTY_BUTTON_SIZE;
+
+        // 放在工具栏远离选择框的一侧，放不下时换到另一侧
+        let below = toolbar.y + toolbar.height + PROPERTY_BAR_MARGIN;
+        let above = toolbar.y - height - PROPERTY_BAR_MARGIN;
+        let fits_below = below + height <= screen_size.height - SCREEN_MARGIN;
+        let fits_above = above >= SCREEN_MARGIN;
+        let (_, _, _, bottom) = selection.bounds();
+        let y = if toolbar.y >= bottom {
+            if fits_below || !fits_above { below } else { above }
+        } else if fits_above || !fits_below {
+            above
+        } else {
+            below
+        };
+
+        // 与工具栏左对齐，但不超出屏幕边界
+        let x = toolbar
+            .x
+            .max(SCREEN_MARGIN)
+            .min(screen_size.width - width - SCREEN_MARGIN);
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+
+    // 自定义颜色输入框：显示在属性栏右侧，放不下时显示在左侧
+    pub fn color_input_rect(&self, screen_size: LogicalSize) -> (f32, f32, f32, f32) {
+        let right_x = self.x + self.width + PROPERTY_BUTTON_SPACING;
+        let x = if right_x + COLOR_INPUT_WIDTH <= screen_size.width - SCREEN_MARGIN {
+            right_x
+        } else {
+            self.x - PROPERTY_BUTTON_SPACING - COLOR_INPUT_WIDTH
+        };
+        (x, self.y, COLOR_INPUT_WIDTH, self.height)
+    }
+
+    pub fn button_x(&self, index: usize) -> f32 {
+        self.x + index as f32 * (PROPERTY_BUTTON_SIZE + PROPERTY_BUTTON_SPACING)
+    }
+
+    pub fn get_button_index(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.contains_point(x, y) {
+            return None;
+        }
+        let relative_x = x - self.x;
+        let index =
+            (relative_x / (PROPERTY_BUTTON_SIZE + PROPERTY_BUTTON_SPACING)).floor() as usize;
+        if index < PropertyButton::COUNT { Some(index) } else { None }
+    }
+
+    pub fn get_button(&self, x: f32, y: f32) -> Option<PropertyButton> {
+        self.get_button_index(x, y).and_then(PropertyButton::from_index)
+    }
+}
+
+impl Magnifier {
+    pub fn calculate(cursor: (f32, f32), screen_size: LogicalSize) -> Self {
+        let width = MAGNIFIER_PIXELS * MAGNIFIER_ZOOM;
+        let height = width + MAGNIFIER_INFO_HEIGHT;
+
+        // 默认在鼠标右下方，超出屏幕时换到左侧或上方
+        let right = cursor.0 + MAGNIFIER_MARGIN;
+        let x = if right + width > screen_size.width - SCREEN_MARGIN {
+            cursor.0 - MAGNIFIER_MARGIN - width
+        } else {
+            right
+        }
+        .max(SCREEN_MARGIN);
+
+        let below = cursor.1 + MAGNIFIER_MARGIN;
+        let y = if below + height > screen_size.height - SCREEN_MARGIN {
+            cursor.1 - MAGNIFIER_MARGIN - height
+        } else {
+            below
+        }
+        .max(SCREEN_MARGIN);
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // 像素网格所在的正方形区域
+    pub fn grid_rect(&self) -> (f32, f32, f32, f32) {
+        (self.x, self.y, self.width, self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magnifier_stays_on_screen() {
+        let screen = LogicalSize::new(1000.0, 800.0);
+        let cases = [
+            // 默认在鼠标右下方
+            ((400.0, 300.0), (420.0, 320.0)),
+            ((0.0, 0.0), (20.0, 20.0)),
+            ((500.0, 0.0), (520.0, 20.0)),
+            ((0.0, 400.0), (20.0, 420.0)),
+            // 靠近右边或下边时换到左侧或上方
+            ((990.0, 300.0), (770.0, 320.0)),
+            ((400.0, 790.0), (420.0, 494.0)),
+            ((1000.0, 0.0), (780.0, 20.0)),
+            ((0.0, 800.0), (20.0, 504.0)),
+            ((1000.0, 800.0), (780.0, 504.0)),
+        ];
+        for (cursor, position) in cases {
+            let magnifier = Magnifier::calculate(cursor, screen);
+            assert_eq!((magnifier.x, magnifier.y), position, "{cursor:?}");
+            assert_eq!(
+                (magnifier.width, magnifier.height),
+                (
+                    MAGNIFIER_PIXELS * MAGNIFIER_ZOOM,
+                    MAGNIFIER_PIXELS * MAGNIFIER_ZOOM + MAGNIFIER_INFO_HEIGHT
+                )
+            );
+            assert!(
+                magnifier.x >= SCREEN_MARGIN
+                    && magnifier.x + magnifier.width <= screen.width - SCREEN_MARGIN
+            );
+            assert!(
+                magnifier.y >= SCREEN_MARGIN
+                    && magnifier.y + magnifier.height <= screen.height - SCREEN_MARGIN
+            );
+        }
+    }
+
+    #[test]
+    fn magnifier_keeps_margin_on_small_screen() {
+        // 两侧都放不下时贴着屏幕左上边距
+        let magnifier = Magnifier::calculate((75.0, 75.0), LogicalSize::new(150.0, 150.0));
+        assert_eq!((magnifier.x, magnifier.y), (SCREEN_MARGIN, SCREEN_MARGIN));
     }
 }
@@ -1,4 +1,3 @@
-use freya::prelude::{Readable, Signal, Writable};
 use winit::window::CursorIcon;
 
 use crate::types::app_state::ResizeHandle;
@@ -12,51 +11,22 @@ pub fn resize_handle_to_cursor(handle: ResizeHandle) -> CursorIcon {
     }
 }
 
+use crate::coords::{PhysicalSize, ScaleFactor};
 use crate::geometry::{get_resize_handle, point_in_rect};
+use crate::shapes::style::shows_property_bar;
 use crate::types::{
     app_state::AppState,
     drawing::{DrawingShape, DrawingTool},
-    ui::{Selection, Toolbar},
+    ui::{PropertyBar, Selection, Toolbar},
 };
 
 /// 光标管理器
 pub struct CursorManager;
 
 impl CursorManager {
-    pub fn get_cursor_icon_with_cache(
-        mouse_pos: (f32, f32),
-        app_state: AppState,
-        current_selection: Option<Selection>,
-        current_tool: DrawingTool,
-        drawing_shapes: &[DrawingShape],
-        selected_shape_index: Option<usize>,
-        resize_handle: Option<ResizeHandle>,
-        shape_resize_handle: Option<ResizeHandle>,
-        screen_size: (u32, u32),
-        last_cursor: &mut Signal<CursorIcon>, // 传入可变引用
-    ) -> CursorIcon {
-        let new_cursor = Self::get_cursor_icon(
-            mouse_pos,
-            app_state,
-            current_selection,
-            current_tool,
-            drawing_shapes,
-            selected_shape_index,
-            resize_handle,
-            shape_resize_handle,
-            screen_size,
-        );
-
-        // 只有当光标改变时才更新状态
-        if new_cursor != *last_cursor.read() {
-            last_cursor.set(new_cursor);
-        }
-
-        new_cursor
-    }
-
     /// 获取当前应该显示的光标图标
-    fn get_cursor_icon(
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_cursor_icon(
         mouse_pos: (f32, f32),
         app_state: AppState,
         current_selection: Option<Selection>,
@@ -65,7 +35,8 @@ impl CursorManager {
         selected_shape_index: Option<usize>,
         resize_handle: Option<ResizeHandle>,
         shape_resize_handle: Option<ResizeHandle>,
-        screen_size: (u32, u32),
+        screen_size: PhysicalSize,
+        ui_scale: ScaleFactor,
     ) -> CursorIcon {
         let (x, y) = mouse_pos;
 
@@ -96,6 +67,7 @@ impl CursorManager {
                 drawing_shapes,
                 selected_shape_index,
                 screen_size,
+                ui_scale,
             ),
         }
     }
@@ -107,11 +79,15 @@ impl CursorManager {
             DrawingTool::Circle => CursorIcon::Crosshair,
             DrawingTool::Arrow => CursorIcon::Crosshair,
             DrawingTool::Brush => CursorIcon::Crosshair,
+            DrawingTool::Text => CursorIcon::Text,
+            DrawingTool::Mosaic => CursorIcon::Crosshair,
+            DrawingTool::Blur => CursorIcon::Crosshair,
             DrawingTool::None => CursorIcon::Default,
         }
     }
 
     /// 获取空闲状态时的光标
+    #[allow(clippy::too_many_arguments)]
     fn get_idle_cursor(
         x: f32,
         y: f32,
@@ -119,20 +95,29 @@ impl CursorManager {
         current_tool: DrawingTool,
         drawing_shapes: &[DrawingShape],
         selected_shape_index: Option<usize>,
-        screen_size: (u32, u32),
+        screen_size: PhysicalSize,
+        ui_scale: ScaleFactor,
     ) -> CursorIcon {
         if let Some(selection) = current_selection {
-            let toolbar = Toolbar::calculate(&selection, screen_size);
+            // 工具栏和属性栏按逻辑像素布局
+            let ui_selection = selection.to_logical(ui_scale);
+            let ui_screen = screen_size.to_logical(ui_scale);
+            let (ui_x, ui_y) = (x / ui_scale.get(), y / ui_scale.get());
+            let toolbar = Toolbar::calculate(&ui_selection, ui_screen);
+            let selected_shape = selected_shape_index.and_then(|index| drawing_shapes.get(index));
+            let on_property_bar = shows_property_bar(current_tool, selected_shape)
+                && PropertyBar::calculate(&toolbar, &ui_selection, ui_screen)
+                    .contains_point(ui_x, ui_y);
 
-            // 1. 优先检查工具栏
-            if toolbar.contains_point(x, y) {
+            // 1. 优先检查工具栏和属性栏
+            if toolbar.contains_point(ui_x, ui_y) || on_property_bar {
                 CursorIcon::Pointer
             }
             // 2. 只有在没有绘图工具时才检查选择框调整手柄
             else if current_tool == DrawingTool::None
-                && get_resize_handle(x, y, &selection).is_some()
+                && get_resize_handle(x, y, &selection, ui_scale).is_some()
             {
-                let handle = get_resize_handle(x, y, &selection).unwrap();
+                let handle = get_resize_handle(x, y, &selection, ui_scale).unwrap();
                 resize_handle_to_cursor(handle)
             }
             // 3. 检查选择框内部
@@ -143,6 +128,7 @@ impl CursorManager {
                     current_tool,
                     drawing_shapes,
                     selected_shape_index,
+                    ui_scale,
                 )
             } else {
                 // 在选择框外部 - 始终显示禁止光标
@@ -160,11 +146,12 @@ impl CursorManager {
         current_tool: DrawingTool,
         drawing_shapes: &[DrawingShape],
         selected_shape_index: Option<usize>,
+        ui_scale: ScaleFactor,
     ) -> CursorIcon {
         // 优先检查是否有选中的图形的调整大小手柄
         if let Some(selected_idx) = selected_shape_index {
             if let Some(shape) = drawing_shapes.get(selected_idx) {
-                if let Some(handle) = shape.get_resize_handle(x, y) {
+                if let Some(handle) = shape.get_resize_handle(x, y, ui_scale) {
                     return resize_handle_to_cursor(handle);
                 }
             }
@@ -174,7 +161,7 @@ impl CursorManager {
         for shape in drawing_shapes.iter().rev() {
             if shape.contains_point(x, y) {
                 // 再次检查确保不是在调整手柄上
-                if shape.get_resize_handle(x, y).is_none() {
+                if shape.get_resize_handle(x, y, ui_scale).is_none() {
                     return CursorIcon::Pointer;
                 }
             }
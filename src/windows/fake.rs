@@ -0,0 +1,19 @@
+use super::{WindowError, WindowGeometry, WindowSource};
+
+/// 返回固定窗口列表的窗口来源，不依赖桌面环境
+pub struct FakeWindows {
+    // 从最上层到最下层
+    windows: Vec<WindowGeometry>,
+}
+
+impl FakeWindows {
+    pub fn new(windows: Vec<WindowGeometry>) -> Self {
+        Self { windows }
+    }
+}
+
+impl WindowSource for FakeWindows {
+    fn windows(&self) -> Result<Vec<WindowGeometry>, WindowError> {
+        Ok(self.windows.clone())
+    }
+}
@@ -0,0 +1,199 @@
+pub mod fake;
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+use std::fmt;
+
+use crate::{
+    capture::{CAPTURE_FILE_ENV, VirtualDesktop},
+    constants::constants::MIN_SELECTION_SIZE,
+    coords::{PhysicalPoint, PhysicalSize},
+    geometry::point_in_rect,
+    types::ui::Selection,
+};
+
+// 顶层窗口在桌面上的位置和大小（物理像素，与 X11 根窗口坐标相同），包含边框
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub origin: PhysicalPoint,
+    pub size: PhysicalSize,
+}
+
+#[derive(Debug)]
+pub enum WindowError {
+    // 当前平台或会话无法枚举窗口（例如 Wayland）
+    Unsupported,
+    Backend(String),
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowError::Unsupported => write!(f, "当前环境不支持窗口识别"),
+            WindowError::Backend(message) => write!(f, "无法获取窗口列表: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for WindowError {}
+
+/// 列出可见的顶层窗口，按叠放顺序从最上层到最下层
+pub trait WindowSource {
+    fn windows(&self) -> Result<Vec<WindowGeometry>, WindowError>;
+}
+
+// 从文件读取截图时窗口位置与截图无关，不做识别
+pub fn default_source() -> Result<Box<dyn WindowSource>, WindowError> {
+    if std::env::var_os(CAPTURE_FILE_ENV).is_some() {
+        return Err(WindowError::Unsupported);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(x11::X11Windows::connect()?))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(WindowError::Unsupported)
+    }
+}
+
+// 窗口在截图上的区域（物理像素），裁剪到截图范围内
+// 完全在屏幕外或裁剪后小于最小选择尺寸的窗口被丢弃，顺序保持从上到下
+pub fn window_regions(windows: &[WindowGeometry], desktop: &VirtualDesktop) -> Vec<Selection> {
+    let screen = desktop.physical_size();
+    windows
+        .iter()
+        .filter_map(|window| {
+            let rect = desktop.physical_canvas_rect(window.origin, window.size);
+            let left = rect.left.max(0.0);
+            let top = rect.top.max(0.0);
+            let right = rect.right.min(screen.width_f32());
+            let bottom = rect.bottom.min(screen.height_f32());
+            (right - left >= MIN_SELECTION_SIZE && bottom - top >= MIN_SELECTION_SIZE).then_some(
+                Selection {
+                    start: (left, top),
+                    end: (right, bottom),
+                },
+            )
+        })
+        .collect()
+}
+
+// 截图时的窗口区域；无法识别窗口时返回空列表，只能手动框选
+pub fn detect_windows(desktop: &VirtualDesktop) -> Vec<Selection> {
+    let result = default_source().and_then(|source| source.windows());
+    match result {
+        Ok(windows) => window_regions(&windows, desktop),
+        Err(WindowError::Unsupported) => Vec::new(),
+        Err(err) => {
+            eprintln!("{err}");
+            Vec::new()
+        }
+    }
+}
+
+// 鼠标下最上层的窗口
+pub fn window_at(regions: &[Selection], pos: (f32, f32)) -> Option<Selection> {
+    regions
+        .iter()
+        .find(|region| point_in_rect(pos.0, pos.1, region))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fake::FakeWindows, *};
+    use crate::{
+        capture::DisplayGeometry,
+        coords::{LogicalPoint, LogicalSize, ScaleFactor},
+    };
+
+    fn window(x: f32, y: f32, width: u32, height: u32) -> WindowGeometry {
+        WindowGeometry {
+            origin: PhysicalPoint::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    fn display(x: f32, y: f32, width: f32, height: f32, scale: f32) -> DisplayGeometry {
+        DisplayGeometry {
+            id: 0,
+            origin: LogicalPoint::new(x, y),
+            size: LogicalSize::new(width, height),
+            scale: ScaleFactor::new(scale),
+        }
+    }
+
+    fn regions(windows: Vec<WindowGeometry>, desktop: &VirtualDesktop) -> Vec<Selection> {
+        let source = FakeWindows::new(windows);
+        window_regions(&source.windows().unwrap(), desktop)
+    }
+
+    fn bounds(regions: &[Selection]) -> Vec<(f32, f32, f32, f32)> {
+        regions.iter().map(Selection::bounds).collect()
+    }
+
+    #[test]
+    fn windows_are_clipped_to_desktop() {
+        // 2 倍缩放的 400x300 显示器，画布为 800x600 物理像素
+        let desktop = VirtualDesktop::new(vec![display(0.0, 0.0, 400.0, 300.0, 2.0)]).unwrap();
+        let regions = regions(
+            vec![
+                window(100.0, 100.0, 300, 200),
+                // 部分在屏幕外
+                window(-50.0, -40.0, 200, 200),
+                window(700.0, 500.0, 300, 300),
+                // 完全在屏幕外
+                window(900.0, 0.0, 100, 100),
+                window(-200.0, 0.0, 100, 100),
+                // 太小或裁剪后太窄
+                window(400.0, 400.0, 9, 50),
+                window(795.0, 0.0, 100, 100),
+                window(0.0, 0.0, 800, 600),
+            ],
+            &desktop,
+        );
+        // 窗口坐标已经是物理像素，不再乘以缩放比例
+        assert_eq!(
+            bounds(&regions),
+            [
+                (100.0, 100.0, 400.0, 300.0),
+                (0.0, 0.0, 150.0, 160.0),
+                (700.0, 500.0, 800.0, 600.0),
+                (0.0, 0.0, 800.0, 600.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn windows_are_relative_to_desktop_origin() {
+        // 左侧显示器在原点左边，画布原点为桌面的左上角
+        let desktop = VirtualDesktop::new(vec![
+            display(-300.0, 0.0, 300.0, 200.0, 1.0),
+            display(0.0, 0.0, 400.0, 300.0, 1.0),
+        ])
+        .unwrap();
+        let regions = regions(
+            vec![window(-300.0, 0.0, 300, 200), window(10.0, 20.0, 100, 50)],
+            &desktop,
+        );
+        assert_eq!(
+            bounds(&regions),
+            [(0.0, 0.0, 300.0, 200.0), (310.0, 20.0, 410.0, 70.0)]
+        );
+    }
+
+    #[test]
+    fn window_at_picks_topmost() {
+        let desktop = VirtualDesktop::new(vec![display(0.0, 0.0, 800.0, 600.0, 1.0)]).unwrap();
+        let regions = regions(
+            vec![window(100.0, 100.0, 200, 100), window(50.0, 50.0, 400, 400)],
+            &desktop,
+        );
+        assert_eq!(window_at(&regions, (150.0, 150.0)), Some(regions[0]));
+        assert_eq!(window_at(&regions, (60.0, 60.0)), Some(regions[1]));
+        assert_eq!(window_at(&regions, (300.0, 200.0)), Some(regions[0]));
+        assert_eq!(window_at(&regions, (700.0, 500.0)), None);
+        assert_eq!(window_at(&[], (150.0, 150.0)), None);
+    }
+}
@@ -0,0 +1,94 @@
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ConnectionExt as _, MapState, Window, WindowClass},
+    rust_connection::RustConnection,
+};
+
+use super::{WindowError, WindowGeometry, WindowSource};
+use crate::coords::{PhysicalPoint, PhysicalSize};
+
+/// 通过根窗口的子窗口列表枚举顶层窗口，有窗口管理器时为带装饰的外框窗口
+pub struct X11Windows {
+    connection: RustConnection,
+    root: Window,
+}
+
+impl X11Windows {
+    pub fn connect() -> Result<Self, WindowError> {
+        // 纯 Wayland 会话没有 X 服务器，无法获取其他程序的窗口
+        if std::env::var_os("DISPLAY").is_none() {
+            return Err(WindowError::Unsupported);
+        }
+        let (connection, screen) =
+            x11rb::connect(None).map_err(|err| WindowError::Backend(err.to_string()))?;
+        let root = connection.setup().roots[screen].root;
+        Ok(Self { connection, root })
+    }
+
+    // 可见窗口的位置和大小（根窗口坐标，包含边框），不可见的窗口返回 None
+    // X11 不做缩放，根窗口坐标就是物理像素
+    fn geometry(&self, window: Window) -> Result<Option<WindowGeometry>, WindowError> {
+        let reply_error = |err: x11rb::errors::ReplyError| WindowError::Backend(err.to_string());
+        let connection_error =
+            |err: x11rb::errors::ConnectionError| WindowError::Backend(err.to_string());
+
+        let attributes = self
+            .connection
+            .get_window_attributes(window)
+            .map_err(connection_error)?
+            .reply();
+        // 枚举过程中窗口可能已经关闭
+        let Ok(attributes) = attributes else {
+            return Ok(None);
+        };
+        if attributes.map_state != MapState::VIEWABLE || attributes.class == WindowClass::INPUT_ONLY
+        {
+            return Ok(None);
+        }
+
+        let geometry = self
+            .connection
+            .get_geometry(window)
+            .map_err(connection_error)?
+            .reply()
+            .map_err(reply_error)?;
+        let position = self
+            .connection
+            .translate_coordinates(window, self.root, 0, 0)
+            .map_err(connection_error)?
+            .reply()
+            .map_err(reply_error)?;
+
+        let border = u32::from(geometry.border_width);
+        Ok(Some(WindowGeometry {
+            origin: PhysicalPoint::new(
+                f32::from(position.dst_x) - border as f32,
+                f32::from(position.dst_y) - border as f32,
+            ),
+            size: PhysicalSize::new(
+                u32::from(geometry.width) + border * 2,
+                u32::from(geometry.height) + border * 2,
+            ),
+        }))
+    }
+}
+
+impl WindowSource for X11Windows {
+    fn windows(&self) -> Result<Vec<WindowGeometry>, WindowError> {
+        let tree = self
+            .connection
+            .query_tree(self.root)
+            .map_err(|err| WindowError::Backend(err.to_string()))?
+            .reply()
+            .map_err(|err| WindowError::Backend(err.to_string()))?;
+
+        // QueryTree 按叠放顺序从下到上返回子窗口
+        let mut windows = Vec::new();
+        for &window in tree.children.iter().rev() {
+            if let Some(geometry) = self.geometry(window)? {
+                windows.push(geometry);
+            }
+        }
+        Ok(windows)
+    }
+}